@@ -0,0 +1,49 @@
+#import bevy_sprite::mesh2d_types
+#import bevy_sprite::mesh2d_view_bindings
+
+struct PieceOutline {
+    color: vec4<f32>,
+    // The piece's cell of the atlas, from its top left to its bottom right
+    rect: vec4<f32>,
+    // How far the outline reaches past the piece, as a part of its size
+    width: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> outline: PieceOutline;
+@group(1) @binding(1)
+var atlas_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var atlas_sampler: sampler;
+
+struct FragmentInput {
+    #import bevy_sprite::mesh2d_vertex_output
+};
+
+// How much of the piece covers a point of its cell, from 0 to 1 across it
+fn coverage(point: vec2<f32>) -> f32 {
+    if (any(point < vec2<f32>(0.0)) || any(point > vec2<f32>(1.0))) {
+        return 0.0;
+    }
+    let uv = mix(outline.rect.xy, outline.rect.zw, point);
+    return textureSampleLevel(atlas_texture, atlas_sampler, uv, 0.0).a;
+}
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    // The quad is larger than the piece by the outline's width on each side
+    let point = (in.uv - 0.5) * (1.0 + 2.0 * outline.width) + 0.5;
+
+    // Solid close to the piece and fading further out, for a glow
+    var strength = 0.0;
+    for (var i = 0; i < 16; i = i + 1) {
+        let angle = f32(i) * 0.39269908;
+        let direction = vec2<f32>(cos(angle), sin(angle)) * outline.width;
+        strength = max(strength, coverage(point + direction * 0.5));
+        strength = max(strength, coverage(point + direction) * 0.5);
+    }
+
+    // Nothing over the piece itself, which is drawn on top anyway
+    strength = strength * (1.0 - coverage(point));
+    return vec4<f32>(outline.color.rgb, outline.color.a * strength);
+}
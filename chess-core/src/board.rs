@@ -0,0 +1,136 @@
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Resource;
+
+use crate::{get_starting_pieces, BoardPosition, Move, Piece, Player, Square, BOARD_SIZE};
+
+// What stands on each square, indexed by [x][y]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Board(pub [[Option<(Piece, Player)>; BOARD_SIZE as usize]; BOARD_SIZE as usize]);
+
+impl Board {
+    pub fn from_pieces(pieces: &[(Piece, Player, BoardPosition)]) -> Self {
+        let mut board = Self::default();
+
+        for &(piece_type, player, position) in pieces {
+            board.0[position.x as usize][position.y as usize] = Some((piece_type, player));
+        }
+
+        board
+    }
+
+    // Squares off the board are empty
+    pub fn get(&self, square: Square) -> Option<(Piece, Player)> {
+        if !square.is_on_board() {
+            return None;
+        }
+
+        self.0[square.0 as usize][square.1 as usize]
+    }
+
+    // Gives back whatever was captured, for undo_move
+    pub fn apply_move(&mut self, mv: &Move) -> Option<(Piece, Player)> {
+        let moving_piece = self.get(mv.from);
+        let captured_piece = self.get(mv.to);
+
+        if moving_piece.is_none() || !mv.to.is_on_board() {
+            return None;
+        }
+
+        let landing_piece = match (moving_piece, mv.promotion) {
+            (Some((_, player)), Some(promotion)) => Some((promotion, player)),
+            _ => moving_piece,
+        };
+
+        self.0[mv.from.0 as usize][mv.from.1 as usize] = None;
+        self.0[mv.to.0 as usize][mv.to.1 as usize] = landing_piece;
+
+        captured_piece
+    }
+
+    pub fn undo_move(&mut self, mv: &Move, captured_piece: Option<(Piece, Player)>) {
+        let moving_piece = self.get(mv.to);
+
+        if moving_piece.is_none() || !mv.from.is_on_board() {
+            return;
+        }
+
+        // A promoted piece goes back as the pawn it was
+        let moving_piece = match (moving_piece, mv.promotion) {
+            (Some((_, player)), Some(_)) => Some((Piece::Pawn, player)),
+            _ => moving_piece,
+        };
+
+        self.0[mv.from.0 as usize][mv.from.1 as usize] = moving_piece;
+        self.0[mv.to.0 as usize][mv.to.1 as usize] = captured_piece;
+    }
+
+    // White's material minus Black's, in pawns
+    pub fn material_balance(&self) -> i32 {
+        self.pieces()
+            .map(|(piece_type, player, _)| match player {
+                Player::White => piece_type.value(),
+                Player::Black => -piece_type.value(),
+            })
+            .sum()
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = (Piece, Player, BoardPosition)> + '_ {
+        (0..BOARD_SIZE).flat_map(move |x| {
+            (0..BOARD_SIZE).filter_map(move |y| {
+                self.get(Square(x, y))
+                    .map(|(piece_type, player)| (piece_type, player, BoardPosition::new(x, y)))
+            })
+        })
+    }
+}
+
+pub fn get_board_after_moves(moves: &[Move]) -> Board {
+    let mut board = Board::from_pieces(&get_starting_pieces());
+
+    for mv in moves {
+        board.apply_move(mv);
+    }
+
+    board
+}
+
+// Moves since the last capture or pawn move, for the fifty-move rule
+pub fn get_halfmove_clock(moves: &[Move]) -> usize {
+    let mut board = Board::from_pieces(&get_starting_pieces());
+    let mut halfmove_clock = 0;
+
+    for mv in moves {
+        let is_capture = board.get(mv.to).is_some();
+        let is_pawn_move = matches!(board.get(mv.from), Some((Piece::Pawn, _)));
+
+        if is_capture || is_pawn_move {
+            halfmove_clock = 0;
+        } else {
+            halfmove_clock += 1;
+        }
+
+        board.apply_move(mv);
+    }
+
+    halfmove_clock
+}
+
+// How often the position after the moves has come up, counting itself.
+// Positions only match with the same player to move
+pub fn get_repetition_count(moves: &[Move]) -> usize {
+    let mut board = Board::from_pieces(&get_starting_pieces());
+    let mut positions = vec![board];
+
+    for mv in moves {
+        board.apply_move(mv);
+        positions.push(board);
+    }
+
+    positions
+        .iter()
+        .rev()
+        .step_by(2)
+        .filter(|position| **position == board)
+        .count()
+}
@@ -0,0 +1,190 @@
+use crate::{get_all_legal_moves, is_king_attacked, Board, Move, Piece, Player};
+
+// Worth more than any amount of material
+const MATE_SCORE: i32 = 100_000;
+const INFINITY: i32 = 2 * MATE_SCORE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EngineConfig {
+    // Plies searched before evaluating
+    pub depth: u32,
+    // Centipawns for each step a piece other than the king stands closer
+    // to the middle of the board
+    pub center_weight: i32,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            center_weight: 10,
+        }
+    }
+}
+
+// In centipawns, from the point of view of the player
+pub fn evaluate(board: &Board, player: Player, config: &EngineConfig) -> i32 {
+    board
+        .pieces()
+        .map(|(piece_type, owner, position)| {
+            // 0 on the edge of the board up to 3 in the middle four squares
+            let centrality = 3 - (2 * position.x - 7).abs().max((2 * position.y - 7).abs()) / 2;
+            let mut score = piece_type.value() * 100;
+            if piece_type != Piece::King {
+                score += config.center_weight * centrality;
+            }
+
+            if owner == player {
+                score
+            } else {
+                -score
+            }
+        })
+        .sum()
+}
+
+// In centipawns for the player to move, searched to the configured depth.
+// Being mated scores below any material
+pub fn get_score(board: &Board, player: Player, config: &EngineConfig) -> i32 {
+    search(board, player, config.depth, -INFINITY, INFINITY, config)
+}
+
+// None when the player has no legal moves left
+pub fn get_best_move(board: &Board, player: Player, config: &EngineConfig) -> Option<Move> {
+    let mut best_move = None;
+    let mut alpha = -INFINITY;
+
+    for mv in get_ordered_moves(board, player) {
+        let mut board_after = *board;
+        board_after.apply_move(&mv);
+
+        let score = -search(
+            &board_after,
+            player.opponent(),
+            config.depth.saturating_sub(1),
+            -INFINITY,
+            -alpha,
+            config,
+        );
+
+        if best_move.is_none() || score > alpha {
+            alpha = score;
+            best_move = Some(mv);
+        }
+    }
+
+    best_move
+}
+
+// Up to count of the player's moves with their scores, best first. Each is
+// searched in full, so the scores compare, unlike in get_best_move
+pub fn get_candidate_moves(
+    board: &Board,
+    player: Player,
+    config: &EngineConfig,
+    count: usize,
+) -> Vec<(Move, i32)> {
+    let mut candidates: Vec<_> = get_ordered_moves(board, player)
+        .into_iter()
+        .map(|mv| {
+            let mut board_after = *board;
+            board_after.apply_move(&mv);
+
+            let score = -search(
+                &board_after,
+                player.opponent(),
+                config.depth.saturating_sub(1),
+                -INFINITY,
+                INFINITY,
+                config,
+            );
+            (mv, score)
+        })
+        .collect();
+
+    candidates.sort_by_key(|(_, score)| -score);
+    candidates.truncate(count);
+    candidates
+}
+
+// The line the engine expects, each side playing its best move in turn,
+// for up to the given number of plies
+pub fn get_principal_variation(
+    board: &Board,
+    player: Player,
+    config: &EngineConfig,
+    plies: usize,
+) -> Vec<Move> {
+    let mut board = *board;
+    let mut player = player;
+    let mut line = Vec::new();
+
+    while line.len() < plies {
+        let Some(mv) = get_best_move(&board, player, config) else {
+            break;
+        };
+
+        board.apply_move(&mv);
+        line.push(mv);
+        player = player.opponent();
+    }
+
+    line
+}
+
+// Negamax with alpha-beta pruning
+fn search(
+    board: &Board,
+    player: Player,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    config: &EngineConfig,
+) -> i32 {
+    if depth == 0 {
+        return evaluate(board, player, config);
+    }
+
+    let moves = get_ordered_moves(board, player);
+
+    if moves.is_empty() {
+        // Being mated with more depth left means being mated sooner
+        return if is_king_attacked(board, player) {
+            -MATE_SCORE - depth as i32
+        } else {
+            0
+        };
+    }
+
+    for mv in moves {
+        let mut board_after = *board;
+        board_after.apply_move(&mv);
+
+        let score = -search(
+            &board_after,
+            player.opponent(),
+            depth - 1,
+            -beta,
+            -alpha,
+            config,
+        );
+
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+
+    alpha
+}
+
+// Captures of the most valuable pieces first, which prunes the most
+fn get_ordered_moves(board: &Board, player: Player) -> Vec<Move> {
+    let mut moves = get_all_legal_moves(board, player);
+    moves.sort_by_key(|mv| {
+        -board
+            .get(mv.to)
+            .map_or(0, |(piece_type, _)| piece_type.value())
+    });
+    moves
+}
@@ -0,0 +1,157 @@
+// Signs that a player may have had outside help, from the moves and move
+// times of a finished game. They are only signs: strong players find the
+// engine's moves too, and forced lines leave little choice, so a report
+// is for a person to weigh, not a verdict
+
+use crate::{
+    get_all_legal_moves, get_board_after_moves, get_candidate_moves, EngineConfig, Move, Player,
+};
+
+// Most players know their openings by heart, so these say little
+const OPENING_PLIES: usize = 10;
+// Too few moves to tell anything from
+const MIN_JUDGED_MOVES: usize = 10;
+// Flagged at or above this share of the engine's first choices
+const ENGINE_MATCH_WARNING: f64 = 0.9;
+// Flagged below this spread of think times, as a part of their average.
+// People take longer over hard moves than easy ones; relayed moves don't
+const TIME_SPREAD_WARNING: f64 = 0.2;
+// Missing a mate would otherwise outweigh the rest of the game
+const LOSS_CAP: i32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FairPlayFlag {
+    EngineCorrelation,
+    UniformMoveTimes,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FairPlayReport {
+    pub player: Player,
+    // Moves after the opening where there was more than one to choose from
+    pub judged_moves: usize,
+    pub engine_matches: usize,
+    // Centipawns given up against the engine's choice, on average
+    pub average_loss: f64,
+    // In seconds, over the judged moves with a time
+    pub average_think_time: f64,
+    pub think_time_spread: f64,
+    pub flags: Vec<FairPlayFlag>,
+}
+
+impl FairPlayReport {
+    pub fn engine_match_rate(&self) -> f64 {
+        if self.judged_moves == 0 {
+            0.0
+        } else {
+            self.engine_matches as f64 / self.judged_moves as f64
+        }
+    }
+}
+
+// White's report, then Black's. Move times are counted from the start of
+// the game, as MoveHistory keeps them, and may be missing
+pub fn get_fair_play_reports(
+    moves: &[Move],
+    move_times: &[f64],
+    config: &EngineConfig,
+) -> [FairPlayReport; 2] {
+    let mut board = get_board_after_moves(&[]);
+    let mut losses = [Vec::new(), Vec::new()];
+    let mut matches = [0, 0];
+    let mut think_times = [Vec::new(), Vec::new()];
+
+    for (ply, mv) in moves.iter().enumerate() {
+        let (player, side) = if ply.is_multiple_of(2) {
+            (Player::White, 0)
+        } else {
+            (Player::Black, 1)
+        };
+
+        let judged = ply >= OPENING_PLIES && get_all_legal_moves(&board, player).len() > 1;
+        if judged {
+            let candidates = get_candidate_moves(&board, player, config, usize::MAX);
+            let best_score = candidates[0].1;
+            let score = candidates
+                .iter()
+                .find(|(candidate, _)| candidate == mv)
+                .map_or(best_score, |(_, score)| *score);
+
+            // Moves as good as the engine's choice count as it
+            if score == best_score {
+                matches[side] += 1;
+            }
+            losses[side].push((best_score - score).min(LOSS_CAP));
+
+            if let Some(&time) = move_times.get(ply) {
+                let previous = ply.checked_sub(1).map_or(0.0, |last| move_times[last]);
+                think_times[side].push((time - previous).max(0.0));
+            }
+        }
+
+        board.apply_move(mv);
+    }
+
+    [Player::White, Player::Black].map(|player| {
+        let side = if player == Player::White { 0 } else { 1 };
+        get_report(player, &losses[side], matches[side], &think_times[side])
+    })
+}
+
+fn get_report(
+    player: Player,
+    losses: &[i32],
+    matches: usize,
+    think_times: &[f64],
+) -> FairPlayReport {
+    let judged_moves = losses.len();
+    let average_loss = if judged_moves == 0 {
+        0.0
+    } else {
+        losses.iter().sum::<i32>() as f64 / judged_moves as f64
+    };
+
+    let (average_think_time, think_time_spread) = get_spread(think_times);
+
+    let mut report = FairPlayReport {
+        player,
+        judged_moves,
+        engine_matches: matches,
+        average_loss,
+        average_think_time,
+        think_time_spread,
+        flags: Vec::new(),
+    };
+
+    if judged_moves >= MIN_JUDGED_MOVES && report.engine_match_rate() >= ENGINE_MATCH_WARNING {
+        report.flags.push(FairPlayFlag::EngineCorrelation);
+    }
+    if think_times.len() >= MIN_JUDGED_MOVES
+        && average_think_time > 0.0
+        && think_time_spread < TIME_SPREAD_WARNING
+    {
+        report.flags.push(FairPlayFlag::UniformMoveTimes);
+    }
+
+    report
+}
+
+// The average, and the standard deviation as a part of it
+fn get_spread(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let count = values.len() as f64;
+    let average = values.iter().sum::<f64>() / count;
+    if average <= 0.0 {
+        return (average, 0.0);
+    }
+
+    let variance = values
+        .iter()
+        .map(|value| (value - average).powi(2))
+        .sum::<f64>()
+        / count;
+    (average, variance.sqrt() / average)
+}
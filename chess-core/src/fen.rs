@@ -0,0 +1,68 @@
+use crate::{get_board_after_moves, get_halfmove_clock, Board, Move, Player, Square, BOARD_SIZE};
+
+pub fn get_fen(moves: &[Move]) -> String {
+    let board = get_board_after_moves(moves);
+    let halfmove_clock = get_halfmove_clock(moves);
+
+    let placement = get_placement(&board);
+
+    let side_to_move = if moves.len().is_multiple_of(2) {
+        "w"
+    } else {
+        "b"
+    };
+
+    // The rules have no castling, so neither side has the right to, and a
+    // FEN claiming it would let an analysis board castle where this can't
+    format!(
+        "{placement} {side_to_move} - - {halfmove_clock} {}",
+        moves.len() / 2 + 1
+    )
+}
+
+// For a position that didn't come from the starting one, so has no
+// castling rights or move counts to speak of
+pub fn get_position_fen(board: &Board, side_to_move: Player) -> String {
+    let side_to_move = match side_to_move {
+        Player::White => "w",
+        Player::Black => "b",
+    };
+
+    format!("{} {side_to_move} - - 0 1", get_placement(board))
+}
+
+// The first field of a FEN, rank 8 first
+fn get_placement(board: &Board) -> String {
+    let mut placement = String::new();
+
+    for y in (0..BOARD_SIZE).rev() {
+        let mut empty_squares = 0;
+
+        for x in 0..BOARD_SIZE {
+            if let Some((piece_type, player)) = board.get(Square(x, y)) {
+                if empty_squares > 0 {
+                    placement.push_str(&empty_squares.to_string());
+                    empty_squares = 0;
+                }
+
+                let letter = piece_type.letter();
+                placement.push(match player {
+                    Player::White => letter,
+                    Player::Black => letter.to_ascii_lowercase(),
+                });
+            } else {
+                empty_squares += 1;
+            }
+        }
+
+        if empty_squares > 0 {
+            placement.push_str(&empty_squares.to_string());
+        }
+
+        if y > 0 {
+            placement.push('/');
+        }
+    }
+
+    placement
+}
@@ -0,0 +1,228 @@
+// Board representation and rules of chess, without any rendering or input.
+// The `bevy` feature makes the board types usable as Bevy components.
+
+use std::fmt;
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Component;
+#[cfg(feature = "bevy")]
+use bevy_reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
+mod board;
+mod engine;
+mod fairplay;
+mod fen;
+mod mate;
+mod maze;
+mod moves;
+mod performance;
+mod pgn;
+mod puzzle;
+mod random;
+mod repertoire;
+mod result;
+mod safety;
+
+pub use board::{get_board_after_moves, get_halfmove_clock, get_repetition_count, Board};
+pub use engine::{
+    evaluate, get_best_move, get_candidate_moves, get_principal_variation, get_score, EngineConfig,
+};
+pub use fairplay::{get_fair_play_reports, FairPlayFlag, FairPlayReport};
+pub use fen::{get_fen, get_position_fen};
+pub use mate::{find_mate, MateTree};
+pub use maze::{get_random_maze, Maze, MAZE_PIECES};
+pub use moves::{
+    get_all_legal_moves, get_attack_map, get_attacked_squares, get_legal_moves, get_possible_moves,
+    is_king_attacked, is_promotion, PROMOTION_PIECES,
+};
+pub use performance::{get_accuracy, get_performance_rating};
+pub use pgn::{
+    get_armageddon_pgn, get_nag_symbol, get_pgn, get_pgn_with_clocks, get_san, parse_clock_comment,
+    read_pgn, PgnGame, PgnMove, PgnShape,
+};
+pub use puzzle::{get_puzzle, is_blunder, Puzzle, BLUNDER_LOSS};
+pub use random::{get_random_position, parse_material};
+pub use repertoire::Repertoire;
+pub use result::{get_game_result, GameResult};
+pub use safety::{get_king_safety, KingSafety};
+
+pub const BOARD_SIZE: i32 = 8;
+
+#[cfg_attr(feature = "bevy", derive(Component, Reflect))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Piece {
+    King,
+    Queen,
+    Knight,
+    Pawn,
+    Bishop,
+    Rook,
+}
+
+impl Piece {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Piece::King => "king",
+            Piece::Queen => "queen",
+            Piece::Knight => "knight",
+            Piece::Pawn => "pawn",
+            Piece::Bishop => "bishop",
+            Piece::Rook => "rook",
+        }
+    }
+
+    // The uppercase letter used in FEN and move notation
+    pub fn letter(&self) -> char {
+        match self {
+            Piece::King => 'K',
+            Piece::Queen => 'Q',
+            Piece::Knight => 'N',
+            Piece::Pawn => 'P',
+            Piece::Bishop => 'B',
+            Piece::Rook => 'R',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'K' => Some(Piece::King),
+            'Q' => Some(Piece::Queen),
+            'N' => Some(Piece::Knight),
+            'P' => Some(Piece::Pawn),
+            'B' => Some(Piece::Bishop),
+            'R' => Some(Piece::Rook),
+            _ => None,
+        }
+    }
+
+    // In pawns, the usual rough count. Kings can't be traded, so have none
+    pub fn value(&self) -> i32 {
+        match self {
+            Piece::King => 0,
+            Piece::Queen => 9,
+            Piece::Knight => 3,
+            Piece::Pawn => 1,
+            Piece::Bishop => 3,
+            Piece::Rook => 5,
+        }
+    }
+}
+
+#[cfg_attr(feature = "bevy", derive(Component, Reflect))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    White,
+    Black,
+}
+
+impl Player {
+    pub fn opponent(&self) -> Self {
+        match self {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Player::White => "white",
+            Player::Black => "black",
+        }
+    }
+}
+
+#[cfg_attr(feature = "bevy", derive(Component, Reflect))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BoardPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl BoardPosition {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn square(&self) -> Square {
+        Square(self.x, self.y)
+    }
+}
+
+// A square by file and rank, both counted from 0, so a1 is Square(0, 0).
+// Saved as a plain (file, rank) pair, the same as before it had a name
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Square(pub i32, pub i32);
+
+impl Square {
+    pub fn file(&self) -> i32 {
+        self.0
+    }
+
+    pub fn rank(&self) -> i32 {
+        self.1
+    }
+
+    pub fn offset(&self, files: i32, ranks: i32) -> Self {
+        Square(self.0 + files, self.1 + ranks)
+    }
+
+    pub fn is_on_board(&self) -> bool {
+        (0..BOARD_SIZE).contains(&self.0) && (0..BOARD_SIZE).contains(&self.1)
+    }
+
+    // Reads a name like "e4"
+    pub fn from_algebraic(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        let (Some(file @ 'a'..='h'), Some(rank @ '1'..='8'), None) =
+            (chars.next(), chars.next(), chars.next())
+        else {
+            return None;
+        };
+
+        Some(Square(file as i32 - 'a' as i32, rank as i32 - '1' as i32))
+    }
+}
+
+// Writes the algebraic name, like "e4". Squares off the board, which a
+// tampered save can hold, have no name and are written as coordinates
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_on_board() {
+            return write!(f, "({}, {})", self.0, self.1);
+        }
+
+        write!(f, "{}{}", (b'a' + self.0 as u8) as char, self.1 + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    // What a pawn reaching the last rank becomes. None leaves it a pawn,
+    // as in games saved before pawns were promoted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion: Option<Piece>,
+}
+
+pub fn get_starting_pieces() -> Vec<(Piece, Player, BoardPosition)> {
+    let mut pieces = Vec::new();
+
+    for (player, back_rank, pawn_rank) in [(Player::White, 0, 1), (Player::Black, 7, 6)] {
+        pieces.push((Piece::King, player, BoardPosition::new(4, back_rank)));
+        pieces.push((Piece::Queen, player, BoardPosition::new(3, back_rank)));
+        pieces.push((Piece::Knight, player, BoardPosition::new(1, back_rank)));
+        pieces.push((Piece::Knight, player, BoardPosition::new(6, back_rank)));
+        pieces.push((Piece::Bishop, player, BoardPosition::new(2, back_rank)));
+        pieces.push((Piece::Bishop, player, BoardPosition::new(5, back_rank)));
+        pieces.push((Piece::Rook, player, BoardPosition::new(0, back_rank)));
+        pieces.push((Piece::Rook, player, BoardPosition::new(7, back_rank)));
+
+        for i in 0..BOARD_SIZE {
+            pieces.push((Piece::Pawn, player, BoardPosition::new(i, pawn_rank)));
+        }
+    }
+
+    pieces
+}
@@ -0,0 +1,103 @@
+use crate::{get_all_legal_moves, get_san, is_king_attacked, Board, Move, Player};
+
+// A forced mate: the attacking move, then for every defence the mate that
+// follows it. No replies means the move itself mates
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MateTree {
+    pub mv: Move,
+    pub replies: Vec<(Move, MateTree)>,
+}
+
+impl MateTree {
+    // Moves by the attacker until mate, against the longest defence
+    pub fn moves_to_mate(&self) -> u32 {
+        1 + self
+            .replies
+            .iter()
+            .map(|(_, tree)| tree.moves_to_mate())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // One move per line in SAN, indented by how deep in the tree it is.
+    // The board is the one the first move is played on
+    pub fn to_text(&self, board: &Board) -> String {
+        let mut text = String::new();
+        self.write_text(board, 0, &mut text);
+        text
+    }
+
+    fn write_text(&self, board: &Board, depth: usize, text: &mut String) {
+        let indent = "  ".repeat(depth);
+        text.push_str(&format!("{indent}{}\n", get_san(board, &self.mv)));
+
+        let mut board_after = *board;
+        board_after.apply_move(&self.mv);
+
+        for (reply, tree) in &self.replies {
+            text.push_str(&format!("{indent}  ...{}\n", get_san(&board_after, reply)));
+
+            let mut board_after_reply = board_after;
+            board_after_reply.apply_move(reply);
+            tree.write_text(&board_after_reply, depth + 2, text);
+        }
+    }
+}
+
+// The quickest forced mate for the player, in at most max_moves of their
+// own moves. Every defence is searched, so this gets slow past three or
+// four moves in busy positions
+pub fn find_mate(board: &Board, player: Player, max_moves: u32) -> Option<MateTree> {
+    (1..=max_moves).find_map(|moves| find_mate_in(board, player, moves))
+}
+
+fn find_mate_in(board: &Board, player: Player, moves: u32) -> Option<MateTree> {
+    let opponent = player.opponent();
+
+    'attacks: for mv in get_checks_first(board, player) {
+        let mut board_after = *board;
+        board_after.apply_move(&mv);
+
+        let defences = get_all_legal_moves(&board_after, opponent);
+        if defences.is_empty() {
+            // Stalemate is no good
+            if is_king_attacked(&board_after, opponent) {
+                return Some(MateTree {
+                    mv,
+                    replies: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        if moves == 1 {
+            continue;
+        }
+
+        let mut replies = Vec::new();
+        for reply in defences {
+            let mut board_after_reply = board_after;
+            board_after_reply.apply_move(&reply);
+
+            let Some(tree) = find_mate(&board_after_reply, player, moves - 1) else {
+                continue 'attacks;
+            };
+            replies.push((reply, tree));
+        }
+
+        return Some(MateTree { mv, replies });
+    }
+
+    None
+}
+
+// Checking moves are the likeliest to lead to mate, so are tried first
+fn get_checks_first(board: &Board, player: Player) -> Vec<Move> {
+    let mut moves = get_all_legal_moves(board, player);
+    moves.sort_by_key(|mv| {
+        let mut board_after = *board;
+        board_after.apply_move(mv);
+        !is_king_attacked(&board_after, player.opponent())
+    });
+    moves
+}
@@ -0,0 +1,178 @@
+// A training game for learning how the pieces move: one piece is steered
+// onto every target square, around obstacles, with the moves it would
+// have in a game. The obstacles are pawns of its own side, so they block
+// it without being taken
+
+use std::collections::VecDeque;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{get_possible_moves, Board, BoardPosition, Piece, Player, Square, BOARD_SIZE};
+
+// Tries before giving up on a layout with enough squares left to reach
+const MAX_ATTEMPTS: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Maze {
+    pub piece: Piece,
+    pub start: Square,
+    pub targets: Vec<Square>,
+    pub obstacles: Vec<Square>,
+}
+
+impl Maze {
+    // There are no kings, and the obstacles are the player's too
+    pub fn get_board(&self, player: Player) -> Board {
+        let mut pieces = vec![(
+            self.piece,
+            player,
+            BoardPosition::new(self.start.0, self.start.1),
+        )];
+        pieces.extend(
+            self.obstacles
+                .iter()
+                .map(|square| (Piece::Pawn, player, BoardPosition::new(square.0, square.1))),
+        );
+
+        Board::from_pieces(&pieces)
+    }
+
+    // The fewest moves from one square to each other the piece can reach,
+    // by square. Unreachable squares are left out
+    pub fn get_distances(&self, from: Square) -> Vec<(Square, usize)> {
+        let mut board = self.get_board(Player::White);
+        board.0[self.start.0 as usize][self.start.1 as usize] = None;
+
+        let mut distances = vec![(from, 0)];
+        let mut queue = VecDeque::from([(from, 0)]);
+
+        while let Some((square, distance)) = queue.pop_front() {
+            let position = BoardPosition::new(square.0, square.1);
+            for next in get_possible_moves(&self.piece, &position, &Player::White, &board) {
+                if distances.iter().all(|(seen, _)| *seen != next) {
+                    distances.push((next, distance + 1));
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+
+    // The fewest moves that visit every target, in whatever order. Tried
+    // in every order, which is few with the handful of targets a maze has
+    pub fn get_par(&self) -> Option<usize> {
+        let mut squares = vec![self.start];
+        squares.extend(&self.targets);
+
+        // distances[i][j] from squares[i] to squares[j]
+        let distances: Vec<Vec<Option<usize>>> = squares
+            .iter()
+            .map(|from| {
+                let reachable = self.get_distances(*from);
+                squares
+                    .iter()
+                    .map(|to| {
+                        reachable
+                            .iter()
+                            .find(|(square, _)| square == to)
+                            .map(|(_, distance)| *distance)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (1..squares.len()).collect();
+        let mut best = None;
+        loop {
+            let length = std::iter::once(0)
+                .chain(order.iter().copied())
+                .zip(order.iter().copied())
+                .map(|(from, to)| distances[from][to])
+                .sum::<Option<usize>>();
+            if let Some(length) = length {
+                best = Some(best.map_or(length, |best: usize| best.min(length)));
+            }
+
+            if !next_permutation(&mut order) {
+                return best;
+            }
+        }
+    }
+}
+
+// Pawns go one way only, so they can't come back for a target they passed
+pub const MAZE_PIECES: [Piece; 5] = [
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Queen,
+    Piece::King,
+];
+
+// A maze whose targets the piece can all reach. None if no such layout
+// turned up, as with more obstacles than the board has room for
+pub fn get_random_maze(
+    piece: Piece,
+    targets: usize,
+    obstacles: usize,
+    rng: &mut impl Rng,
+) -> Option<Maze> {
+    let squares: Vec<Square> = (0..BOARD_SIZE)
+        .flat_map(|x| (0..BOARD_SIZE).map(move |y| Square(x, y)))
+        .collect();
+
+    for _ in 0..MAX_ATTEMPTS {
+        let mut free_squares = squares.clone();
+        free_squares.shuffle(rng);
+
+        let start = free_squares.pop()?;
+        // Off the first and last rank, where pawns can't stand
+        let obstacles: Vec<Square> = free_squares
+            .iter()
+            .copied()
+            .filter(|square| (1..BOARD_SIZE - 1).contains(&square.rank()))
+            .take(obstacles)
+            .collect();
+
+        let mut maze = Maze {
+            piece,
+            start,
+            targets: Vec::new(),
+            obstacles,
+        };
+
+        let mut reachable: Vec<Square> = maze
+            .get_distances(start)
+            .into_iter()
+            .map(|(square, _)| square)
+            .filter(|square| *square != start && !maze.obstacles.contains(square))
+            .collect();
+        if reachable.len() < targets {
+            continue;
+        }
+
+        reachable.shuffle(rng);
+        reachable.truncate(targets);
+        maze.targets = reachable;
+        return Some(maze);
+    }
+
+    None
+}
+
+// Steps to the next order, in the sense of sorting. False once past the
+// last one
+fn next_permutation(order: &mut [usize]) -> bool {
+    let Some(pivot) = (1..order.len()).rev().find(|&i| order[i - 1] < order[i]) else {
+        return false;
+    };
+
+    let swap = (pivot..order.len())
+        .rev()
+        .find(|&i| order[i] > order[pivot - 1])
+        .unwrap();
+    order.swap(pivot - 1, swap);
+    order[pivot..].reverse();
+    true
+}
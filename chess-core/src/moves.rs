@@ -0,0 +1,355 @@
+use tracing::instrument;
+
+use crate::{Board, BoardPosition, Move, Piece, Player, Square, BOARD_SIZE};
+
+pub fn is_king_attacked(board: &Board, player: Player) -> bool {
+    let Some((_, _, king_position)) = board
+        .pieces()
+        .find(|(piece_type, owner, _)| *piece_type == Piece::King && *owner == player)
+    else {
+        return false;
+    };
+
+    board
+        .pieces()
+        .filter(|(_, owner, _)| *owner != player)
+        .any(|(piece_type, owner, position)| {
+            get_possible_moves(&piece_type, &position, &owner, board)
+                .contains(&king_position.square())
+        })
+}
+
+// The squares the piece attacks, including ones its own side stands on,
+// since those are defended. Pawns attack diagonally forward whether or not
+// there is anything to capture there
+pub fn get_attacked_squares(
+    piece_type: &Piece,
+    piece_position: &BoardPosition,
+    piece_player: &Player,
+    board: &Board,
+) -> Vec<Square> {
+    let from = piece_position.square();
+    let straight = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+    let diagonal = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+    let (directions, slides): (Vec<(i32, i32)>, bool) = match piece_type {
+        Piece::King => ([straight, diagonal].concat(), false),
+        Piece::Queen => ([straight, diagonal].concat(), true),
+        Piece::Rook => (straight.to_vec(), true),
+        Piece::Bishop => (diagonal.to_vec(), true),
+        Piece::Knight => (
+            vec![
+                (1, 2),
+                (-1, 2),
+                (2, 1),
+                (2, -1),
+                (1, -2),
+                (-1, -2),
+                (-2, 1),
+                (-2, -1),
+            ],
+            false,
+        ),
+        Piece::Pawn => {
+            let y_modifier = match piece_player {
+                Player::White => 1,
+                Player::Black => -1,
+            };
+            (vec![(1, y_modifier), (-1, y_modifier)], false)
+        }
+    };
+
+    let mut attacked_squares = Vec::new();
+
+    for (dx, dy) in directions {
+        let mut target = from.offset(dx, dy);
+
+        while target.is_on_board() {
+            attacked_squares.push(target);
+
+            if !slides || board.get(target).is_some() {
+                break;
+            }
+            target = target.offset(dx, dy);
+        }
+    }
+
+    attacked_squares
+}
+
+// How many of the player's pieces attack each square, indexed by [x][y]
+// like the board
+pub fn get_attack_map(
+    board: &Board,
+    player: Player,
+) -> [[u8; BOARD_SIZE as usize]; BOARD_SIZE as usize] {
+    let mut attack_map = [[0; BOARD_SIZE as usize]; BOARD_SIZE as usize];
+
+    for (piece_type, owner, position) in board.pieces() {
+        if owner != player {
+            continue;
+        }
+
+        for square in get_attacked_squares(&piece_type, &position, &owner, board) {
+            attack_map[square.0 as usize][square.1 as usize] += 1;
+        }
+    }
+
+    attack_map
+}
+
+// What a pawn on the last rank can become, the likeliest choice first
+pub const PROMOTION_PIECES: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+// Pawns only go forward, so either end of the board is the far one
+pub fn is_promotion(piece_type: Piece, to: Square) -> bool {
+    piece_type == Piece::Pawn && (to.1 == 0 || to.1 == BOARD_SIZE - 1)
+}
+
+// Every legal move of the player's pieces, in board order. A pawn reaching
+// the last rank has a move for each piece it can become
+pub fn get_all_legal_moves(board: &Board, player: Player) -> Vec<Move> {
+    board
+        .pieces()
+        .filter(|(_, owner, _)| *owner == player)
+        .flat_map(|(piece_type, owner, position)| {
+            get_legal_moves(&piece_type, &position, &owner, board)
+                .into_iter()
+                .flat_map(move |to| {
+                    let promotions = if is_promotion(piece_type, to) {
+                        PROMOTION_PIECES.map(Some).to_vec()
+                    } else {
+                        vec![None]
+                    };
+
+                    promotions.into_iter().map(move |promotion| Move {
+                        from: position.square(),
+                        to,
+                        promotion,
+                    })
+                })
+        })
+        .collect()
+}
+
+// The possible moves that don't leave the player's own king attacked
+#[instrument(level = "trace", skip_all, fields(piece = ?piece_type, from = %piece_position.square()))]
+pub fn get_legal_moves(
+    piece_type: &Piece,
+    piece_position: &BoardPosition,
+    piece_player: &Player,
+    board: &Board,
+) -> Vec<Square> {
+    let mut board_after = *board;
+
+    get_possible_moves(piece_type, piece_position, piece_player, board)
+        .into_iter()
+        .filter(|target| {
+            // Whatever a pawn becomes, it stands in the same way
+            let mv = Move {
+                from: piece_position.square(),
+                to: *target,
+                promotion: None,
+            };
+            let captured_piece = board_after.apply_move(&mv);
+            let is_legal = !is_king_attacked(&board_after, *piece_player);
+            board_after.undo_move(&mv, captured_piece);
+
+            is_legal
+        })
+        .collect()
+}
+
+pub fn get_possible_moves(
+    piece_type: &Piece,
+    piece_position: &BoardPosition,
+    piece_player: &Player,
+    board: &Board,
+) -> Vec<Square> {
+    let mut possible_moves = Vec::new();
+    let is_ally =
+        |square: Square| matches!(board.get(square), Some((_, owner)) if owner == *piece_player);
+    let is_enemy =
+        |square: Square| matches!(board.get(square), Some((_, owner)) if owner != *piece_player);
+
+    match piece_type {
+        Piece::King => {
+            for i in 0..8 {
+                let ex_pos = match i {
+                    0 => (1, 0),
+                    1 => (1, 1),
+                    2 => (0, 1),
+                    3 => (-1, 1),
+                    4 => (-1, 0),
+                    5 => (-1, -1),
+                    6 => (0, -1),
+                    7 => (1, -1),
+                    _ => unreachable!(),
+                };
+
+                let target = piece_position.square().offset(ex_pos.0, ex_pos.1);
+
+                if target.is_on_board() && !is_ally(target) {
+                    possible_moves.push(target);
+                }
+            }
+        }
+        Piece::Queen => {
+            for i in 0..8 {
+                let ex_pos = match i {
+                    0 => (0, 1),
+                    1 => (0, -1),
+                    2 => (1, 0),
+                    3 => (-1, 0),
+                    4 => (1, 1),
+                    5 => (1, -1),
+                    6 => (-1, 1),
+                    7 => (-1, -1),
+                    _ => unreachable!(),
+                };
+
+                let mut path = true;
+                let mut chain = 1;
+
+                while path {
+                    let target = piece_position
+                        .square()
+                        .offset(ex_pos.0 * chain, ex_pos.1 * chain);
+
+                    if !is_ally(target) && target.is_on_board() {
+                        possible_moves.push(target);
+
+                        if is_enemy(target) {
+                            path = false;
+                        }
+
+                        chain += 1;
+                    } else {
+                        path = false;
+                    }
+                }
+            }
+        }
+        Piece::Knight => {
+            let targets = [
+                (1, 2),
+                (-1, 2),
+                (2, 1),
+                (2, -1),
+                (1, -2),
+                (-1, -2),
+                (-2, 1),
+                (-2, -1),
+            ];
+
+            for (dx, dy) in targets {
+                let target = piece_position.square().offset(dx, dy);
+
+                if target.is_on_board() && !is_ally(target) {
+                    possible_moves.push(target);
+                }
+            }
+        }
+        Piece::Pawn => {
+            let y_modifier = match piece_player {
+                Player::White => 1,
+                Player::Black => -1,
+            };
+            let starting_y = match piece_player {
+                Player::White => 1,
+                Player::Black => 6,
+            };
+
+            if board
+                .get(piece_position.square().offset(0, y_modifier))
+                .is_none()
+                && piece_position.y < 7
+                && piece_position.y > 0
+            {
+                possible_moves.push(piece_position.square().offset(0, y_modifier));
+            }
+
+            if board
+                .get(piece_position.square().offset(0, 2 * y_modifier))
+                .is_none()
+                && piece_position.y == starting_y
+            {
+                possible_moves.push(piece_position.square().offset(0, 2 * y_modifier));
+            }
+
+            if is_enemy(piece_position.square().offset(1, y_modifier)) {
+                possible_moves.push(piece_position.square().offset(1, y_modifier));
+            }
+
+            if is_enemy(piece_position.square().offset(-1, y_modifier)) {
+                possible_moves.push(piece_position.square().offset(-1, y_modifier));
+            }
+        }
+        Piece::Bishop => {
+            for i in 0..4 {
+                let ex_pos = match i {
+                    0 => (1, 1),
+                    1 => (1, -1),
+                    2 => (-1, 1),
+                    3 => (-1, -1),
+                    _ => unreachable!(),
+                };
+
+                let mut path = true;
+                let mut chain = 1;
+
+                while path {
+                    let target = piece_position
+                        .square()
+                        .offset(ex_pos.0 * chain, ex_pos.1 * chain);
+
+                    if !is_ally(target) && target.is_on_board() {
+                        possible_moves.push(target);
+
+                        if is_enemy(target) {
+                            path = false;
+                        }
+
+                        chain += 1;
+                    } else {
+                        path = false;
+                    }
+                }
+            }
+        }
+        Piece::Rook => {
+            for i in 0..4 {
+                let ex_pos = match i {
+                    0 => (0, 1),
+                    1 => (0, -1),
+                    2 => (1, 0),
+                    3 => (-1, 0),
+                    _ => unreachable!(),
+                };
+
+                let mut path = true;
+                let mut chain = 1;
+
+                while path {
+                    let target = piece_position
+                        .square()
+                        .offset(ex_pos.0 * chain, ex_pos.1 * chain);
+
+                    if !is_ally(target) && target.is_on_board() {
+                        possible_moves.push(target);
+
+                        if is_enemy(target) {
+                            path = false;
+                        }
+
+                        chain += 1;
+                    } else {
+                        path = false;
+                    }
+                }
+            }
+        }
+    }
+
+    possible_moves
+}
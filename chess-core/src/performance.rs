@@ -0,0 +1,34 @@
+// A rough rating for how well a game was played, from how much its moves
+// gave away against the engine and how often they were the engine's own
+// choice. It follows rules of thumb rather than a fit to rated games, so
+// it compares games with each other better than it guesses a rating
+
+use crate::FairPlayReport;
+
+const MIN_RATING: f64 = 400.0;
+const MAX_RATING: f64 = 3000.0;
+// Fewer judged moves than this say too little
+const MIN_RATED_MOVES: usize = 5;
+// The loss that takes the rating down to about a third of the top
+const LOSS_SCALE: f64 = 100.0;
+// How much the loss counts for against the share of engine moves
+const LOSS_WEIGHT: f64 = 2.0 / 3.0;
+
+// From the report of one side of a game
+pub fn get_performance_rating(report: &FairPlayReport) -> Option<u32> {
+    if report.judged_moves < MIN_RATED_MOVES {
+        return None;
+    }
+
+    let from_loss = MAX_RATING * (-report.average_loss / LOSS_SCALE).exp();
+    let from_matches = MIN_RATING + (MAX_RATING - MIN_RATING) * report.engine_match_rate();
+    let rating = LOSS_WEIGHT * from_loss + (1.0 - LOSS_WEIGHT) * from_matches;
+
+    Some(rating.clamp(MIN_RATING, MAX_RATING).round() as u32)
+}
+
+// A percentage from the average centipawn loss, 100 for playing the
+// engine's moves throughout, on the curve lichess uses
+pub fn get_accuracy(average_loss: f64) -> f64 {
+    (103.1668 * (-0.04354 * average_loss).exp() - 3.1669).clamp(0.0, 100.0)
+}
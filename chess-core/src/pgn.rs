@@ -0,0 +1,564 @@
+use tracing::warn;
+
+use crate::{
+    get_all_legal_moves, get_game_result, get_starting_pieces, is_king_attacked, Board, Move,
+    Piece, Player, Square,
+};
+
+// PGN readers expect lines no longer than this
+const LINE_LENGTH: usize = 80;
+// Variations nested deeper than this are skipped, as reading them would
+// overflow the stack on hostile input
+const MAX_VARIATION_DEPTH: usize = 64;
+
+// The move in standard algebraic notation, like "Nbd2" or "exd5+", given the
+// board before it is played
+pub fn get_san(board: &Board, mv: &Move) -> String {
+    let Some((piece_type, player)) = board.get(mv.from) else {
+        return format!("{}{}", mv.from, mv.to);
+    };
+    let is_capture = board.get(mv.to).is_some();
+
+    let mut san = String::new();
+
+    if piece_type == Piece::Pawn {
+        if is_capture {
+            san.push((b'a' + mv.from.file() as u8) as char);
+        }
+    } else {
+        san.push(piece_type.letter());
+
+        // Other pieces of the same kind that could also go to the square
+        let rivals: Vec<Move> = get_all_legal_moves(board, player)
+            .into_iter()
+            .filter(|other| {
+                other.to == mv.to
+                    && other.from != mv.from
+                    && board.get(other.from) == Some((piece_type, player))
+            })
+            .collect();
+
+        if !rivals.is_empty() {
+            let from = mv.from.to_string();
+            if rivals
+                .iter()
+                .all(|other| other.from.file() != mv.from.file())
+            {
+                san.push_str(&from[..1]);
+            } else if rivals
+                .iter()
+                .all(|other| other.from.rank() != mv.from.rank())
+            {
+                san.push_str(&from[1..]);
+            } else {
+                san.push_str(&from);
+            }
+        }
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+    san.push_str(&mv.to.to_string());
+    if let Some(promotion) = mv.promotion {
+        san.push('=');
+        san.push(promotion.letter());
+    }
+
+    let mut board_after = *board;
+    board_after.apply_move(mv);
+
+    let opponent = player.opponent();
+    if is_king_attacked(&board_after, opponent) {
+        san.push(if get_all_legal_moves(&board_after, opponent).is_empty() {
+            '#'
+        } else {
+            '+'
+        });
+    }
+
+    san
+}
+
+// A whole game as PGN. The Result tag is added from the moves, and is "*"
+// for a game that hasn't ended
+pub fn get_pgn(moves: &[Move], tags: &[(&str, String)]) -> String {
+    write_pgn(moves, tags, None, false)
+}
+
+// The same for an armageddon game, where a draw is written as a win for
+// Black and a Variant tag says why
+pub fn get_armageddon_pgn(moves: &[Move], tags: &[(&str, String)]) -> String {
+    write_pgn(moves, tags, None, true)
+}
+
+// The same, with the mover's clock after each move as a [%clk h:mm:ss]
+// comment, in seconds left. Moves past the end of the clocks get none
+pub fn get_pgn_with_clocks(moves: &[Move], tags: &[(&str, String)], clocks: &[f64]) -> String {
+    write_pgn(moves, tags, Some(clocks), false)
+}
+
+// Reads the seconds left out of a comment holding [%clk h:mm:ss], where
+// the seconds may have a fraction
+pub fn parse_clock_comment(comment: &str) -> Option<f64> {
+    let start = comment.find("[%clk")? + "[%clk".len();
+    let end = start + comment[start..].find(']')?;
+
+    let mut seconds = 0.0;
+    let mut fields = 0;
+    for field in comment[start..end].trim().split(':') {
+        seconds = seconds * 60.0 + field.parse::<f64>().ok()?;
+        fields += 1;
+    }
+
+    (fields == 3 && seconds >= 0.0).then_some(seconds)
+}
+
+fn format_clock(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn write_pgn(
+    moves: &[Move],
+    tags: &[(&str, String)],
+    clocks: Option<&[f64]>,
+    armageddon: bool,
+) -> String {
+    let score = get_game_result(moves).map_or("*", |result| {
+        if armageddon {
+            result.armageddon_score()
+        } else {
+            result.score()
+        }
+    });
+
+    let mut pgn = String::new();
+    for (name, value) in tags {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        pgn.push_str(&format!("[{name} \"{value}\"]\n"));
+    }
+    if armageddon {
+        pgn.push_str("[Variant \"Armageddon\"]\n");
+    }
+    pgn.push_str(&format!("[Result \"{score}\"]\n\n"));
+
+    let mut board = Board::from_pieces(&get_starting_pieces());
+    let mut tokens = Vec::new();
+
+    for (ply, mv) in moves.iter().enumerate() {
+        let san = get_san(&board, mv);
+        tokens.push(if ply % 2 == 0 {
+            format!("{}. {san}", ply / 2 + 1)
+        } else if clocks.is_some() {
+            // Black's moves are numbered again after a comment
+            format!("{}... {san}", ply / 2 + 1)
+        } else {
+            san
+        });
+        if let Some(clock) = clocks.and_then(|clocks| clocks.get(ply)) {
+            tokens.push(format!("{{[%clk {}]}}", format_clock(*clock)));
+        }
+        board.apply_move(mv);
+    }
+    tokens.push(score.to_string());
+
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 && line_length + 1 + token.len() > LINE_LENGTH {
+            pgn.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            pgn.push(' ');
+            line_length += 1;
+        }
+
+        pgn.push_str(&token);
+        line_length += token.len();
+    }
+    pgn.push('\n');
+
+    pgn
+}
+
+// A game read from PGN, with its variations and annotations
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // Before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PgnMove {
+    pub mv: Move,
+    // Numeric annotation glyphs, with "!" read as 1, "?" as 2 and so on
+    pub nags: Vec<u8>,
+    // Without the [%...] commands, which are read into the fields below
+    pub comment: Option<String>,
+    pub shapes: Vec<PgnShape>,
+    // Other moves that could have been played instead of this one
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+// Drawn on the board with [%cal] and [%csl], as lichess does. The color is
+// the letter it is written with: G, R, Y or B
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PgnShape {
+    Arrow {
+        from: Square,
+        to: Square,
+        color: char,
+    },
+    Circle {
+        square: Square,
+        color: char,
+    },
+}
+
+// How a numeric annotation glyph is shown next to its move, for the ones
+// commonly used. The rest keep their number, as "$22"
+pub fn get_nag_symbol(nag: u8) -> String {
+    let symbol = match nag {
+        1 => "!",
+        2 => "?",
+        3 => "!!",
+        4 => "??",
+        5 => "!?",
+        6 => "?!",
+        7 => "□",
+        10 => "=",
+        13 => "∞",
+        14 => "⩲",
+        15 => "⩱",
+        16 => "±",
+        17 => "∓",
+        18 => "+−",
+        19 => "−+",
+        _ => return format!("${nag}"),
+    };
+    symbol.to_string()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum PgnToken {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    Result,
+    San(String),
+}
+
+// Reads every game in the text. A line stops at the first move that can't
+// be played here, such as castling, which these rules don't have, and
+// games set up from another position are read as if from the start
+pub fn read_pgn(text: &str) -> Vec<PgnGame> {
+    let tokens = get_pgn_tokens(text);
+    let mut games = Vec::new();
+    let mut position = 0;
+
+    while position < tokens.len() {
+        let mut game = PgnGame::default();
+
+        while let Some(PgnToken::Tag(name, value)) = tokens.get(position) {
+            game.tags.push((name.clone(), value.clone()));
+            position += 1;
+        }
+
+        if let Some(PgnToken::Comment(comment)) = tokens.get(position) {
+            game.comment = get_comment_text(comment);
+            position += 1;
+        }
+
+        let board = Board::from_pieces(&get_starting_pieces());
+        game.moves = read_line(&tokens, &mut position, board, Player::White, 0);
+
+        if tokens.get(position) == Some(&PgnToken::Result) {
+            position += 1;
+        }
+
+        if !game.tags.is_empty() || !game.moves.is_empty() {
+            games.push(game);
+        }
+    }
+
+    games
+}
+
+// Up to the end of the variation or game, leaving the position on the
+// token that ends it, except for a variation's closing bracket
+fn read_line(
+    tokens: &[PgnToken],
+    position: &mut usize,
+    mut board: Board,
+    mut player: Player,
+    depth: usize,
+) -> Vec<PgnMove> {
+    let mut line: Vec<PgnMove> = Vec::new();
+    // The position before each move, which its variations start from
+    let mut boards_before = Vec::new();
+    let mut playable = true;
+    let mut leading_comment = None;
+
+    while let Some(token) = tokens.get(*position) {
+        match token {
+            PgnToken::Tag(..) | PgnToken::Result => return line,
+            PgnToken::VariationEnd => {
+                *position += 1;
+                return line;
+            }
+            PgnToken::VariationStart => {
+                *position += 1;
+                if depth >= MAX_VARIATION_DEPTH {
+                    warn!("variations nested more than {MAX_VARIATION_DEPTH} deep are skipped");
+                    skip_variation(tokens, position);
+                    continue;
+                }
+
+                let variation = match boards_before.last() {
+                    Some(&(board, player)) if playable => {
+                        read_line(tokens, position, board, player, depth + 1)
+                    }
+                    // Alternatives to a move that wasn't read are skipped
+                    _ => {
+                        skip_variation(tokens, position);
+                        continue;
+                    }
+                };
+                if let (Some(last), false) = (line.last_mut(), variation.is_empty()) {
+                    last.variations.push(variation);
+                }
+                continue;
+            }
+            PgnToken::Comment(comment) => match line.last_mut() {
+                Some(last) if playable => {
+                    last.shapes.extend(get_comment_shapes(comment));
+                    last.comment = match (last.comment.take(), get_comment_text(comment)) {
+                        (Some(old), Some(new)) => Some(format!("{old} {new}")),
+                        (old, new) => old.or(new),
+                    };
+                }
+                None => leading_comment = get_comment_text(comment),
+                _ => {}
+            },
+            PgnToken::Nag(nag) => {
+                if let (Some(last), true) = (line.last_mut(), playable) {
+                    last.nags.push(*nag);
+                }
+            }
+            PgnToken::San(san) => {
+                if playable {
+                    match find_san_move(&board, player, san) {
+                        Some(mv) => {
+                            boards_before.push((board, player));
+                            board.apply_move(&mv);
+                            player = player.opponent();
+                            line.push(PgnMove {
+                                mv,
+                                nags: Vec::new(),
+                                comment: leading_comment.take(),
+                                shapes: Vec::new(),
+                                variations: Vec::new(),
+                            });
+                        }
+                        None => {
+                            warn!("can't play {san} here, so the rest of the line is skipped");
+                            playable = false;
+                        }
+                    }
+                }
+            }
+        }
+
+        *position += 1;
+    }
+
+    line
+}
+
+// Past the end of the variation, and of any nested in it, stopping as
+// read_line does at the end of the game
+fn skip_variation(tokens: &[PgnToken], position: &mut usize) {
+    let mut depth = 0;
+
+    while let Some(token) = tokens.get(*position) {
+        match token {
+            PgnToken::Tag(..) | PgnToken::Result => return,
+            PgnToken::VariationStart => depth += 1,
+            PgnToken::VariationEnd if depth == 0 => {
+                *position += 1;
+                return;
+            }
+            PgnToken::VariationEnd => depth -= 1,
+            _ => {}
+        }
+        *position += 1;
+    }
+}
+
+// The legal move written as san, which may have more or fewer check marks
+// and annotations than get_san would give it
+fn find_san_move(board: &Board, player: Player, san: &str) -> Option<Move> {
+    let strip = |san: &str| san.trim_end_matches(['+', '#', '!', '?']).to_string();
+    let san = strip(san);
+
+    get_all_legal_moves(board, player)
+        .into_iter()
+        .find(|mv| strip(&get_san(board, mv)) == san)
+}
+
+fn get_pgn_tokens(text: &str) -> Vec<PgnToken> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                let tag: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let (name, value) = tag
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((&tag, ""));
+                let value = value
+                    .trim()
+                    .trim_matches('"')
+                    .replace("\\\"", "\"")
+                    .replace("\\\\", "\\");
+                tokens.push(PgnToken::Tag(name.to_string(), value));
+            }
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                tokens.push(PgnToken::Comment(comment));
+            }
+            // A comment to the end of the line
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '\n').collect();
+                tokens.push(PgnToken::Comment(comment));
+            }
+            '(' => tokens.push(PgnToken::VariationStart),
+            ')' => tokens.push(PgnToken::VariationEnd),
+            '$' => {
+                let mut number = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    number.push(digit);
+                }
+                if let Ok(nag) = number.parse() {
+                    tokens.push(PgnToken::Nag(nag));
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|&c| !c.is_whitespace() && !"{}()[];$".contains(c))
+                {
+                    word.push(c);
+                }
+                tokens.extend(get_word_tokens(&word));
+            }
+        }
+    }
+
+    tokens
+}
+
+// A move, possibly with its number before it and annotations after, or a
+// result
+fn get_word_tokens(word: &str) -> Vec<PgnToken> {
+    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&word) {
+        return vec![PgnToken::Result];
+    }
+
+    let san = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if san.is_empty() {
+        return Vec::new();
+    }
+
+    let annotation_start = san.find(['!', '?']).unwrap_or(san.len());
+    let (san, annotation) = san.split_at(annotation_start);
+    let nag = match annotation {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+
+    let mut tokens = Vec::new();
+    if !san.is_empty() {
+        tokens.push(PgnToken::San(san.to_string()));
+    }
+    tokens.extend(nag.map(PgnToken::Nag));
+    tokens
+}
+
+// The comment without its [%...] commands, None if nothing is left
+fn get_comment_text(comment: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut rest = comment;
+
+    while let Some(start) = rest.find("[%") {
+        text.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find(']')
+            .map_or("", |end| &rest[start + end + 1..]);
+    }
+    text.push_str(rest);
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+// The arrows of [%cal Ge2e4,Rd1d8] and circles of [%csl Ge4]
+fn get_comment_shapes(comment: &str) -> Vec<PgnShape> {
+    let mut shapes = Vec::new();
+
+    for (command, is_arrow) in [("[%cal", true), ("[%csl", false)] {
+        let mut rest = comment;
+        while let Some(start) = rest.find(command) {
+            rest = &rest[start + command.len()..];
+            let end = rest.find(']').unwrap_or(rest.len());
+
+            for shape in rest[..end].split(',').map(str::trim) {
+                let mut chars = shape.chars();
+                let Some(color) = chars.next() else {
+                    continue;
+                };
+                let squares = chars.as_str();
+
+                // Checked for a char boundary too, as the text may be anything
+                let shape = if is_arrow && squares.len() == 4 && squares.is_char_boundary(2) {
+                    Square::from_algebraic(&squares[..2])
+                        .zip(Square::from_algebraic(&squares[2..]))
+                        .map(|(from, to)| PgnShape::Arrow { from, to, color })
+                } else if !is_arrow {
+                    Square::from_algebraic(squares).map(|square| PgnShape::Circle { square, color })
+                } else {
+                    None
+                };
+                shapes.extend(shape);
+            }
+        }
+    }
+
+    shapes
+}
@@ -0,0 +1,72 @@
+// Puzzles made from a game's mistakes: the position a bad move left, to
+// find the line that punishes it from
+
+use serde::{Deserialize, Serialize};
+
+use crate::{get_board_after_moves, get_principal_variation, EngineConfig, Move, Player};
+
+// Centipawns a move has to give away to count as a blunder
+pub const BLUNDER_LOSS: i32 = 200;
+// Enough for the refutation and the answers to it, ending on the solver's
+// move
+const SOLUTION_PLIES: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    // From the starting position up to the blunder
+    pub moves: Vec<Move>,
+    // The solver's moves, with the replies to them in between
+    pub solution: Vec<Move>,
+}
+
+impl Puzzle {
+    pub fn player(&self) -> Player {
+        if self.moves.len().is_multiple_of(2) {
+            Player::White
+        } else {
+            Player::Black
+        }
+    }
+}
+
+// Whether the move into the position after `plies` moves gave away at
+// least BLUNDER_LOSS. Scores are from White's side, one per position from
+// the start, as the evaluation graph has them
+pub fn is_blunder(scores: &[i32], plies: usize) -> bool {
+    let (Some(before), Some(after)) = (
+        plies.checked_sub(1).and_then(|ply| scores.get(ply)),
+        scores.get(plies),
+    ) else {
+        return false;
+    };
+
+    // White made the odd numbered moves
+    let loss = if !plies.is_multiple_of(2) {
+        before - after
+    } else {
+        after - before
+    };
+    loss >= BLUNDER_LOSS
+}
+
+// The engine's line from the position after the moves. None where the
+// game is already over
+pub fn get_puzzle(moves: &[Move], config: &EngineConfig) -> Option<Puzzle> {
+    let puzzle = Puzzle {
+        moves: moves.to_vec(),
+        solution: Vec::new(),
+    };
+
+    let mut solution = get_principal_variation(
+        &get_board_after_moves(moves),
+        puzzle.player(),
+        config,
+        SOLUTION_PLIES,
+    );
+    // A reply left hanging at the end would give nothing to find
+    if solution.len().is_multiple_of(2) {
+        solution.pop();
+    }
+
+    (!solution.is_empty()).then_some(Puzzle { solution, ..puzzle })
+}
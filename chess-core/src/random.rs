@@ -0,0 +1,84 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{get_all_legal_moves, is_king_attacked, Board, Piece, Player, Square, BOARD_SIZE};
+
+// Tries before giving up on material that hardly ever makes a legal position
+const MAX_ATTEMPTS: usize = 10_000;
+
+// Reads the pieces besides the two kings, in FEN letters, so "QRrp" is a
+// white queen and rook against a black rook and pawn
+pub fn parse_material(material: &str) -> Option<Vec<(Piece, Player)>> {
+    let pieces: Vec<(Piece, Player)> = material
+        .chars()
+        .map(|letter| {
+            let player = if letter.is_ascii_uppercase() {
+                Player::White
+            } else {
+                Player::Black
+            };
+            match Piece::from_letter(letter.to_ascii_uppercase()) {
+                Some(Piece::King) | None => None,
+                Some(piece_type) => Some((piece_type, player)),
+            }
+        })
+        .collect::<Option<_>>()?;
+
+    // Pawns can't stand on the first or last rank
+    let pawns = pieces
+        .iter()
+        .filter(|(piece_type, _)| *piece_type == Piece::Pawn)
+        .count();
+    let squares = (BOARD_SIZE * BOARD_SIZE) as usize - 2;
+    if pieces.len() > squares || pawns > squares - 2 * BOARD_SIZE as usize {
+        return None;
+    }
+
+    Some(pieces)
+}
+
+// The two kings and the material on random squares, in a position that is
+// legal and not already over with the player to move. None if no such
+// position turned up
+pub fn get_random_position(
+    material: &[(Piece, Player)],
+    player: Player,
+    rng: &mut impl Rng,
+) -> Option<Board> {
+    let squares: Vec<Square> = (0..BOARD_SIZE)
+        .flat_map(|x| (0..BOARD_SIZE).map(move |y| Square(x, y)))
+        .collect();
+
+    let kings = [(Piece::King, Player::White), (Piece::King, Player::Black)];
+
+    'attempts: for _ in 0..MAX_ATTEMPTS {
+        let mut free_squares = squares.clone();
+        free_squares.shuffle(rng);
+        let mut board = Board::default();
+
+        for &(piece_type, owner) in kings.iter().chain(material) {
+            let index = free_squares.iter().position(|square| {
+                piece_type != Piece::Pawn || (1..BOARD_SIZE - 1).contains(&square.rank())
+            });
+
+            // Every square left is on a rank the pawn can't stand on
+            let Some(index) = index else {
+                continue 'attempts;
+            };
+            let square = free_squares.swap_remove(index);
+            board.0[square.0 as usize][square.1 as usize] = Some((piece_type, owner));
+        }
+
+        // The player who just moved can't have left their king attacked
+        if is_king_attacked(&board, player.opponent()) {
+            continue;
+        }
+
+        if get_all_legal_moves(&board, player).is_empty() {
+            continue;
+        }
+
+        return Some(board);
+    }
+
+    None
+}
@@ -0,0 +1,81 @@
+// Opening lines a player means to stick to, read from PGN along with their
+// variations. They are kept by position rather than by line, so a move
+// order that transposes into the repertoire is still in it
+
+use std::collections::HashMap;
+
+use crate::{get_board_after_moves, get_position_fen, Board, Move, PgnGame, PgnMove, Player};
+
+#[derive(Clone, Debug, Default)]
+pub struct Repertoire {
+    // By the position's FEN without its move counters
+    moves: HashMap<String, Vec<Move>>,
+}
+
+impl Repertoire {
+    pub fn from_pgn(games: &[PgnGame]) -> Self {
+        let mut repertoire = Self::default();
+        for game in games {
+            repertoire.add_line(get_board_after_moves(&[]), Player::White, &game.moves);
+        }
+        repertoire
+    }
+
+    fn add_line(&mut self, mut board: Board, mut player: Player, line: &[PgnMove]) {
+        for pgn_move in line {
+            let book_moves = self
+                .moves
+                .entry(get_position_fen(&board, player))
+                .or_default();
+            if !book_moves.contains(&pgn_move.mv) {
+                book_moves.push(pgn_move.mv);
+            }
+
+            // After the main line's move, so it comes first
+            for variation in &pgn_move.variations {
+                self.add_line(board, player, variation);
+            }
+
+            board.apply_move(&pgn_move.mv);
+            player = player.opponent();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    // The repertoire's moves in the position after these moves, which are
+    // none once it has been left
+    pub fn get_book_moves(&self, moves: &[Move]) -> &[Move] {
+        let player = if moves.len().is_multiple_of(2) {
+            Player::White
+        } else {
+            Player::Black
+        };
+
+        self.moves
+            .get(&get_position_fen(&get_board_after_moves(moves), player))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    // The ply at which one of the given sides left the repertoire with a
+    // move of its own. None while the game is still in it, or when the
+    // other side left it first
+    pub fn find_deviation(&self, moves: &[Move], players: &[Player]) -> Option<usize> {
+        let mut board = get_board_after_moves(&[]);
+        let mut player = Player::White;
+
+        for (ply, mv) in moves.iter().enumerate() {
+            let book_moves = self.moves.get(&get_position_fen(&board, player))?;
+            if !book_moves.contains(mv) {
+                return players.contains(&player).then_some(ply);
+            }
+
+            board.apply_move(mv);
+            player = player.opponent();
+        }
+
+        None
+    }
+}
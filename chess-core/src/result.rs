@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    get_all_legal_moves, get_board_after_moves, get_halfmove_clock, get_repetition_count,
+    is_king_attacked, Move, Player,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    Checkmate { winner: Player },
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Checkmate { winner } => Some(*winner),
+            _ => None,
+        }
+    }
+
+    // As written in the Result tag and after the moves of a PGN
+    pub fn score(&self) -> &'static str {
+        match self.winner() {
+            Some(Player::White) => "1-0",
+            Some(Player::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+
+    // Armageddon games can't be drawn: Black, playing with less time for
+    // it, wins whatever White doesn't
+    pub fn armageddon_winner(&self) -> Player {
+        self.winner().unwrap_or(Player::Black)
+    }
+
+    pub fn armageddon_score(&self) -> &'static str {
+        match self.armageddon_winner() {
+            Player::White => "1-0",
+            Player::Black => "0-1",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameResult::Checkmate { .. } => "checkmate",
+            GameResult::Stalemate => "stalemate",
+            GameResult::Repetition => "repetition",
+            GameResult::FiftyMoves => "fifty-moves",
+        }
+    }
+}
+
+// None while the game goes on. Draws by repetition and the fifty-move rule
+// are taken as soon as they can be claimed
+pub fn get_game_result(moves: &[Move]) -> Option<GameResult> {
+    let board = get_board_after_moves(moves);
+    let player = if moves.len().is_multiple_of(2) {
+        Player::White
+    } else {
+        Player::Black
+    };
+
+    if get_all_legal_moves(&board, player).is_empty() {
+        return Some(if is_king_attacked(&board, player) {
+            GameResult::Checkmate {
+                winner: player.opponent(),
+            }
+        } else {
+            GameResult::Stalemate
+        });
+    }
+
+    if get_repetition_count(moves) >= 3 {
+        return Some(GameResult::Repetition);
+    }
+
+    if get_halfmove_clock(moves) >= 100 {
+        return Some(GameResult::FiftyMoves);
+    }
+
+    None
+}
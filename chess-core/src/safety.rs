@@ -0,0 +1,68 @@
+use crate::{get_attacked_squares, Board, Piece, Player, Square, BOARD_SIZE};
+
+// A rough measure of how exposed a king is, for display rather than search
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KingSafety {
+    // Own pawns on the king's file and the two beside it, one or two
+    // ranks in front
+    pub pawn_shield: u32,
+    // Of those three files, the ones without an own pawn on them
+    pub open_files: u32,
+    // Enemy pieces attacking the king or a square next to it
+    pub attackers: u32,
+}
+
+impl KingSafety {
+    pub const MAX_RATING: u32 = 5;
+
+    // From 0 for a bare, hunted king up to MAX_RATING behind a full shield
+    pub fn rating(&self) -> u32 {
+        (2 + self.pawn_shield as i32 - self.open_files as i32 - self.attackers as i32)
+            .clamp(0, Self::MAX_RATING as i32) as u32
+    }
+}
+
+// None when the player has no king, as in a tampered save
+pub fn get_king_safety(board: &Board, player: Player) -> Option<KingSafety> {
+    let (_, _, king_position) = board
+        .pieces()
+        .find(|(piece_type, owner, _)| *piece_type == Piece::King && *owner == player)?;
+    let king = king_position.square();
+
+    let forward = match player {
+        Player::White => 1,
+        Player::Black => -1,
+    };
+    let is_own_pawn = |square: Square| board.get(square) == Some((Piece::Pawn, player));
+
+    let mut safety = KingSafety::default();
+
+    for file in king.file() - 1..=king.file() + 1 {
+        if !(0..BOARD_SIZE).contains(&file) {
+            continue;
+        }
+
+        safety.pawn_shield += (1..=2)
+            .filter(|ranks| is_own_pawn(Square(file, king.rank() + forward * ranks)))
+            .count() as u32;
+
+        if !(0..BOARD_SIZE).any(|rank| is_own_pawn(Square(file, rank))) {
+            safety.open_files += 1;
+        }
+    }
+
+    let is_near_king = |square: &Square| {
+        (square.file() - king.file()).abs() <= 1 && (square.rank() - king.rank()).abs() <= 1
+    };
+    safety.attackers = board
+        .pieces()
+        .filter(|(_, owner, _)| *owner != player)
+        .filter(|(piece_type, owner, position)| {
+            get_attacked_squares(piece_type, position, owner, board)
+                .iter()
+                .any(is_near_king)
+        })
+        .count() as u32;
+
+    Some(safety)
+}
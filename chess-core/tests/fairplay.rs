@@ -0,0 +1,96 @@
+use chess_core::{
+    get_accuracy, get_best_move, get_board_after_moves, get_fair_play_reports,
+    get_performance_rating, EngineConfig, FairPlayFlag, FairPlayReport, Move, Player,
+};
+
+// The engine playing both sides, as a player relaying its moves would
+fn play_engine_game(plies: usize) -> Vec<Move> {
+    let config = EngineConfig::default();
+    let mut board = get_board_after_moves(&[]);
+    let mut player = Player::White;
+    let mut moves = Vec::new();
+
+    while moves.len() < plies {
+        let Some(mv) = get_best_move(&board, player, &config) else {
+            break;
+        };
+        board.apply_move(&mv);
+        moves.push(mv);
+        player = player.opponent();
+    }
+
+    moves
+}
+
+#[test]
+fn engine_moves_at_a_steady_pace_are_flagged() {
+    let moves = play_engine_game(40);
+    // Five seconds a move, every move
+    let times: Vec<f64> = (1..=moves.len()).map(|ply| ply as f64 * 5.0).collect();
+
+    for report in get_fair_play_reports(&moves, &times, &EngineConfig::default()) {
+        assert_eq!(report.engine_matches, report.judged_moves);
+        assert_eq!(report.average_loss, 0.0);
+        assert_eq!(report.average_think_time, 5.0);
+        assert_eq!(
+            report.flags,
+            vec![
+                FairPlayFlag::EngineCorrelation,
+                FairPlayFlag::UniformMoveTimes
+            ]
+        );
+    }
+}
+
+#[test]
+fn varied_move_times_are_not_flagged() {
+    let moves = play_engine_game(40);
+    let mut time = 0.0;
+    let times: Vec<f64> = (0..moves.len())
+        .map(|ply| {
+            time += [2.0, 30.0, 7.0, 1.0, 12.0][ply % 5];
+            time
+        })
+        .collect();
+
+    for report in get_fair_play_reports(&moves, &times, &EngineConfig::default()) {
+        assert!(!report.flags.contains(&FairPlayFlag::UniformMoveTimes));
+    }
+}
+
+#[test]
+fn openings_and_short_games_are_not_judged() {
+    let moves = play_engine_game(10);
+
+    for report in get_fair_play_reports(&moves, &[], &EngineConfig::default()) {
+        assert_eq!(report.judged_moves, 0);
+        assert!(report.flags.is_empty());
+    }
+}
+
+#[test]
+fn performance_ratings_follow_the_loss_and_matches() {
+    let report = |judged_moves, engine_matches, average_loss| FairPlayReport {
+        player: Player::White,
+        judged_moves,
+        engine_matches,
+        average_loss,
+        average_think_time: 0.0,
+        think_time_spread: 0.0,
+        flags: Vec::new(),
+    };
+
+    assert_eq!(get_performance_rating(&report(20, 20, 0.0)), Some(3000));
+    assert_eq!(get_performance_rating(&report(4, 4, 0.0)), None);
+
+    let strong = get_performance_rating(&report(20, 12, 20.0)).unwrap();
+    let weak = get_performance_rating(&report(20, 6, 80.0)).unwrap();
+    let hopeless = get_performance_rating(&report(20, 0, 1000.0)).unwrap();
+    assert!(strong > weak, "{strong} {weak}");
+    assert!(weak > hopeless, "{weak} {hopeless}");
+    assert_eq!(hopeless, 400);
+
+    assert!((get_accuracy(0.0) - 100.0).abs() < 0.01);
+    assert!(get_accuracy(20.0) > get_accuracy(50.0));
+    assert_eq!(get_accuracy(1000.0), 0.0);
+}
@@ -0,0 +1,24 @@
+use chess_core::{get_fen, Move, Square};
+
+fn get_moves(names: &[&str]) -> Vec<Move> {
+    names
+        .iter()
+        .map(|name| Move {
+            from: Square::from_algebraic(&name[..2]).unwrap(),
+            to: Square::from_algebraic(&name[2..]).unwrap(),
+            promotion: None,
+        })
+        .collect()
+}
+
+#[test]
+fn no_castling_rights_are_claimed() {
+    assert_eq!(
+        get_fen(&[]),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"
+    );
+    assert_eq!(
+        get_fen(&get_moves(&["e2e4", "e7e5", "g1f3"])),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b - - 1 2"
+    );
+}
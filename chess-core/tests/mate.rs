@@ -0,0 +1,45 @@
+use chess_core::{find_mate, get_board_after_moves, Move, Player, Square};
+
+fn get_moves(names: &[&str]) -> Vec<Move> {
+    names
+        .iter()
+        .map(|name| Move {
+            from: Square::from_algebraic(&name[..2]).unwrap(),
+            to: Square::from_algebraic(&name[2..]).unwrap(),
+            promotion: None,
+        })
+        .collect()
+}
+
+#[test]
+fn forced_mates_are_found_with_every_defence() {
+    // The fool's mate position, one move before Qh4#
+    let moves = get_moves(&["f2f3", "e7e5", "g2g4"]);
+    let board = get_board_after_moves(&moves);
+
+    let tree = find_mate(&board, Player::Black, 2).unwrap();
+    assert_eq!(tree.moves_to_mate(), 1);
+    assert_eq!(tree.to_text(&board), "Qh4#\n");
+
+    // Scholar's mate: with Qh5 and Bc4 aimed at f7, either piece mates there
+    let moves = get_moves(&["e2e4", "a7a6", "f1c4", "a6a5", "d1h5", "a5a4"]);
+    let board = get_board_after_moves(&moves);
+    let tree = find_mate(&board, Player::White, 1).unwrap();
+    assert!(tree.to_text(&board).ends_with("xf7#\n"));
+
+    // Mate in two, where the only block on the diagonal is taken with mate
+    let moves = get_moves(&[
+        "e2e3", "b8c6", "e3e4", "f7f5", "f1e2", "c7c5", "g2g3", "h7h6", "e1f1", "b7b5", "g1h3",
+        "a8b8",
+    ]);
+    let board = get_board_after_moves(&moves);
+    let tree = find_mate(&board, Player::White, 3).unwrap();
+    assert_eq!(tree.moves_to_mate(), 2);
+    assert_eq!(tree.to_text(&board), "Bh5+\n  ...g6\n    Bxg6#\n");
+
+    // No mate in the starting position
+    assert_eq!(
+        find_mate(&get_board_after_moves(&[]), Player::White, 2),
+        None
+    );
+}
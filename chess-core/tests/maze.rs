@@ -0,0 +1,49 @@
+use chess_core::{get_random_maze, Maze, Piece, Square, MAZE_PIECES};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn square(name: &str) -> Square {
+    Square::from_algebraic(name).unwrap()
+}
+
+#[test]
+fn obstacles_make_the_way_longer() {
+    let mut maze = Maze {
+        piece: Piece::Rook,
+        start: square("a1"),
+        targets: vec![square("a8"), square("h8")],
+        obstacles: Vec::new(),
+    };
+    assert_eq!(maze.get_par(), Some(2));
+
+    // Around by h1 instead
+    maze.obstacles.push(square("a2"));
+    assert_eq!(maze.get_par(), Some(3));
+
+    // A bishop never reaches the other color
+    let maze = Maze {
+        piece: Piece::Bishop,
+        start: square("c1"),
+        targets: vec![square("h6"), square("c2")],
+        obstacles: Vec::new(),
+    };
+    assert_eq!(maze.get_par(), None);
+}
+
+#[test]
+fn random_mazes_can_be_solved() {
+    let mut rng = StdRng::seed_from_u64(7);
+
+    for piece in MAZE_PIECES {
+        for _ in 0..20 {
+            let maze = get_random_maze(piece, 4, 12, &mut rng).unwrap();
+            assert_eq!(maze.targets.len(), 4);
+            assert_eq!(maze.obstacles.len(), 12);
+            assert!(!maze.targets.contains(&maze.start));
+            assert!(maze
+                .targets
+                .iter()
+                .all(|target| !maze.obstacles.contains(target)));
+            assert!(maze.get_par().is_some_and(|par| par >= 4));
+        }
+    }
+}
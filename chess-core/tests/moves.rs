@@ -0,0 +1,228 @@
+// Checks move generation against rules that hold in every position. The
+// positions come from playing random legal moves from the starting one
+
+use chess_core::{
+    get_all_legal_moves, get_attacked_squares, get_board_after_moves, get_king_safety,
+    get_legal_moves, get_possible_moves, get_random_position, get_repetition_count,
+    is_king_attacked, parse_material, Board, KingSafety, Move, Piece, Player, Square, BOARD_SIZE,
+    PROMOTION_PIECES,
+};
+use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+// Each choice picks one of the legal moves, until the side to move has none
+fn play_random_game(choices: &[usize]) -> (Board, Player) {
+    let mut board = get_board_after_moves(&[]);
+    let mut player = Player::White;
+
+    for choice in choices {
+        let moves = get_all_legal_moves(&board, player);
+        if moves.is_empty() {
+            break;
+        }
+
+        board.apply_move(&moves[choice % moves.len()]);
+        player = player.opponent();
+    }
+
+    (board, player)
+}
+
+fn random_position() -> impl Strategy<Value = (Board, Player)> {
+    prop::collection::vec(any::<usize>(), 0..80).prop_map(|choices| play_random_game(&choices))
+}
+
+proptest! {
+    #[test]
+    fn moves_stay_on_the_board((board, _) in random_position()) {
+        for (piece_type, player, position) in board.pieces() {
+            for target in get_possible_moves(&piece_type, &position, &player, &board) {
+                prop_assert!(
+                    target.is_on_board(),
+                    "{piece_type:?} on {} moves off the board to {target:?}",
+                    position.square()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn own_pieces_are_never_captured((board, _) in random_position()) {
+        for (piece_type, player, position) in board.pieces() {
+            for target in get_possible_moves(&piece_type, &position, &player, &board) {
+                prop_assert!(
+                    !matches!(board.get(target), Some((_, owner)) if owner == player),
+                    "{piece_type:?} on {} captures its own piece on {target}",
+                    position.square()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn legal_moves_never_leave_the_king_attacked((board, player) in random_position()) {
+        for mv in get_all_legal_moves(&board, player) {
+            let mut board_after = board;
+            board_after.apply_move(&mv);
+
+            prop_assert!(
+                !is_king_attacked(&board_after, player),
+                "{}-{} leaves the king attacked",
+                mv.from,
+                mv.to
+            );
+        }
+    }
+
+    #[test]
+    fn legal_moves_are_possible_moves((board, _) in random_position()) {
+        for (piece_type, player, position) in board.pieces() {
+            let possible_moves = get_possible_moves(&piece_type, &position, &player, &board);
+
+            for target in get_legal_moves(&piece_type, &position, &player, &board) {
+                prop_assert!(possible_moves.contains(&target));
+            }
+        }
+    }
+
+    #[test]
+    fn undoing_a_move_restores_the_board((board, player) in random_position()) {
+        for mv in get_all_legal_moves(&board, player) {
+            let mut board_after = board;
+            let captured_piece = board_after.apply_move(&mv);
+            prop_assert_eq!(captured_piece, board.get(mv.to));
+
+            board_after.undo_move(&mv, captured_piece);
+            prop_assert_eq!(board_after, board);
+        }
+    }
+
+    #[test]
+    fn captures_are_on_attacked_squares((board, _) in random_position()) {
+        for (piece_type, player, position) in board.pieces() {
+            let attacked_squares = get_attacked_squares(&piece_type, &position, &player, &board);
+
+            for target in get_possible_moves(&piece_type, &position, &player, &board) {
+                if board.get(target).is_some() {
+                    prop_assert!(
+                        attacked_squares.contains(&target),
+                        "{piece_type:?} on {} captures on {target} without attacking it",
+                        position.square()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn square_names_round_trip(file in 0..BOARD_SIZE, rank in 0..BOARD_SIZE) {
+        let square = Square(file, rank);
+        prop_assert_eq!(Square::from_algebraic(&square.to_string()), Some(square));
+    }
+}
+
+#[test]
+fn kings_start_on_the_e_file_and_queens_on_the_d_file() {
+    let board = get_board_after_moves(&[]);
+
+    for (player, rank) in [(Player::White, 0), (Player::Black, BOARD_SIZE - 1)] {
+        assert_eq!(board.get(Square(4, rank)), Some((Piece::King, player)));
+        assert_eq!(board.get(Square(3, rank)), Some((Piece::Queen, player)));
+    }
+}
+
+#[test]
+fn promoted_pawns_go_back_as_pawns() {
+    let moves = [
+        "h2h4", "g7g5", "h4g5", "h7h6", "g5h6", "e7e6", "h6h7", "e6e5",
+    ]
+    .map(|name| Move {
+        from: Square::from_algebraic(&name[..2]).unwrap(),
+        to: Square::from_algebraic(&name[2..]).unwrap(),
+        promotion: None,
+    });
+    let mut board = get_board_after_moves(&moves);
+    let (from, to) = (Square(7, 6), Square(6, 7));
+
+    // Taking the knight on g8 is one move for each piece the pawn can become
+    let promotions: Vec<Option<Piece>> = get_all_legal_moves(&board, Player::White)
+        .into_iter()
+        .filter(|mv| mv.from == from)
+        .map(|mv| mv.promotion)
+        .collect();
+    assert_eq!(promotions, PROMOTION_PIECES.map(Some));
+
+    let mv = Move {
+        from,
+        to,
+        promotion: Some(Piece::Knight),
+    };
+    let captured_piece = board.apply_move(&mv);
+    assert_eq!(captured_piece, Some((Piece::Knight, Player::Black)));
+    assert_eq!(board.get(to), Some((Piece::Knight, Player::White)));
+
+    board.undo_move(&mv, captured_piece);
+    assert_eq!(board, get_board_after_moves(&moves));
+}
+
+#[test]
+fn repeated_positions_are_counted() {
+    let knight_moves = ["g1f3", "g8f6", "f3g1", "f6g8"].map(|name| Move {
+        from: Square::from_algebraic(&name[..2]).unwrap(),
+        to: Square::from_algebraic(&name[2..]).unwrap(),
+        promotion: None,
+    });
+    let moves: Vec<Move> = knight_moves.iter().cycle().take(8).copied().collect();
+
+    assert_eq!(get_repetition_count(&[]), 1);
+    assert_eq!(get_repetition_count(&moves[..2]), 1);
+    assert_eq!(get_repetition_count(&moves[..4]), 2);
+    assert_eq!(get_repetition_count(&moves), 3);
+}
+
+#[test]
+fn king_safety_drops_as_the_shield_goes() {
+    let safety = get_king_safety(&get_board_after_moves(&[]), Player::White).unwrap();
+    assert_eq!(
+        safety,
+        KingSafety {
+            pawn_shield: 3,
+            open_files: 0,
+            attackers: 0,
+        }
+    );
+    assert_eq!(safety.rating(), KingSafety::MAX_RATING);
+
+    // After f3 the f-pawn is still in front of the king, and the queen on
+    // h4 attacks it
+    let moves = ["f2f3", "e7e5", "g2g4", "d8h4"].map(|name| Move {
+        from: Square::from_algebraic(&name[..2]).unwrap(),
+        to: Square::from_algebraic(&name[2..]).unwrap(),
+        promotion: None,
+    });
+    let safety = get_king_safety(&get_board_after_moves(&moves), Player::White).unwrap();
+    assert_eq!(safety.pawn_shield, 3);
+    assert_eq!(safety.attackers, 1);
+    assert!(safety.rating() < KingSafety::MAX_RATING);
+}
+
+#[test]
+fn random_positions_are_legal() {
+    let material = parse_material("QRPPrbpp").unwrap();
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for player in [Player::White, Player::Black].into_iter().cycle().take(50) {
+        let board = get_random_position(&material, player, &mut rng).unwrap();
+
+        assert_eq!(board.pieces().count(), material.len() + 2);
+        assert!(!is_king_attacked(&board, player.opponent()));
+        assert!(!get_all_legal_moves(&board, player).is_empty());
+        assert!(board
+            .pieces()
+            .filter(|(piece_type, _, _)| *piece_type == Piece::Pawn)
+            .all(|(_, _, position)| (1..BOARD_SIZE - 1).contains(&position.y)));
+    }
+
+    assert_eq!(parse_material("KQ"), None);
+    assert_eq!(parse_material("Qx"), None);
+}
@@ -0,0 +1,188 @@
+use chess_core::{
+    get_armageddon_pgn, get_game_result, get_nag_symbol, get_pgn, get_pgn_with_clocks,
+    parse_clock_comment, read_pgn, GameResult, Move, PgnShape, Piece, Player, Square,
+};
+
+fn get_moves(names: &[&str]) -> Vec<Move> {
+    names
+        .iter()
+        .map(|name| Move {
+            from: Square::from_algebraic(&name[..2]).unwrap(),
+            to: Square::from_algebraic(&name[2..]).unwrap(),
+            promotion: None,
+        })
+        .collect()
+}
+
+#[test]
+fn fools_mate_is_written_as_pgn() {
+    let moves = get_moves(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+
+    assert_eq!(
+        get_game_result(&moves),
+        Some(GameResult::Checkmate {
+            winner: Player::Black
+        })
+    );
+    assert_eq!(
+        get_pgn(&moves, &[("White", "A".to_string())]),
+        "[White \"A\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n"
+    );
+}
+
+#[test]
+fn captures_and_ambiguous_moves_are_written_in_full() {
+    let moves = get_moves(&["e2e4", "d7d5", "e4d5", "g8f6", "g1f3", "f6d5", "b1c3"]);
+    let pgn = get_pgn(&moves, &[]);
+
+    assert!(
+        pgn.ends_with("1. e4 d5 2. exd5 Nf6 3. Nf3 Nxd5 4. Nc3 *\n"),
+        "{pgn}"
+    );
+    assert_eq!(get_game_result(&moves), None);
+
+    // Both knights can reach d4 now
+    let moves = get_moves(&[
+        "g1f3", "a7a6", "b1c3", "a6a5", "c3e4", "a5a4", "e4c5", "b7b6", "c5b3", "a8a7", "b3d4",
+    ]);
+    let pgn = get_pgn(&moves, &[]);
+    assert!(pgn.contains("6. Nbd4"), "{pgn}");
+}
+
+#[test]
+fn promotions_are_written_and_read_back() {
+    let mut moves = get_moves(&[
+        "h2h4", "g7g5", "h4g5", "h7h6", "g5h6", "e7e6", "h6h7", "e6e5", "h7g8",
+    ]);
+    moves[8].promotion = Some(Piece::Rook);
+    let pgn = get_pgn(&moves, &[]);
+    assert!(pgn.ends_with("5. hxg8=R *\n"), "{pgn}");
+
+    let games = read_pgn(&pgn);
+    let read: Vec<Move> = games[0].moves.iter().map(|pgn_move| pgn_move.mv).collect();
+    assert_eq!(read, moves);
+}
+
+#[test]
+fn clock_times_are_written_and_read_back() {
+    let moves = get_moves(&["e2e4", "e7e5", "g1f3"]);
+    let pgn = get_pgn_with_clocks(&moves, &[], &[299.5, 298.0, 3725.0]);
+
+    assert!(
+        pgn.ends_with(
+            "1. e4 {[%clk 0:04:59]} 1... e5 {[%clk 0:04:58]} 2. Nf3 {[%clk 1:02:05]} *\n"
+        ),
+        "{pgn}"
+    );
+
+    assert_eq!(parse_clock_comment("[%clk 1:02:05]"), Some(3725.0));
+    assert_eq!(
+        parse_clock_comment(" [%eval 0.2] [%clk 0:00:09.5] "),
+        Some(9.5)
+    );
+    assert_eq!(parse_clock_comment("[%clk 2:05]"), None);
+    assert_eq!(parse_clock_comment("no clock"), None);
+}
+
+#[test]
+fn armageddon_draws_go_to_black() {
+    let moves = get_moves(&[
+        "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+    ]);
+
+    let result = get_game_result(&moves).unwrap();
+    assert_eq!(result, GameResult::Repetition);
+    assert_eq!(result.armageddon_winner(), Player::Black);
+    assert_eq!(
+        GameResult::Checkmate {
+            winner: Player::White
+        }
+        .armageddon_score(),
+        "1-0"
+    );
+
+    let pgn = get_armageddon_pgn(&moves, &[]);
+    assert!(
+        pgn.starts_with("[Variant \"Armageddon\"]\n[Result \"0-1\"]\n"),
+        "{pgn}"
+    );
+    assert!(pgn.ends_with("4. Ng1 Ng8 0-1\n"), "{pgn}");
+    assert!(get_pgn(&moves, &[]).ends_with("4. Ng1 Ng8 1/2-1/2\n"));
+}
+
+#[test]
+fn annotated_games_are_read_with_their_variations() {
+    let pgn = "[Event \"Study: Chapter 1\"]\n\n\
+        {The start} 1. e4 $1 {Best by test [%cal Ge2e4] [%csl Re4]} \
+        (1. d4!? d5 (1... Nf6) 2. c4) 1... e5 2. Nf3 Nc6 3. O-O {unreachable} *\n\
+        [Event \"Second\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n";
+
+    let games = read_pgn(pgn);
+    assert_eq!(games.len(), 2);
+
+    let game = &games[0];
+    assert_eq!(game.tag("Event"), Some("Study: Chapter 1"));
+    assert_eq!(game.comment.as_deref(), Some("The start"));
+    // Castling isn't in these rules, so the line stops before it
+    assert_eq!(
+        game.moves.iter().map(|m| m.mv).collect::<Vec<_>>(),
+        get_moves(&["e2e4", "e7e5", "g1f3", "b8c6"])
+    );
+
+    let first = &game.moves[0];
+    assert_eq!(first.nags, vec![1]);
+    assert_eq!(first.comment.as_deref(), Some("Best by test"));
+    assert_eq!(
+        first.shapes,
+        vec![
+            PgnShape::Arrow {
+                from: Square::from_algebraic("e2").unwrap(),
+                to: Square::from_algebraic("e4").unwrap(),
+                color: 'G',
+            },
+            PgnShape::Circle {
+                square: Square::from_algebraic("e4").unwrap(),
+                color: 'R',
+            },
+        ]
+    );
+
+    let variation = &first.variations[0];
+    assert_eq!(
+        variation.iter().map(|m| m.mv).collect::<Vec<_>>(),
+        get_moves(&["d2d4", "d7d5", "c2c4"])
+    );
+    assert_eq!(variation[0].nags, vec![5]);
+    assert_eq!(get_nag_symbol(5), "!?");
+    assert_eq!(get_nag_symbol(22), "$22");
+    assert_eq!(variation[1].variations[0][0].mv, get_moves(&["g8f6"])[0]);
+
+    assert_eq!(games[1].moves.len(), 4);
+}
+
+#[test]
+fn shapes_with_odd_characters_are_skipped() {
+    let games = read_pgn("1. e4 {[%cal Gaé1,Ge2é] [%csl Ré4]} e5 *\n");
+
+    assert_eq!(games[0].moves.len(), 2);
+    assert!(games[0].moves[0].shapes.is_empty());
+}
+
+#[test]
+fn deeply_nested_variations_are_cut_short() {
+    let pgn = format!("1. e4 {}e5 *\n", "( e3 ".repeat(200_000));
+    let games = read_pgn(&pgn);
+
+    // Each level is an alternative to the e3 it is nested in
+    let mut depth = 0;
+    let mut line = &games[0].moves;
+    while let Some(variation) = line.last().and_then(|last| last.variations.first()) {
+        assert_eq!(variation[0].mv, get_moves(&["e2e3"])[0]);
+        line = variation;
+        depth += 1;
+    }
+    assert!(depth > 0 && depth < 1000, "{depth}");
+
+    // Unclosed, the outermost variation runs to the end of the game
+    assert_eq!(games[0].moves.len(), 1);
+}
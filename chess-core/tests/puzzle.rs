@@ -0,0 +1,43 @@
+use chess_core::{get_puzzle, is_blunder, EngineConfig, Move, Player, Square};
+
+fn get_moves(names: &[&str]) -> Vec<Move> {
+    names
+        .iter()
+        .map(|name| Move {
+            from: Square::from_algebraic(&name[..2]).unwrap(),
+            to: Square::from_algebraic(&name[2..]).unwrap(),
+            promotion: None,
+        })
+        .collect()
+}
+
+#[test]
+fn blunders_are_judged_from_the_movers_side() {
+    // White drops 300 with the first move, Black 50 with the second and
+    // then 250 with the third
+    let scores = [0, -300, -250, 0];
+
+    assert!(!is_blunder(&scores, 0));
+    assert!(is_blunder(&scores, 1));
+    assert!(!is_blunder(&scores, 2));
+    assert!(!is_blunder(&scores, 3));
+    assert!(!is_blunder(&scores, 4));
+
+    let scores = [0, 0, 250];
+    assert!(is_blunder(&scores, 2));
+}
+
+#[test]
+fn puzzles_are_solved_by_the_side_to_move() {
+    // g4 lets Black mate at once
+    let moves = get_moves(&["f2f3", "e7e5", "g2g4"]);
+    let puzzle = get_puzzle(&moves, &EngineConfig::default()).unwrap();
+
+    assert_eq!(puzzle.player(), Player::Black);
+    assert_eq!(puzzle.moves, moves);
+    assert_eq!(puzzle.solution, get_moves(&["d8h4"]));
+
+    // Nothing to solve once the game is over
+    let moves = get_moves(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert_eq!(get_puzzle(&moves, &EngineConfig::default()), None);
+}
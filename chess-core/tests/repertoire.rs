@@ -0,0 +1,61 @@
+use chess_core::{read_pgn, Move, Player, Repertoire, Square};
+
+fn get_moves(names: &[&str]) -> Vec<Move> {
+    names
+        .iter()
+        .map(|name| Move {
+            from: Square::from_algebraic(&name[..2]).unwrap(),
+            to: Square::from_algebraic(&name[2..]).unwrap(),
+            promotion: None,
+        })
+        .collect()
+}
+
+fn get_repertoire() -> Repertoire {
+    Repertoire::from_pgn(&read_pgn(
+        "[Event \"Open games\"]\n\n1. e4 e5 (1... c5 2. Nf3) 2. Nf3 Nc6 *\n\n\
+         [Event \"Queen's pawn\"]\n\n1. d4 d5 2. Nf3 *\n",
+    ))
+}
+
+#[test]
+fn book_moves_include_variations_and_other_games() {
+    let repertoire = get_repertoire();
+    assert!(!repertoire.is_empty());
+
+    assert_eq!(
+        repertoire.get_book_moves(&[]),
+        get_moves(&["e2e4", "d2d4"]).as_slice()
+    );
+    assert_eq!(
+        repertoire.get_book_moves(&get_moves(&["e2e4"])),
+        get_moves(&["e7e5", "c7c5"]).as_slice()
+    );
+    assert!(repertoire
+        .get_book_moves(&get_moves(&["e2e4", "e7e5", "g1f3", "b8c6"]))
+        .is_empty());
+}
+
+#[test]
+fn deviations_are_only_found_for_the_given_sides() {
+    let repertoire = get_repertoire();
+    let players = [Player::White];
+
+    let moves = get_moves(&["e2e4", "c7c5", "g1f3"]);
+    assert_eq!(repertoire.find_deviation(&moves, &players), None);
+
+    let moves = get_moves(&["e2e4", "c7c5", "b1c3"]);
+    assert_eq!(repertoire.find_deviation(&moves, &players), Some(2));
+
+    // Black left it first, so White's next move is no deviation
+    let moves = get_moves(&["e2e4", "d7d5", "b1c3"]);
+    assert_eq!(repertoire.find_deviation(&moves, &players), None);
+    assert_eq!(
+        repertoire.find_deviation(&moves, &[Player::White, Player::Black]),
+        Some(1)
+    );
+
+    // Past the end of the lines
+    let moves = get_moves(&["d2d4", "d7d5", "g1f3", "g8f6", "c2c4"]);
+    assert_eq!(repertoire.find_deviation(&moves, &players), None);
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use chess_core::{get_board_after_moves, get_pgn, read_pgn};
+use libfuzzer_sys::fuzz_target;
+
+// PGN comes from files and lichess studies, so any text has to be read
+// without panicking, and what is read has to be moves that can be played
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    for game in read_pgn(text) {
+        let moves: Vec<_> = game.moves.iter().map(|pgn_move| pgn_move.mv).collect();
+        get_board_after_moves(&moves);
+        get_pgn(&moves, &[]);
+    }
+});
@@ -0,0 +1,29 @@
+// Save and replay files can hold any moves at all, including ones from
+// squares off the board, so replaying them must never panic
+#![no_main]
+
+use chess_core::{get_board_after_moves, get_fen, get_legal_moves, Move, Square};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let moves: Vec<Move> = data
+        .chunks_exact(4)
+        .map(|bytes| Move {
+            from: Square(bytes[0] as i8 as i32, bytes[1] as i8 as i32),
+            to: Square(bytes[2] as i8 as i32, bytes[3] as i8 as i32),
+            promotion: None,
+        })
+        .collect();
+
+    let board = get_board_after_moves(&moves);
+    get_fen(&moves);
+
+    for (piece_type, player, position) in board.pieces() {
+        get_legal_moves(&piece_type, &position, &player, &board);
+    }
+
+    // Announcements and the move list name the squares of every move
+    for mv in &moves {
+        let _ = format!("{}-{}", mv.from, mv.to);
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use chess_core::Square;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(name) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Some(square) = Square::from_algebraic(name) {
+        assert!(square.is_on_board());
+        assert_eq!(square.to_string(), name);
+    }
+});
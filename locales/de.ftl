@@ -0,0 +1,228 @@
+## Key bindings screen
+
+key-bindings-title = Tastenbelegung
+key-bindings-row = { $action }: { $keys }
+key-bindings-waiting = Taste drücken...
+key-bindings-unbound = nicht belegt
+key-bindings-help = Auf/Ab wählen, Enter neu belegen, Rücktaste zurücksetzen, Esc schließen
+
+binding-shift = Umschalt+{ $key }
+binding-mouse = { $button ->
+        [Left] Linke Maustaste
+        [Right] Rechte Maustaste
+        [Middle] Mittlere Maustaste
+       *[other] Maustaste { $button }
+    }
+binding-scroll-up = Mausrad hoch
+binding-scroll-down = Mausrad runter
+
+action-select = Feld unter der Maus wählen
+action-cursor-left = Cursor nach links
+action-cursor-right = Cursor nach rechts
+action-cursor-up = Cursor nach oben
+action-cursor-down = Cursor nach unten
+action-confirm = Feld unter dem Cursor wählen
+action-next-piece = Nächste ziehbare Figur
+action-previous-piece = Vorherige ziehbare Figur
+action-clear-selection = Auswahl aufheben
+action-save-screenshot = Brettbild speichern
+action-save-animation = Partie als Animation speichern
+action-toggle-qr-code = QR-Code der Stellung zeigen
+action-remap-keys = Tastenbelegung
+action-cycle-theme = Nächstes Farbschema
+action-cycle-language = Nächste Sprache
+action-increase-ui-scale = Größere Schrift
+action-decrease-ui-scale = Kleinere Schrift
+action-toggle-diagnostics = Debug-Informationen
+action-find-mate = Erzwungenes Matt suchen
+action-toggle-heatmap = Angegriffene Felder zeigen, nach der Partie die Wege der Figuren
+action-previous-move = Vorheriger Zug, nach der Partie
+action-next-move = Nächster Zug, nach der Partie
+action-first-move = Ausgangsstellung, nach der Partie
+action-last-move = Schlussstellung, nach der Partie
+action-preview-line = Beste Engine-Variante als Geisterfiguren zeigen, nach der Partie
+action-request-takeback = Zurücknahme des letzten Zuges erbitten
+action-accept-takeback = Zurücknahme erlauben
+action-decline-takeback = Zurücknahme ablehnen
+action-play-from-here = Nach der Partie ab der gezeigten Stellung gegen die Engine weiterspielen, oder zu ihr zurück
+action-toggle-exploration = Züge an der gezeigten Stellung ausprobieren oder zur Partie zurückkehren
+action-next-variation = Nächste Variante ab dieser Stellung
+action-previous-variation = Vorherige Variante ab dieser Stellung
+action-promote-variation = Variante in Richtung Hauptvariante verschieben
+action-delete-variation = Variante löschen
+action-toggle-move-panel = Züge und Varianten zeigen
+action-toggle-comment-box = Kommentare unter dem Brett zeigen
+action-clear-arrows = Pfeile und Kreise vom Brett nehmen
+action-fit-board = Ganzes Brett zeigen
+action-toggle-isometric-view = Zwischen flachem und isometrischem Brett wechseln
+action-toggle-analysis-window = Ein zweites Fenster zum Analysieren der Partie öffnen
+action-call-pawn = Hand und Hirn: Bauer ansagen
+action-call-knight = Hand und Hirn: Springer ansagen
+action-call-bishop = Hand und Hirn: Läufer ansagen
+action-call-rook = Hand und Hirn: Turm ansagen
+action-call-queen = Hand und Hirn: Dame ansagen
+action-call-king = Hand und Hirn: König ansagen
+action-save-puzzle = Den gezeigten Patzer als Aufgabe behalten, nach der Partie
+action-next-puzzle = Nächste Aufgabe aus den eigenen Partien
+action-leave-puzzles = Aufgaben verlassen und zur Partie zurückkehren
+action-next-maze = Nächstes Labyrinth, um die Gangart einer Figur zu üben
+action-leave-maze = Labyrinthe verlassen und zur Partie zurückkehren
+action-seal-move = Zug abgeben und die Partie unterbrechen
+action-resume-last-game = Letzte Partie fortsetzen
+
+## Notifications
+
+toast-king-in-check = Ungültiger Zug: Der König stünde im Schach
+toast-confirm-move = Feld erneut anklicken oder Enter drücken, um den Zug zu spielen
+toast-pick-promotion = Wähle auf der Linie des Bauern, in welche Figur er sich verwandelt
+toast-board-image-saved = Brettbild gespeichert unter { $path }
+toast-board-image-failed = Das Brettbild konnte nicht gespeichert werden
+toast-animation-saved = Animation gespeichert unter { $path }
+toast-animation-failed = Die Animation konnte nicht gespeichert werden
+toast-pieces-not-loaded = Die Figurenbilder sind noch nicht geladen
+toast-no-pictures-directory = Es gibt keinen Bilderordner zum Speichern
+toast-qr-code-failed = Für die Stellung konnte kein QR-Code erstellt werden
+toast-resume-failed = Die gespeicherte Partie konnte nicht geladen werden
+toast-no-last-game = Es gibt keine Partie zum Fortsetzen
+toast-autosave-failed = Die Partie konnte nicht gespeichert werden
+toast-settings-failed = Die Einstellungsdatei konnte nicht gelesen werden
+toast-mate-found = Matt in { $moves }, beginnend mit { $move }
+toast-no-mate = Kein erzwungenes Matt in höchstens { $moves } Zügen
+toast-takeback-accepted = Der Zug wurde zurückgenommen
+toast-takeback-declined = Die Zurücknahme wurde abgelehnt
+toast-game-archived = Die beendete Partie liegt unter { $path }
+toast-archive-failed = Die beendete Partie konnte nicht gesichert werden
+toast-study-imported = Studie { $name } geöffnet
+toast-study-failed = Die Studie konnte nicht geöffnet werden
+toast-brain-no-moves = Figurenart { piece-name } kann nicht ziehen, bitte eine andere ansagen
+toast-puzzle-not-ready = Die Partie wird noch analysiert
+toast-not-a-blunder = Der gezeigte Zug ist kein Patzer
+toast-puzzle-saved = Stellung als Aufgabe { $count } behalten
+toast-puzzle-failed = Die Stellung konnte nicht als Aufgabe behalten werden
+toast-puzzles-failed = Die Aufgaben konnten nicht gelesen werden
+toast-no-puzzles = Noch keine Aufgaben. Nach einer Partie lassen sich Patzer als Aufgaben behalten
+toast-puzzle-wrong-move = Nicht der richtige Zug, noch einmal versuchen
+toast-puzzle-solved = Aufgabe gelöst
+toast-repertoire-failed = Das Eröffnungsrepertoire konnte nicht gelesen werden
+toast-maze-failed = Es konnte kein Labyrinth angelegt werden
+toast-adjourn-failed = Die Partie konnte nicht unterbrochen werden und geht weiter
+toast-adjourned-failed = Die unterbrochene Partie konnte nicht geöffnet werden
+toast-sealed-move = Der abgegebene Zug war { $move }
+toast-maze-done = Alle Ziele in { $moves } Zügen ({ $par } bestenfalls), { $seconds } Sekunden
+notification-title = Schach
+notification-opponent-moved = { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    } spielte { $move }. Du bist am Zug
+
+## Status
+
+hud-fifty-moves = Halbzüge zur 50-Züge-Regel: { $halfmoves }/{ $limit }
+hud-repetitions = Wiederholungen dieser Stellung: { $count }/{ $limit }
+hud-king-safety = Königssicherheit { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    }: { $meter }
+
+## Analysis
+
+game-over-checkmate = Schachmatt, { $winner ->
+        [white] Weiß
+       *[black] Schwarz
+    } gewinnt
+game-over-stalemate = Remis durch Patt
+game-over-repetition = Remis durch dreifache Stellungswiederholung
+game-over-fifty-moves = Remis durch die 50-Züge-Regel
+game-over-armageddon = { $draw }, damit gewinnt Schwarz das Armageddon
+game-over-performance = Geschätzte Turnierleistung: Weiß { $white }, Schwarz { $black }
+mate-tree-title = Matt in { $moves }
+
+## Repertoire
+
+repertoire-left = Abseits des Repertoires
+repertoire-left-book = Mit { $move } das Repertoire verlassen, dort steht { $book }
+
+## Exploration
+
+exploration-banner = Analysebrett: Diese Züge gehören nicht zur Partie. Mit X zurück
+
+## Puzzles
+
+puzzle-banner = Aufgabe { $number } von { $count }: Finde die beste Fortsetzung für { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    }
+puzzle-solved-banner = Aufgabe { $number } von { $count } gelöst. F11 für die nächste, Umschalt+F11 zurück zur Partie
+
+## Adjournment
+
+sealing-banner = Den abzugebenden Zug wählen. Er bleibt verborgen und die Partie wird unterbrochen. S, um weiterzuspielen
+
+## Mazes
+
+maze-banner = Figurenart { piece-name } auf alle Ziele ziehen: { $found } von { $targets } in { $moves } Zügen, { $seconds } s
+maze-done-banner = Geschafft in { $moves } Zügen ({ $par } bestenfalls), { $seconds } s. Z für das nächste Labyrinth, Umschalt+Z zurück zur Partie
+
+## Analysis window
+
+analysis-window-title = Analyse
+analysis-window-status = Halbzug { $ply } von { $plies }, { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    } am Zug
+
+## Takebacks
+
+takeback-prompt = { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    } möchte den letzten Zug zurücknehmen. Erlauben? (Y/N)
+brain-prompt = Hirn von { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    }: Figur zum Ziehen ansagen (1-6)
+hand-prompt = Hand von { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    }: mit { piece-name } ziehen
+
+## Screen readers
+
+board-name = Schachbrett
+square-empty = { $square }, leer
+square-occupied = { $square }, { colored-piece }
+move-description = { colored-piece } { $from } nach { $to }
+move-capture = , schlägt { piece-name }
+move-check = , Schach
+move-announcement = { $move }. { $player ->
+        [white] Weiß
+       *[black] Schwarz
+    } am Zug.
+
+## Spoken moves
+
+spoken-move = { spoken-piece } { $to }
+spoken-capture = { spoken-piece } schlägt { $to }
+spoken-check = { $move }, Schach
+
+## Pieces
+
+piece-name = { $piece ->
+        [king] König
+        [queen] Dame
+        [knight] Springer
+        [bishop] Läufer
+        [rook] Turm
+       *[pawn] Bauer
+    }
+colored-piece = { $player ->
+        [white] { $piece ->
+            [queen] weiße
+           *[other] weißer
+        }
+       *[black] { $piece ->
+            [queen] schwarze
+           *[other] schwarzer
+        }
+    } { piece-name }
+spoken-piece = { piece-name }
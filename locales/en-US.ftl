@@ -0,0 +1,229 @@
+## Key bindings screen
+
+key-bindings-title = Key bindings
+key-bindings-row = { $action }: { $keys }
+key-bindings-waiting = press a key...
+key-bindings-unbound = unbound
+key-bindings-help = Up/Down choose, Enter rebind, Backspace reset, Esc close
+
+binding-shift = Shift+{ $key }
+binding-mouse = { $button ->
+        [Left] Left mouse button
+        [Right] Right mouse button
+        [Middle] Middle mouse button
+       *[other] Mouse button { $button }
+    }
+binding-scroll-up = Mouse wheel up
+binding-scroll-down = Mouse wheel down
+
+action-select = Select square under the mouse
+action-cursor-left = Cursor left
+action-cursor-right = Cursor right
+action-cursor-up = Cursor up
+action-cursor-down = Cursor down
+action-confirm = Select square under the cursor
+action-next-piece = Next movable piece
+action-previous-piece = Previous movable piece
+action-clear-selection = Clear selection
+action-save-screenshot = Save board picture
+action-save-animation = Save game animation
+action-toggle-qr-code = Show position QR code
+action-remap-keys = Key bindings
+action-cycle-theme = Next color theme
+action-cycle-language = Next language
+action-increase-ui-scale = Larger text
+action-decrease-ui-scale = Smaller text
+action-toggle-diagnostics = Debug information
+action-find-mate = Look for a forced mate
+action-toggle-heatmap = Show attacked squares, or where pieces went after the game
+action-previous-move = Previous move, after the game
+action-next-move = Next move, after the game
+action-first-move = Starting position, after the game
+action-last-move = Final position, after the game
+action-preview-line = Play the engine's best line as ghost pieces, after the game
+action-request-takeback = Ask to take back the last move
+action-accept-takeback = Allow a takeback
+action-decline-takeback = Refuse a takeback
+action-play-from-here = Play on from the shown position against the engine after the game, or go back to it
+action-toggle-exploration = Try out moves on the shown position, or go back to the game
+action-next-variation = Next line from this position
+action-previous-variation = Previous line from this position
+action-promote-variation = Move this line up towards the main line
+action-delete-variation = Delete this line
+action-toggle-move-panel = Show the moves and their variations
+action-toggle-comment-box = Show the comments below the board
+action-clear-arrows = Clear the arrows and circles off the board
+action-fit-board = Fit the whole board in view
+action-toggle-isometric-view = Switch between the flat and isometric board
+action-toggle-analysis-window = Open a second window to analyse the game in
+action-call-pawn = Hand and brain: call a pawn
+action-call-knight = Hand and brain: call a knight
+action-call-bishop = Hand and brain: call a bishop
+action-call-rook = Hand and brain: call a rook
+action-call-queen = Hand and brain: call the queen
+action-call-king = Hand and brain: call the king
+action-save-puzzle = Keep the blunder shown as a puzzle, after the game
+action-next-puzzle = Next puzzle from your own games
+action-leave-puzzles = Leave the puzzles and go back to the game
+action-next-maze = Next maze, to practise how a piece moves
+action-leave-maze = Leave the mazes and go back to the game
+action-seal-move = Seal your move and adjourn the game
+action-resume-last-game = Resume the last game
+
+## Notifications
+
+toast-king-in-check = Illegal move: your king would be in check
+toast-confirm-move = Click the square again or press Enter to play the move
+toast-pick-promotion = Pick what the pawn becomes from the pieces on its file
+toast-board-image-saved = Saved the board picture to { $path }
+toast-board-image-failed = Could not save the board picture
+toast-animation-saved = Saved the game animation to { $path }
+toast-animation-failed = Could not save the game animation
+toast-pieces-not-loaded = The piece images are not loaded yet
+toast-no-pictures-directory = There is no pictures folder to save to
+toast-qr-code-failed = Could not make a QR code of the position
+toast-resume-failed = Could not load the saved game
+toast-no-last-game = There is no game to resume
+toast-autosave-failed = Could not save the game
+toast-settings-failed = Could not read the settings file
+toast-mate-found = Mate in { $moves }, starting with { $move }
+toast-no-mate = No forced mate in { $moves } moves or fewer
+toast-takeback-accepted = The move was taken back
+toast-takeback-declined = The takeback was refused
+toast-game-archived = Kept the finished game at { $path }
+toast-archive-failed = Could not keep a copy of the finished game
+toast-study-imported = Opened the study { $name }
+toast-study-failed = Could not open the study
+toast-brain-no-moves = No { piece-name } can move, call another piece
+toast-puzzle-not-ready = The game is still being analysed
+toast-not-a-blunder = The move shown is not a blunder
+toast-puzzle-saved = Kept the position as puzzle { $count }
+toast-puzzle-failed = Could not keep the position as a puzzle
+toast-puzzles-failed = Could not read the puzzles
+toast-no-puzzles = No puzzles yet. Keep blunders from your games as puzzles after a game
+toast-puzzle-wrong-move = Not the move, try again
+toast-puzzle-solved = Puzzle solved
+toast-repertoire-failed = Could not read the opening repertoire
+toast-maze-failed = Could not lay out a maze
+toast-adjourn-failed = Could not adjourn the game, so it goes on
+toast-adjourned-failed = Could not open the adjourned game
+toast-sealed-move = The sealed move was { $move }
+toast-maze-done = All targets in { $moves } moves ({ $par } at best), { $seconds } seconds
+notification-title = Chess
+notification-opponent-moved = { $player ->
+        [white] White
+       *[black] Black
+    } played { $move }. Your move
+
+## Status
+
+hud-fifty-moves = Half-moves toward the fifty-move rule: { $halfmoves }/{ $limit }
+hud-repetitions = Times this position occurred: { $count }/{ $limit }
+hud-king-safety = { $player ->
+        [white] White
+       *[black] Black
+    } king safety: { $meter }
+
+## Analysis
+
+game-over-checkmate = Checkmate, { $winner ->
+        [white] White
+       *[black] Black
+    } wins
+game-over-stalemate = Draw by stalemate
+game-over-repetition = Draw by threefold repetition
+game-over-fifty-moves = Draw by the fifty-move rule
+game-over-armageddon = { $draw }, so Black wins the armageddon
+game-over-performance = Estimated performance: White { $white }, Black { $black }
+mate-tree-title = Mate in { $moves }
+
+## Repertoire
+
+repertoire-left = Out of book
+repertoire-left-book = Left the repertoire with { $move }, where it has { $book }
+
+## Exploration
+
+exploration-banner = Analysis board: these moves are not part of the game. Press X to go back
+
+## Puzzles
+
+puzzle-banner = Puzzle { $number } of { $count }: find the best line for { $player ->
+        [white] White
+       *[black] Black
+    }
+puzzle-solved-banner = Puzzle { $number } of { $count } solved. F11 for the next one, Shift+F11 to go back to the game
+
+## Adjournment
+
+sealing-banner = Pick the move to seal. It stays hidden and the game is adjourned. S to go back to playing
+
+## Mazes
+
+maze-banner = Take the { piece-name } to every target: { $found } of { $targets } in { $moves } moves, { $seconds } s
+maze-done-banner = Done in { $moves } moves ({ $par } at best), { $seconds } s. Z for the next maze, Shift+Z to go back to the game
+
+## Analysis window
+
+analysis-window-title = Analysis
+analysis-window-status = Move { $ply } of { $plies }, { $player ->
+        [white] White
+       *[black] Black
+    } to move
+
+## Takebacks
+
+takeback-prompt = { $player ->
+        [white] White
+       *[black] Black
+    } asks to take back their last move. Allow it? (Y/N)
+brain-prompt = { $player ->
+        [white] White
+       *[black] Black
+    }'s brain: call a piece to move (1-6)
+hand-prompt = { $player ->
+        [white] White
+       *[black] Black
+    }'s hand: move a { piece-name }
+
+## Screen readers
+
+board-name = Chess board
+square-empty = { $square }, empty
+square-occupied = { $square }, { colored-piece }
+move-description = { colored-piece } { $from } to { $to }
+move-capture = , takes { piece-name }
+move-check = , check
+move-announcement = { $move }. { $player ->
+        [white] White
+       *[black] Black
+    } to move.
+
+## Spoken moves
+
+spoken-move = { spoken-piece } { $to }
+spoken-capture = { spoken-piece } takes { $to }
+spoken-check = { $move }, check
+
+## Pieces
+
+piece-name = { $piece ->
+        [king] king
+        [queen] queen
+        [knight] knight
+        [bishop] bishop
+        [rook] rook
+       *[pawn] pawn
+    }
+colored-piece = { $player ->
+        [white] white
+       *[black] black
+    } { piece-name }
+spoken-piece = { $piece ->
+        [king] King
+        [queen] Queen
+        [knight] Knight
+        [bishop] Bishop
+        [rook] Rook
+       *[pawn] Pawn
+    }
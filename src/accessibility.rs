@@ -0,0 +1,159 @@
+use bevy::{
+    a11y::{
+        accesskit::{Live, NodeBuilder, Role},
+        AccessibilityNode, Focus,
+    },
+    prelude::*,
+};
+use chess_core::{get_board_after_moves, is_king_attacked, BoardPosition, Move, Piece, Player};
+use fluent::fluent_args;
+
+use crate::{
+    board::{BoardRoot, Tile},
+    input::{KeyboardCursor, Selection},
+    locale::Localizer,
+    rules::{CurrentTurn, MoveHistory},
+    GameSet,
+};
+
+pub struct ScreenReaderPlugin;
+
+impl Plugin for ScreenReaderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_announcer)
+            .add_system(focus_cursor_square.in_set(GameSet::Render))
+            // Like the autosave, this needs captured pieces to be gone
+            .add_system(update_square_labels.in_base_set(CoreSet::PostUpdate))
+            .add_system(announce_moves.in_set(GameSet::Render));
+    }
+}
+
+// Tells assistive technology about moves as they happen
+#[derive(Component)]
+struct Announcer;
+
+fn spawn_announcer(mut commands: Commands) {
+    let mut announcer_node = NodeBuilder::new(Role::Status);
+    announcer_node.set_live(Live::Polite);
+    commands.spawn((AccessibilityNode::from(announcer_node), Announcer));
+}
+
+fn update_square_labels(
+    selection: Res<Selection>,
+    pieces: Query<(Entity, &Piece, &Player, &BoardPosition)>,
+    added_pieces: Query<(), Added<Piece>>,
+    history: Res<MoveHistory>,
+    localizer: Res<Localizer>,
+    mut board: Query<&mut AccessibilityNode, (With<BoardRoot>, Without<Tile>)>,
+    mut tiles: Query<(&BoardPosition, &mut AccessibilityNode), With<Tile>>,
+) {
+    if !history.is_changed()
+        && !selection.is_changed()
+        && !localizer.is_changed()
+        && added_pieces.is_empty()
+    {
+        return;
+    }
+
+    for mut node in board.iter_mut() {
+        node.set_name(localizer.get("board-name"));
+    }
+
+    for (tile_pos, mut node) in tiles.iter_mut() {
+        let square = tile_pos.square().to_string();
+        let piece = pieces.iter().find(|(.., position)| *position == tile_pos);
+
+        match piece {
+            Some((entity, piece_type, player, _)) => {
+                node.set_name(localizer.format(
+                    "square-occupied",
+                    &fluent_args![
+                        "square" => square,
+                        "player" => player.name(),
+                        "piece" => piece_type.name()
+                    ],
+                ));
+                node.set_selected(selection.piece() == Some(entity));
+            }
+            None => {
+                node.set_name(localizer.format("square-empty", &fluent_args!["square" => square]));
+                node.set_selected(false);
+            }
+        }
+    }
+}
+
+fn focus_cursor_square(
+    cursor: Query<(&BoardPosition, &Visibility), (With<KeyboardCursor>, Changed<BoardPosition>)>,
+    tiles: Query<(Entity, &BoardPosition), With<Tile>>,
+    mut focus: ResMut<Focus>,
+) {
+    let Ok((cursor_position, Visibility::Visible)) = cursor.get_single() else {
+        return;
+    };
+
+    **focus = tiles
+        .iter()
+        .find(|(_, position)| *position == cursor_position)
+        .map(|(entity, _)| entity);
+}
+
+fn announce_moves(
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    localizer: Res<Localizer>,
+    mut announcer: Query<&mut AccessibilityNode, With<Announcer>>,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    let Some((last_move, earlier_moves)) = history.moves.split_last() else {
+        return;
+    };
+
+    let Ok(mut node) = announcer.get_single_mut() else {
+        return;
+    };
+
+    node.set_name(localizer.format(
+        "move-announcement",
+        &fluent_args![
+            "move" => get_move_description(earlier_moves, last_move, &localizer),
+            "player" => current_turn.0.name()
+        ],
+    ));
+}
+
+fn get_move_description(earlier_moves: &[Move], mv: &Move, localizer: &Localizer) -> String {
+    let mut board = get_board_after_moves(earlier_moves);
+
+    let Some((piece_type, player)) = board.get(mv.from) else {
+        return format!("{}-{}", mv.from, mv.to);
+    };
+
+    let mut description = localizer.format(
+        "move-description",
+        &fluent_args![
+            "player" => player.name(),
+            "piece" => piece_type.name(),
+            "from" => mv.from.to_string(),
+            "to" => mv.to.to_string()
+        ],
+    );
+
+    if let Some((captured_type, _)) = board.get(mv.to) {
+        description.push_str(&localizer.format(
+            "move-capture",
+            &fluent_args!["piece" => captured_type.name()],
+        ));
+    }
+
+    board.apply_move(mv);
+
+    if is_king_attacked(&board, player.opponent()) {
+        description.push_str(&localizer.get("move-check"));
+    }
+
+    description
+}
@@ -0,0 +1,364 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use chess_core::{get_san, Board, BoardPosition, Move, Piece, Player};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explore::Exploration,
+    input::{Action, Actions, Selection},
+    locale::Localizer,
+    maze::MazeRun,
+    pieces::{BoardSetup, GameAssets},
+    puzzles::PuzzleAttempt,
+    rules::{CurrentTurn, GameOver, GameTime, MoveEvent, MoveHistory},
+    save::{GameSnapshot, ReplayPlayback},
+    settings::{read_ron_file, write_ron_file, Settings},
+    toast::Toast,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+const ADJOURNED_FILE_NAME: &str = "adjourned.ron";
+const KEY_FILE_NAME: &str = "adjourned.key";
+
+// Adjourning a game the old way: the player to move seals their move
+// without playing it, and the game stops there. The game and the sealed
+// move are saved encrypted, so the move isn't read off the save at a
+// glance. The key goes in the config directory, which on some platforms
+// is the data directory too, so this is an envelope rather than a safe:
+// anyone with the player's files can open it. Resuming with --resume
+// opens it and plays the sealed move before anything else
+pub struct AdjournPlugin {
+    pub resume: bool,
+}
+
+impl Plugin for AdjournPlugin {
+    fn build(&self, app: &mut App) {
+        let resume = self.resume;
+
+        // After the autosave is loaded, which the adjourned game replaces
+        app.init_resource::<AdjournDirs>()
+            .add_startup_system(
+                load_adjourned_game
+                    .run_if(move || resume)
+                    .in_base_set(StartupSet::PostStartup),
+            )
+            .add_system(toggle_sealing.in_set(GameSet::Input))
+            .add_system(
+                seal_move
+                    .run_if(resource_exists::<SealingMove>())
+                    .in_set(GameSet::Apply),
+            )
+            .add_system(
+                reveal_sealed_move
+                    .run_if(resource_exists::<SealedMove>())
+                    .in_set(GameSet::Rules),
+            )
+            .add_system(
+                update_sealing_banner
+                    .run_if(
+                        resource_added::<SealingMove>()
+                            .or_else(resource_removed::<SealingMove>())
+                            .or_else(resource_changed::<Localizer>())
+                            .or_else(resource_changed::<UiTheme>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+// Where the game and its key are kept, the same directory on macOS and
+// Windows. None where the platform has no such directory, which leaves
+// nothing to adjourn to
+#[derive(Resource, Clone)]
+pub struct AdjournDirs {
+    pub data: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+}
+
+impl Default for AdjournDirs {
+    fn default() -> Self {
+        Self {
+            data: dirs::data_dir().map(|dir| dir.join("chess")),
+            config: dirs::config_dir().map(|dir| dir.join("chess")),
+        }
+    }
+}
+
+impl AdjournDirs {
+    fn adjourned_path(&self) -> Option<PathBuf> {
+        self.data.as_ref().map(|dir| dir.join(ADJOURNED_FILE_NAME))
+    }
+
+    fn key_path(&self) -> Option<PathBuf> {
+        self.config.as_ref().map(|dir| dir.join(KEY_FILE_NAME))
+    }
+}
+
+// Present while the player to move picks the move to seal, which is then
+// kept off the board
+#[derive(Resource)]
+pub struct SealingMove;
+
+// The move sealed at adjournment, played once the game is back on the board
+#[derive(Resource)]
+pub struct SealedMove(pub Move);
+
+// What went in the envelope
+#[derive(Serialize, Deserialize)]
+pub struct SealedGame {
+    pub snapshot: GameSnapshot,
+    pub sealed: Move,
+}
+
+// As saved: a SealedGame in RON, encrypted
+#[derive(Serialize, Deserialize)]
+struct AdjournedGame {
+    nonce: Vec<u8>,
+    sealed: Vec<u8>,
+}
+
+#[derive(Component)]
+struct SealingBanner;
+
+fn load_adjourned_game(
+    mut commands: Commands,
+    mut history: ResMut<MoveHistory>,
+    mut current_turn: ResMut<CurrentTurn>,
+    mut board_setup: ResMut<BoardSetup>,
+    mut game_time: ResMut<GameTime>,
+    mut settings: ResMut<Settings>,
+    adjourn_dirs: Res<AdjournDirs>,
+    mut toasts: EventWriter<Toast>,
+) {
+    // None adjourned is no error
+    if !adjourn_dirs
+        .adjourned_path()
+        .is_some_and(|path| path.exists())
+    {
+        return;
+    }
+
+    match read_adjourned_game(&adjourn_dirs) {
+        Ok(game) => {
+            info!(
+                "resuming an adjourned game of {} moves",
+                game.snapshot.history.len()
+            );
+            game.snapshot.restore(
+                &mut history,
+                &mut current_turn,
+                &mut board_setup,
+                &mut game_time,
+                &mut settings,
+            );
+            commands.insert_resource(SealedMove(game.sealed));
+        }
+        Err(err) => {
+            warn!("could not open the adjourned game: {err}");
+            toasts.send(Toast::new("toast-adjourned-failed"));
+        }
+    }
+}
+
+fn toggle_sealing(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    current_turn: Res<CurrentTurn>,
+    sealing: Option<Res<SealingMove>>,
+    game_over: Option<Res<GameOver>>,
+    exploration: Option<Res<Exploration>>,
+    attempt: Option<Res<PuzzleAttempt>>,
+    maze: Option<Res<MazeRun>>,
+    replay: Option<Res<ReplayPlayback>>,
+) {
+    if !actions.just_pressed(Action::SealMove) {
+        return;
+    }
+
+    if sealing.is_some() {
+        info!("not sealing after all");
+        commands.remove_resource::<SealingMove>();
+        return;
+    }
+
+    // Only a game still being played, by the player whose move it is
+    if game_over.is_some()
+        || settings.engine_opponent == Some(current_turn.0)
+        || exploration.is_some()
+        || attempt.is_some()
+        || maze.is_some()
+        || replay.is_some()
+    {
+        return;
+    }
+
+    info!("{} seals their move", current_turn.0.name());
+    commands.insert_resource(SealingMove);
+}
+
+// The game stops once the move is sealed. If it can't be saved, the move
+// is dropped and the game goes on. Moves are drained rather than read, so
+// the game doesn't play the sealed one once sealing is over
+fn seal_move(
+    mut commands: Commands,
+    mut move_events: ResMut<Events<MoveEvent>>,
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    game_time: Res<GameTime>,
+    settings: Res<Settings>,
+    adjourn_dirs: Res<AdjournDirs>,
+    mut selection: ResMut<Selection>,
+    pieces: Query<(&Piece, &Player, &BoardPosition)>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(MoveEvent(mv)) = move_events.drain().next() else {
+        return;
+    };
+    *selection = Selection::Idle;
+    commands.remove_resource::<SealingMove>();
+
+    let game = SealedGame {
+        snapshot: GameSnapshot::capture(
+            pieces.iter(),
+            &current_turn,
+            &history,
+            &game_time,
+            &settings,
+        ),
+        sealed: mv,
+    };
+    match write_adjourned_game(&adjourn_dirs, &game) {
+        Ok(()) => {
+            info!("sealed the move, adjourning");
+            app_exit_events.send(AppExit);
+        }
+        Err(err) => {
+            warn!("could not adjourn the game: {err}");
+            toasts.send(Toast::new("toast-adjourn-failed"));
+        }
+    }
+}
+
+// The adjourned game is done with once its move is played, so a later
+// --resume goes on from the autosave as usual
+fn reveal_sealed_move(
+    mut commands: Commands,
+    sealed: Res<SealedMove>,
+    board: Res<Board>,
+    adjourn_dirs: Res<AdjournDirs>,
+    mut move_events: EventWriter<MoveEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let san = get_san(&board, &sealed.0);
+    info!("the sealed move was {san}");
+    toasts.send(Toast::new("toast-sealed-move").with_arg("move", san));
+    move_events.send(MoveEvent(sealed.0));
+    commands.remove_resource::<SealedMove>();
+
+    let paths = [adjourn_dirs.adjourned_path(), adjourn_dirs.key_path()];
+    for path in paths.into_iter().flatten() {
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("could not remove {}: {err}", path.display());
+        }
+    }
+}
+
+// A new key for every adjournment
+pub fn write_adjourned_game(adjourn_dirs: &AdjournDirs, game: &SealedGame) -> Result<(), String> {
+    let path = adjourn_dirs
+        .adjourned_path()
+        .ok_or("there is no data directory")?;
+    let key_path = adjourn_dirs
+        .key_path()
+        .ok_or("there is no config directory")?;
+
+    let mut key = [0; 32];
+    let mut nonce = [0; 12];
+    rand::thread_rng().fill_bytes(&mut key);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let plain = ron::to_string(game).map_err(|err| err.to_string())?;
+    let sealed = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(Nonce::from_slice(&nonce), plain.as_bytes())
+        .map_err(|_| "could not encrypt the game")?;
+
+    if let Some(dir) = key_path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    fs::write(&key_path, key).map_err(|err| err.to_string())?;
+
+    write_ron_file(
+        &path,
+        &AdjournedGame {
+            nonce: nonce.to_vec(),
+            sealed,
+        },
+    )
+}
+
+pub fn read_adjourned_game(adjourn_dirs: &AdjournDirs) -> Result<SealedGame, String> {
+    let path = adjourn_dirs
+        .adjourned_path()
+        .ok_or("there is no data directory")?;
+    let key_path = adjourn_dirs
+        .key_path()
+        .ok_or("there is no config directory")?;
+
+    let adjourned: AdjournedGame = read_ron_file(&path)?;
+    let key = fs::read(&key_path).map_err(|err| format!("no key: {err}"))?;
+    if key.len() != 32 || adjourned.nonce.len() != 12 {
+        return Err("the key or the saved game is damaged".to_string());
+    }
+
+    let plain = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(
+            Nonce::from_slice(&adjourned.nonce),
+            adjourned.sealed.as_slice(),
+        )
+        .map_err(|_| "the key doesn't open the saved game")?;
+
+    let plain = String::from_utf8(plain).map_err(|err| err.to_string())?;
+    ron::from_str(&plain).map_err(|err| err.to_string())
+}
+
+fn update_sealing_banner(
+    mut commands: Commands,
+    sealing: Option<Res<SealingMove>>,
+    localizer: Res<Localizer>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    banners: Query<Entity, With<SealingBanner>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(_), Some(game_assets), Ok(status_bar)) =
+        (sealing, game_assets, status_bars.get_single())
+    else {
+        return;
+    };
+
+    let text = localizer.get("sealing-banner");
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Mode,
+        &game_assets,
+        &theme,
+        SealingBanner,
+    );
+}
@@ -0,0 +1,588 @@
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_core::{
+    get_board_after_moves, get_fair_play_reports, get_game_result, get_performance_rating,
+    get_score, Board, EngineConfig, GameResult, Piece, Player,
+};
+use fluent::fluent_args;
+use futures_lite::future;
+
+use crate::{
+    board::BoardRoot,
+    input::{Action, Actions},
+    locale::Localizer,
+    pieces::{spawn_pieces, GameAssets},
+    rules::{GameOver, MoveHistory, ReplaceHistoryEvent},
+    save::archive_game,
+    settings::Settings,
+    toast::Toast,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::{ThemedBackground, UiTheme},
+    GameSet,
+};
+
+pub const EVAL_GRAPH_HEIGHT: f32 = 80.0;
+// Centipawns at the top and bottom of the graph. Larger leads, and mates,
+// are drawn at the edge
+const EVAL_GRAPH_CAP: i32 = 1000;
+// Sits on top of the evaluation graph
+pub const TIME_GRAPH_HEIGHT: f32 = 40.0;
+
+// What is shown once the game is over: the result, graphs of the engine's
+// view of each position and of the time each move took, and over the
+// board the activity heatmap. The moves can be stepped through or picked
+// on the graphs
+pub struct AnalysisPlugin;
+
+impl Plugin for AnalysisPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            update_game_over_banner
+                .run_if(
+                    resource_exists_and_changed::<GameOver>()
+                        .or_else(resource_removed::<GameOver>())
+                        .or_else(resource_changed::<UiTheme>())
+                        .or_else(resource_changed::<Settings>())
+                        .or_else(resource_exists_and_changed::<PerformanceRatings>()),
+                )
+                .in_set(GameSet::Render),
+        )
+        .add_system(start_analysis.run_if(resource_added::<GameOver>()))
+        .add_system(end_analysis.run_if(resource_removed::<GameOver>()))
+        .add_systems(
+            (
+                navigate_moves,
+                click_graphs,
+                show_viewed_position.run_if(resource_changed::<ViewedPly>()),
+                play_from_viewed_position.run_if(not(resource_exists::<ForkedGame>())),
+            )
+                .chain()
+                .distributive_run_if(resource_exists::<ViewedPly>())
+                .in_set(GameSet::Input),
+        )
+        .add_system(
+            leave_forked_game
+                .run_if(resource_exists::<ForkedGame>())
+                .in_set(GameSet::Input),
+        )
+        .add_system(finish_eval_graph)
+        .add_system(finish_performance_ratings)
+        .add_system(
+            highlight_viewed_column
+                .run_if(resource_exists::<ViewedPly>())
+                .in_set(GameSet::Render),
+        );
+    }
+}
+
+#[derive(Component)]
+struct GameOverBanner;
+
+fn update_game_over_banner(
+    mut commands: Commands,
+    game_over: Option<Res<GameOver>>,
+    localizer: Res<Localizer>,
+    game_assets: Res<GameAssets>,
+    theme: Res<UiTheme>,
+    settings: Res<Settings>,
+    ratings: Option<Res<PerformanceRatings>>,
+    banners: Query<Entity, With<GameOverBanner>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(game_over), Ok(status_bar)) = (game_over, status_bars.get_single()) else {
+        return;
+    };
+
+    let mut text = match game_over.0 {
+        GameResult::Checkmate { winner } => localizer.format(
+            "game-over-checkmate",
+            &fluent_args!["winner" => winner.name()],
+        ),
+        GameResult::Stalemate => localizer.get("game-over-stalemate"),
+        GameResult::Repetition => localizer.get("game-over-repetition"),
+        GameResult::FiftyMoves => localizer.get("game-over-fifty-moves"),
+    };
+    if settings.armageddon && game_over.0.winner().is_none() {
+        text = localizer.format("game-over-armageddon", &fluent_args!["draw" => text]);
+    }
+    if let Some(ratings) = ratings {
+        let [white, black] = ratings
+            .0
+            .map(|rating| rating.map_or("?".to_string(), |rating| rating.to_string()));
+        text.push('\n');
+        text.push_str(&localizer.format(
+            "game-over-performance",
+            &fluent_args!["white" => white, "black" => black],
+        ));
+    }
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Result,
+        &game_assets,
+        &theme,
+        GameOverBanner,
+    );
+}
+
+// How many moves into the game the board shows, once the game is over
+#[derive(Resource)]
+pub struct ViewedPly(pub usize);
+
+// The engine's score of each position of the game, from White's side, once
+// the evaluation graph has been worked out
+#[derive(Resource)]
+pub struct EvalScores(pub Vec<i32>);
+
+// Engine scores are worked out off the main thread, one per position
+#[derive(Component)]
+struct EvalGraphTask(Task<Vec<i32>>);
+
+// Estimated from how close each side played to the engine, White's first.
+// None for a side with too few moves to tell
+#[derive(Resource)]
+struct PerformanceRatings([Option<u32>; 2]);
+
+#[derive(Component)]
+struct PerformanceTask(Task<[Option<u32>; 2]>);
+
+// The evaluation and time graphs
+#[derive(Component)]
+struct AnalysisGraph;
+
+// Picks the position after this many moves, in either graph
+#[derive(Component)]
+struct GraphColumn(usize);
+
+fn start_analysis(mut commands: Commands, history: Res<MoveHistory>, theme: Res<UiTheme>) {
+    commands.insert_resource(ViewedPly(history.moves.len()));
+    spawn_time_graph(&mut commands, &theme, &history.times);
+
+    let moves = history.moves.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let config = EngineConfig::default();
+        let mut board = get_board_after_moves(&[]);
+
+        (0..=moves.len())
+            .map(|ply| {
+                if ply > 0 {
+                    board.apply_move(&moves[ply - 1]);
+                }
+
+                // Always from White's side, so the graph reads one way
+                if ply % 2 == 0 {
+                    get_score(&board, Player::White, &config)
+                } else {
+                    -get_score(&board, Player::Black, &config)
+                }
+            })
+            .collect()
+    });
+    commands.spawn(EvalGraphTask(task));
+
+    let moves = history.moves.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        get_fair_play_reports(&moves, &[], &EngineConfig::default())
+            .map(|report| get_performance_rating(&report))
+    });
+    commands.spawn(PerformanceTask(task));
+}
+
+fn end_analysis(
+    mut commands: Commands,
+    graphs: Query<
+        Entity,
+        Or<(
+            With<AnalysisGraph>,
+            With<EvalGraphTask>,
+            With<PerformanceTask>,
+        )>,
+    >,
+) {
+    commands.remove_resource::<ViewedPly>();
+    commands.remove_resource::<EvalScores>();
+    commands.remove_resource::<PerformanceRatings>();
+
+    for entity in graphs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_moves(
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    mut viewed_ply: ResMut<ViewedPly>,
+) {
+    let last_ply = history.moves.len();
+
+    let ply = if actions.just_pressed(Action::PreviousMove) {
+        viewed_ply.0.saturating_sub(1)
+    } else if actions.just_pressed(Action::NextMove) {
+        (viewed_ply.0 + 1).min(last_ply)
+    } else if actions.just_pressed(Action::FirstMove) {
+        0
+    } else if actions.just_pressed(Action::LastMove) {
+        last_ply
+    } else {
+        return;
+    };
+
+    // Only mark it changed when it is, so the board isn't rebuilt
+    if ply != viewed_ply.0 {
+        viewed_ply.0 = ply;
+    }
+}
+
+// Lays out the pieces of the viewed position, which the rules then take as
+// the board. No moves are made after the game, so the two can't clash
+fn show_viewed_position(
+    mut commands: Commands,
+    viewed_ply: Res<ViewedPly>,
+    history: Res<MoveHistory>,
+    game_assets: Res<GameAssets>,
+    mut board: ResMut<Board>,
+    pieces: Query<Entity, With<Piece>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    // Freshly inserted, it already matches the board
+    if viewed_ply.is_added() {
+        return;
+    }
+
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    *board = get_board_after_moves(&history.moves[..viewed_ply.0]);
+    spawn_pieces(&mut commands, &game_assets, board_root, board.pieces());
+}
+
+// A game played on from a position of a finished one. The finished game
+// is kept aside until it's left, and the engine's side is kept here, so
+// the settings stay as they were
+#[derive(Resource)]
+pub struct ForkedGame {
+    pub game: MoveHistory,
+    pub engine: Player,
+}
+
+// Plays on from the viewed position against the engine, as a game of its
+// own that starts with the moves up to there
+fn play_from_viewed_position(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    viewed_ply: Res<ViewedPly>,
+    history: Res<MoveHistory>,
+    settings: Res<Settings>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !actions.just_pressed(Action::PlayFromHere) {
+        return;
+    }
+
+    // Nothing to play on from
+    if get_game_result(&history.moves[..viewed_ply.0]).is_some() {
+        return;
+    }
+
+    match archive_game(&history, &settings) {
+        Ok(path) => {
+            info!("archived the game to {}", path.display());
+            toasts.send(Toast::new("toast-game-archived").with_arg("path", path.display()));
+        }
+        Err(err) => {
+            warn!("could not archive the game: {err}");
+            toasts.send(Toast::new("toast-archive-failed"));
+        }
+    }
+
+    // The player takes the side to move, unless the engine already has one
+    let engine = settings
+        .engine_opponent
+        .unwrap_or(if viewed_ply.0.is_multiple_of(2) {
+            Player::Black
+        } else {
+            Player::White
+        });
+
+    info!("playing on from move {}", viewed_ply.0);
+    commands.insert_resource(ForkedGame {
+        game: history.clone(),
+        engine,
+    });
+    replace_events.send(ReplaceHistoryEvent(MoveHistory {
+        moves: history.moves[..viewed_ply.0].to_vec(),
+        times: history.times.iter().take(viewed_ply.0).copied().collect(),
+        tree: history.tree.clone(),
+    }));
+}
+
+// Back to the finished game, the same key as left it
+fn leave_forked_game(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    forked: Res<ForkedGame>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+) {
+    if !actions.just_pressed(Action::PlayFromHere) {
+        return;
+    }
+
+    info!("back to the finished game");
+    replace_events.send(ReplaceHistoryEvent(forked.game.clone()));
+    commands.remove_resource::<ForkedGame>();
+}
+
+fn finish_eval_graph(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    mut tasks: Query<(Entity, &mut EvalGraphTask)>,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(scores) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+        spawn_eval_graph(&mut commands, &theme, &scores);
+        commands.insert_resource(EvalScores(scores));
+    }
+}
+
+fn finish_performance_ratings(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut PerformanceTask)>,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(ratings) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+        info!("estimated performance: {ratings:?}");
+        commands.insert_resource(PerformanceRatings(ratings));
+    }
+}
+
+// A column per position, with a bar from the middle up for White's
+// advantage or down for Black's, topped by a dot. Clicking a column shows
+// that position
+fn spawn_eval_graph(commands: &mut Commands, theme: &UiTheme, scores: &[i32]) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(0.0),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(100.0), Val::Px(EVAL_GRAPH_HEIGHT)),
+                    ..default()
+                },
+                background_color: theme.panel.into(),
+                ..default()
+            },
+            ThemedBackground(|theme| theme.panel),
+            AnalysisGraph,
+        ))
+        .with_children(|graph| {
+            for (ply, score) in scores.iter().enumerate() {
+                // 0 at the bottom, 1 at the top
+                let height = 0.5
+                    + (*score).clamp(-EVAL_GRAPH_CAP, EVAL_GRAPH_CAP) as f32
+                        / (2 * EVAL_GRAPH_CAP) as f32;
+                let (bar_bottom, bar_top) = if height > 0.5 {
+                    (0.5, height)
+                } else {
+                    (height, 0.5)
+                };
+
+                graph
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                flex_grow: 1.0,
+                                size: Size::height(Val::Percent(100.0)),
+                                ..default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..default()
+                        },
+                        GraphColumn(ply),
+                    ))
+                    .with_children(|column| {
+                        column.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: UiRect {
+                                        bottom: Val::Percent(bar_bottom * 100.0),
+                                        ..default()
+                                    },
+                                    size: Size::new(
+                                        Val::Percent(100.0),
+                                        Val::Percent((bar_top - bar_bottom) * 100.0),
+                                    ),
+                                    ..default()
+                                },
+                                background_color: theme.graph_fill.into(),
+                                ..default()
+                            },
+                            ThemedBackground(|theme| theme.graph_fill),
+                        ));
+                        column.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: UiRect {
+                                        bottom: Val::Percent(height * 100.0),
+                                        ..default()
+                                    },
+                                    size: Size::new(Val::Percent(100.0), Val::Px(3.0)),
+                                    ..default()
+                                },
+                                background_color: theme.graph_line.into(),
+                                ..default()
+                            },
+                            ThemedBackground(|theme| theme.graph_line),
+                        ));
+                    });
+            }
+        });
+}
+
+// A bar per move for how long it took, White's light and Black's dark,
+// lined up with the columns of the evaluation graph below
+fn spawn_time_graph(commands: &mut Commands, theme: &UiTheme, move_times: &[f64]) {
+    let think_times: Vec<f64> = move_times
+        .iter()
+        .scan(0.0, |previous, &time| {
+            let think_time = time - *previous;
+            *previous = time;
+            Some(think_time.max(0.0))
+        })
+        .collect();
+    let longest = think_times.iter().copied().fold(0.0, f64::max);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(EVAL_GRAPH_HEIGHT),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(100.0), Val::Px(TIME_GRAPH_HEIGHT)),
+                    ..default()
+                },
+                background_color: theme.panel.into(),
+                ..default()
+            },
+            ThemedBackground(|theme| theme.panel),
+            AnalysisGraph,
+        ))
+        .with_children(|graph| {
+            // The starting position took no time, but keeps the columns
+            // in line with the other graph
+            let bars = std::iter::once(None).chain(think_times.iter().map(Some));
+
+            for (ply, think_time) in bars.enumerate() {
+                graph
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                flex_grow: 1.0,
+                                size: Size::height(Val::Percent(100.0)),
+                                ..default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..default()
+                        },
+                        GraphColumn(ply),
+                    ))
+                    .with_children(|column| {
+                        let Some(think_time) = think_time else {
+                            return;
+                        };
+                        let height = if longest > 0.0 {
+                            think_time / longest
+                        } else {
+                            0.0
+                        };
+                        // Odd plies are White's moves
+                        let themed = if ply % 2 == 1 {
+                            ThemedBackground(|theme| theme.white_bar)
+                        } else {
+                            ThemedBackground(|theme| theme.black_bar)
+                        };
+
+                        column.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: UiRect {
+                                        bottom: Val::Px(0.0),
+                                        ..default()
+                                    },
+                                    size: Size::new(
+                                        Val::Percent(100.0),
+                                        Val::Percent(height as f32 * 100.0),
+                                    ),
+                                    ..default()
+                                },
+                                background_color: (themed.0)(theme).into(),
+                                ..default()
+                            },
+                            themed,
+                        ));
+                    });
+            }
+        });
+}
+
+fn click_graphs(
+    columns: Query<(&Interaction, &GraphColumn), Changed<Interaction>>,
+    mut viewed_ply: ResMut<ViewedPly>,
+) {
+    for (interaction, column) in columns.iter() {
+        if *interaction == Interaction::Clicked && viewed_ply.0 != column.0 {
+            viewed_ply.0 = column.0;
+        }
+    }
+}
+
+// Marks the viewed position's column, whichever way it was picked
+fn highlight_viewed_column(
+    viewed_ply: Res<ViewedPly>,
+    theme: Res<UiTheme>,
+    mut columns: Query<(&GraphColumn, &mut BackgroundColor)>,
+) {
+    for (column, mut background_color) in columns.iter_mut() {
+        let color = if column.0 == viewed_ply.0 {
+            theme.highlight
+        } else {
+            Color::NONE
+        };
+
+        // The graph can turn up after the ply was picked, so this runs
+        // every frame, but only touches columns that are out of date
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, camera::ScalingMode, view::RenderLayers},
+    window::WindowRef,
+};
+use chess_core::{
+    get_all_legal_moves, get_board_after_moves, Board, BoardPosition, Move, Player, Square,
+    BOARD_SIZE,
+};
+use fluent::fluent_args;
+
+use crate::{
+    board::{get_square_at, get_tile_color, PIECE_SIZE, PIECE_Z_INDEX, TILE_Z_INDEX},
+    board_3d::blend,
+    camera::get_window_ray,
+    input::{Action, Actions},
+    locale::Localizer,
+    pieces::{get_atlas_index, GameAssets},
+    rules::MoveHistory,
+    settings::Settings,
+    ui_theme::UiTheme,
+    GameState,
+};
+
+// Only the analysis window's camera draws this layer, and it draws nothing
+// else, so the two boards can share the same coordinates
+const ANALYSIS_LAYER: u8 = 1;
+// Room above the board for the status line
+const STATUS_HEIGHT: f32 = 40.0;
+
+// A second window with a board of its own, starting from the game as it
+// was when opened, to step through and try moves on while the game goes
+// on in the main window. Nothing done there touches the game
+pub struct AnalysisWindowPlugin;
+
+impl Plugin for AnalysisWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_analysis_window.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                (
+                    close_analysis_board,
+                    handle_analysis_keys,
+                    handle_analysis_clicks,
+                    draw_analysis_board,
+                )
+                    .chain()
+                    .distributive_run_if(resource_exists::<AnalysisBoard>()),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct AnalysisBoard {
+    window: Entity,
+    camera: Entity,
+    // The line on the board, of which the first `ply` moves are played
+    moves: Vec<Move>,
+    ply: usize,
+    selected: Option<Square>,
+}
+
+impl AnalysisBoard {
+    fn player(&self) -> Player {
+        if self.ply.is_multiple_of(2) {
+            Player::White
+        } else {
+            Player::Black
+        }
+    }
+}
+
+// Everything drawn in the analysis window, rebuilt whenever it changes
+#[derive(Component, Clone)]
+struct AnalysisSprite;
+
+fn toggle_analysis_window(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    localizer: Res<Localizer>,
+    analysis_board: Option<Res<AnalysisBoard>>,
+) {
+    if !actions.just_pressed(Action::ToggleAnalysisWindow) {
+        return;
+    }
+
+    // Closing it from here or from the window's own close button both
+    // leave close_analysis_board to clean up
+    if let Some(analysis_board) = analysis_board {
+        commands.entity(analysis_board.window).despawn();
+        return;
+    }
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+
+    let window = commands
+        .spawn(Window {
+            title: localizer.get("analysis-window-title"),
+            resolution: (board_size, board_size + STATUS_HEIGHT).into(),
+            ..default()
+        })
+        .id();
+
+    let camera = commands
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    target: RenderTarget::Window(WindowRef::Entity(window)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(
+                    board_size / 2.0,
+                    (board_size + STATUS_HEIGHT) / 2.0,
+                    999.0,
+                ),
+                projection: OrthographicProjection {
+                    scaling_mode: ScalingMode::AutoMin {
+                        min_width: board_size,
+                        min_height: board_size + STATUS_HEIGHT,
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            // The game's panels would otherwise show up here too
+            UiCameraConfig { show_ui: false },
+            RenderLayers::layer(ANALYSIS_LAYER),
+        ))
+        .id();
+
+    info!("opened the analysis window at move {}", history.moves.len());
+    commands.insert_resource(AnalysisBoard {
+        window,
+        camera,
+        moves: history.moves.clone(),
+        ply: history.moves.len(),
+        selected: None,
+    });
+}
+
+fn close_analysis_board(
+    mut commands: Commands,
+    analysis_board: Res<AnalysisBoard>,
+    windows: Query<(), With<Window>>,
+    sprites: Query<Entity, With<AnalysisSprite>>,
+) {
+    if windows.contains(analysis_board.window) {
+        return;
+    }
+
+    info!("closed the analysis window");
+    commands.entity(analysis_board.camera).despawn();
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<AnalysisBoard>();
+}
+
+// The same keys step through the moves as in the main window, while this
+// one has the focus
+fn handle_analysis_keys(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    settings: Res<Settings>,
+    windows: Query<&Window>,
+    mut analysis_board: ResMut<AnalysisBoard>,
+) {
+    if !windows
+        .get(analysis_board.window)
+        .is_ok_and(|window| window.focused)
+    {
+        return;
+    }
+
+    let pressed = |action: Action| {
+        settings.key_bindings.get(&action).is_some_and(|bindings| {
+            bindings
+                .iter()
+                .any(|binding| binding.just_pressed(&keys, &buttons, 0.0))
+        })
+    };
+
+    let last_ply = analysis_board.moves.len();
+    let ply = if pressed(Action::PreviousMove) {
+        analysis_board.ply.saturating_sub(1)
+    } else if pressed(Action::NextMove) {
+        (analysis_board.ply + 1).min(last_ply)
+    } else if pressed(Action::FirstMove) {
+        0
+    } else if pressed(Action::LastMove) {
+        last_ply
+    } else {
+        return;
+    };
+
+    if ply != analysis_board.ply {
+        analysis_board.ply = ply;
+        analysis_board.selected = None;
+    }
+}
+
+// A piece of the side to move is picked, then a square it can go to. A
+// move there replaces the rest of the line
+fn handle_analysis_clicks(
+    buttons: Res<Input<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut analysis_board: ResMut<AnalysisBoard>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Ok(window), Ok((camera, camera_transform))) = (
+        windows.get(analysis_board.window),
+        cameras.get(analysis_board.camera),
+    ) else {
+        return;
+    };
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| get_window_ray(camera, camera_transform, window, cursor))
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    let square = get_square_at(position);
+    if !square.is_on_board() {
+        return;
+    }
+
+    let ply = analysis_board.ply;
+    let board = get_board_after_moves(&analysis_board.moves[..ply]);
+    let player = analysis_board.player();
+
+    if let Some(from) = analysis_board.selected {
+        // A pawn reaching the last rank here becomes a queen, the first of
+        // the promotions the legal moves list
+        if let Some(mv) = get_all_legal_moves(&board, player)
+            .into_iter()
+            .find(|mv| mv.from == from && mv.to == square)
+        {
+            analysis_board.moves.truncate(ply);
+            analysis_board.moves.push(mv);
+            analysis_board.ply += 1;
+            analysis_board.selected = None;
+            return;
+        }
+    }
+
+    analysis_board.selected = board
+        .get(square)
+        .filter(|(_, owner)| *owner == player)
+        .map(|_| square);
+}
+
+fn draw_analysis_board(
+    mut commands: Commands,
+    analysis_board: Res<AnalysisBoard>,
+    settings: Res<Settings>,
+    theme: Res<UiTheme>,
+    localizer: Res<Localizer>,
+    game_assets: Res<GameAssets>,
+    sprites: Query<Entity, With<AnalysisSprite>>,
+) {
+    let redraw = analysis_board.is_changed()
+        || settings.is_changed()
+        || theme.is_changed()
+        || localizer.is_changed();
+    if !redraw {
+        return;
+    }
+
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let layer = RenderLayers::layer(ANALYSIS_LAYER);
+    let palette = settings.palette();
+    let ply = analysis_board.ply;
+
+    // The selection drawn over the last move, where they meet
+    let mut marked = Vec::new();
+    if let Some(last_move) = ply.checked_sub(1).map(|last| analysis_board.moves[last]) {
+        marked.push((last_move.from, palette.last_move));
+        marked.push((last_move.to, palette.last_move));
+    }
+    if let Some(selected) = analysis_board.selected {
+        marked.push((selected, palette.selected_tile.with_a(1.0)));
+    }
+
+    spawn_board_sprites(
+        &mut commands,
+        &get_board_after_moves(&analysis_board.moves[..ply]),
+        &marked,
+        &settings,
+        &game_assets,
+        (layer, AnalysisSprite),
+    );
+
+    let status = localizer.format(
+        "analysis-window-status",
+        &fluent_args![
+            "ply" => ply,
+            "plies" => analysis_board.moves.len(),
+            "player" => analysis_board.player().name()
+        ],
+    );
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                status,
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 18.0,
+                    color: theme.text,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(
+                board_size / 2.0,
+                board_size + STATUS_HEIGHT / 2.0,
+                PIECE_Z_INDEX,
+            ),
+            ..default()
+        },
+        layer,
+        AnalysisSprite,
+    ));
+}
+
+// The tiles and pieces of a board other than the game's, for a camera of
+// its own to draw. Marked squares are tinted with their colors in order
+pub fn spawn_board_sprites(
+    commands: &mut Commands,
+    board: &Board,
+    marked: &[(Square, Color)],
+    settings: &Settings,
+    game_assets: &GameAssets,
+    bundle: impl Bundle + Clone,
+) {
+    let square_size = PIECE_SIZE as f32;
+
+    for x in 0..BOARD_SIZE {
+        for y in 0..BOARD_SIZE {
+            let color = marked
+                .iter()
+                .filter(|(square, _)| *square == Square(x, y))
+                .fold(get_tile_color(x, y, settings), |color, (_, tint)| {
+                    blend(color, *tint)
+                });
+
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(square_size)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(get_square_center(
+                        BoardPosition::new(x, y),
+                        TILE_Z_INDEX,
+                    )),
+                    ..default()
+                },
+                bundle.clone(),
+            ));
+        }
+    }
+
+    for (piece, player, position) in board.pieces() {
+        commands.spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(Vec2::splat(square_size)),
+                    index: get_atlas_index(game_assets, piece, player),
+                    ..default()
+                },
+                texture_atlas: game_assets.piece_atlas.clone(),
+                transform: Transform::from_translation(get_square_center(position, PIECE_Z_INDEX)),
+                ..default()
+            },
+            bundle.clone(),
+        ));
+    }
+}
+
+// Tiles and pieces aren't parented to a board root here, so they are
+// placed where the main board's children end up
+fn get_square_center(position: BoardPosition, z: f32) -> Vec3 {
+    Vec3::new(
+        (position.x * PIECE_SIZE + (PIECE_SIZE / 2)) as f32,
+        (position.y * PIECE_SIZE + (PIECE_SIZE / 2)) as f32,
+        z,
+    )
+}
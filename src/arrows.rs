@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use chess_core::{get_candidate_moves, Board, EngineConfig, PgnShape, Player, Square};
+
+use crate::{
+    analysis::ViewedPly,
+    board::{BoardRoot, ARROW_Z_INDEX, PIECE_SIZE},
+    input::{Action, Actions},
+    rules::MoveHistory,
+    GameSet,
+};
+
+const CANDIDATE_COUNT: usize = 3;
+// Centipawns behind the best move at which an arrow is as thin and yellow
+// as it gets
+const ARROW_LOSS_CAP: i32 = 200;
+const BEST_ARROW_WIDTH: f32 = 12.0;
+const WORST_ARROW_WIDTH: f32 = 4.0;
+const BEST_ARROW_COLOR: Color = Color::rgba(0.2, 0.75, 0.2, 0.8);
+const WORST_ARROW_COLOR: Color = Color::rgba(0.9, 0.8, 0.1, 0.8);
+const SHAPE_LINE_WIDTH: f32 = 8.0;
+
+// Arrows over the position shown after the game for the engine's best few
+// moves, from a wide green one for the best down to thin yellow ones for
+// moves that lose ground. Also draws the arrows and circles a PGN gives
+// the move on the board. Both can be cleared away until the next move
+pub struct CandidateArrowsPlugin;
+
+impl Plugin for CandidateArrowsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            update_candidate_arrows
+                .run_if(
+                    resource_exists_and_changed::<ViewedPly>()
+                        .or_else(resource_removed::<ViewedPly>()),
+                )
+                .in_set(GameSet::Render),
+        )
+        .add_system(
+            update_annotation_shapes
+                .run_if(resource_changed::<MoveHistory>())
+                .in_set(GameSet::Render),
+        )
+        .add_system(clear_arrows.in_set(GameSet::Render));
+    }
+}
+
+#[derive(Component)]
+struct CandidateArrow;
+
+#[derive(Component)]
+struct AnnotationShape;
+
+fn update_candidate_arrows(
+    mut commands: Commands,
+    board: Res<Board>,
+    viewed_ply: Option<Res<ViewedPly>>,
+    arrows: Query<Entity, With<CandidateArrow>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    for entity in arrows.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(viewed_ply) = viewed_ply else {
+        return;
+    };
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    let player = if viewed_ply.0.is_multiple_of(2) {
+        Player::White
+    } else {
+        Player::Black
+    };
+
+    let candidates = get_candidate_moves(&board, player, &EngineConfig::default(), CANDIDATE_COUNT);
+    let Some(&(_, best_score)) = candidates.first() else {
+        return;
+    };
+
+    // The worst last, so the best is drawn on top where they cross
+    for (index, (mv, score)) in candidates.iter().enumerate().rev() {
+        let loss = ((best_score - score) as f32 / ARROW_LOSS_CAP as f32).clamp(0.0, 1.0);
+        let width = BEST_ARROW_WIDTH + (WORST_ARROW_WIDTH - BEST_ARROW_WIDTH) * loss;
+        let color = get_arrow_color(loss);
+        let z_index = ARROW_Z_INDEX + 0.01 * (CANDIDATE_COUNT - index) as f32;
+
+        let arrow = spawn_arrow(&mut commands, mv.from, mv.to, width, color, z_index);
+        commands
+            .entity(arrow)
+            .insert(CandidateArrow)
+            .set_parent(board_root);
+    }
+}
+
+// Only what is drawn over the board, leaving the selection and last move
+// highlights on the tiles alone
+fn clear_arrows(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    shapes: Query<Entity, Or<(With<CandidateArrow>, With<AnnotationShape>)>>,
+) {
+    if !actions.just_pressed(Action::ClearArrows) {
+        return;
+    }
+
+    for entity in shapes.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_annotation_shapes(
+    mut commands: Commands,
+    history: Res<MoveHistory>,
+    shapes: Query<Entity, With<AnnotationShape>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    for entity in shapes.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+    let Some(node) = history.tree.find(&history.moves) else {
+        return;
+    };
+
+    for shape in &history.tree.node(node).shapes {
+        let shape = match *shape {
+            PgnShape::Arrow { from, to, color } => spawn_arrow(
+                &mut commands,
+                from,
+                to,
+                SHAPE_LINE_WIDTH,
+                get_shape_color(color),
+                ARROW_Z_INDEX,
+            ),
+            PgnShape::Circle { square, color } => {
+                spawn_circle(&mut commands, square, get_shape_color(color))
+            }
+        };
+        commands
+            .entity(shape)
+            .insert(AnnotationShape)
+            .set_parent(board_root);
+    }
+}
+
+// The colors lichess draws them in
+fn get_shape_color(letter: char) -> Color {
+    match letter {
+        'R' => Color::rgba(0.85, 0.15, 0.1, 0.7),
+        'Y' => Color::rgba(0.9, 0.75, 0.0, 0.7),
+        'B' => Color::rgba(0.0, 0.35, 0.85, 0.7),
+        _ => Color::rgba(0.1, 0.6, 0.1, 0.7),
+    }
+}
+
+// A ring drawn as a frame just inside the square
+fn spawn_circle(commands: &mut Commands, square: Square, color: Color) -> Entity {
+    let size = PIECE_SIZE as f32 - SHAPE_LINE_WIDTH;
+    let offset = (size - SHAPE_LINE_WIDTH) / 2.0;
+
+    commands
+        .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+            (square.0 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+            (square.1 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+            ARROW_Z_INDEX,
+        )))
+        .with_children(|parent| {
+            for (side_size, position) in [
+                (Vec2::new(size, SHAPE_LINE_WIDTH), Vec2::new(0.0, offset)),
+                (Vec2::new(size, SHAPE_LINE_WIDTH), Vec2::new(0.0, -offset)),
+                (Vec2::new(SHAPE_LINE_WIDTH, size), Vec2::new(offset, 0.0)),
+                (Vec2::new(SHAPE_LINE_WIDTH, size), Vec2::new(-offset, 0.0)),
+            ] {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(side_size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position.extend(0.0)),
+                    ..default()
+                });
+            }
+        })
+        .id()
+}
+
+// Green for the best move through to yellow at the loss cap
+fn get_arrow_color(loss: f32) -> Color {
+    let [r0, g0, b0, a0] = BEST_ARROW_COLOR.as_rgba_f32();
+    let [r1, g1, b1, a1] = WORST_ARROW_COLOR.as_rgba_f32();
+
+    Color::rgba(
+        r0 + (r1 - r0) * loss,
+        g0 + (g1 - g0) * loss,
+        b0 + (b1 - b0) * loss,
+        a0 + (a1 - a0) * loss,
+    )
+}
+
+// A shaft from the middle of one square to the other, with a head made of
+// two strokes at its tip
+fn spawn_arrow(
+    commands: &mut Commands,
+    from: Square,
+    to: Square,
+    width: f32,
+    color: Color,
+    z_index: f32,
+) -> Entity {
+    let square_center = |square: Square| {
+        Vec2::new(
+            (square.0 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+            (square.1 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+        )
+    };
+    let start = square_center(from);
+    let end = square_center(to);
+    let direction = (end - start).normalize_or_zero();
+    let angle = direction.y.atan2(direction.x);
+    let head_length = width * 2.5;
+
+    commands
+        .spawn(SpatialBundle::from_transform(
+            Transform::from_translation(start.extend(z_index))
+                .with_rotation(Quat::from_rotation_z(angle)),
+        ))
+        .with_children(|parent| {
+            // Stops short of the tip so it doesn't poke through the head
+            let length = start.distance(end) - width / 2.0;
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(length, width)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(length / 2.0, 0.0, 0.0),
+                ..default()
+            });
+
+            let tip = start.distance(end);
+            for side in [-1.0, 1.0] {
+                let stroke_angle = side * std::f32::consts::FRAC_PI_4 * 3.0;
+                let offset = Vec2::from_angle(stroke_angle) * head_length / 2.0;
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::new(head_length, width)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(tip + offset.x, offset.y, 0.0)
+                        .with_rotation(Quat::from_rotation_z(stroke_angle)),
+                    ..default()
+                });
+            }
+        })
+        .id()
+}
@@ -0,0 +1,373 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{
+    a11y::{
+        accesskit::{NodeBuilder, Role},
+        AccessibilityNode,
+    },
+    prelude::*,
+};
+use chess_core::{is_king_attacked, Board, BoardPosition, Piece, Square, BOARD_SIZE};
+
+use crate::{
+    input::{KeyboardCursor, Selection, SnapBack},
+    rules::{CurrentTurn, MoveHistory},
+    settings::Settings,
+    GameSet,
+};
+
+pub const PIECE_SIZE: i32 = 60;
+const MARKER_LINE_WIDTH: f32 = 4.0;
+
+// Draw order of everything on the board, back to front. The camera sits
+// at z 999 and sees down to z -1, so all of it has to stay in between
+pub const TILE_Z_INDEX: f32 = 0.0;
+pub const HEATMAP_Z_INDEX: f32 = 0.05;
+const LAST_MOVE_Z_INDEX: f32 = 0.1;
+const CHECK_Z_INDEX: f32 = 0.2;
+const SELECTION_Z_INDEX: f32 = 0.3;
+pub const MAZE_TARGET_Z_INDEX: f32 = 0.4;
+pub const CURSOR_Z_INDEX: f32 = 0.5;
+pub const PIECE_Z_INDEX: f32 = 1.0;
+pub const GHOST_Z_INDEX: f32 = 1.5;
+pub const ARROW_Z_INDEX: f32 = 1.8;
+const GUIDE_Z_INDEX: f32 = 2.0;
+pub const PROMOTION_Z_INDEX: f32 = 2.5;
+pub const QR_CODE_Z_INDEX: f32 = 10.0;
+
+pub struct BoardPlugin;
+
+impl Plugin for BoardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(generate_board).add_systems(
+            (
+                update_pieces_positions,
+                display_possible_piece_movements.run_if(resource_changed::<Selection>()),
+                apply_guide_colors,
+                apply_tile_colors,
+                update_board_markers,
+            )
+                .in_set(GameSet::Render),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct BoardRoot;
+
+#[derive(Component)]
+pub struct Tile;
+
+#[derive(Component)]
+struct Guide;
+
+// Selection, last move and check highlights drawn over the tiles
+#[derive(Component)]
+struct BoardMarker;
+
+fn apply_guide_colors(
+    settings: Res<Settings>,
+    mut guides: Query<&mut Sprite, With<Guide>>,
+    mut cursor: Query<&mut Sprite, (With<KeyboardCursor>, Without<Guide>)>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    for mut guide_sprite in guides.iter_mut() {
+        guide_sprite.color = settings.palette().guide;
+    }
+
+    for mut cursor_sprite in cursor.iter_mut() {
+        cursor_sprite.color = settings.palette().cursor;
+    }
+}
+
+fn generate_board(mut commands: Commands, settings: Res<Settings>) {
+    let board = commands
+        .spawn((
+            TransformBundle::default(),
+            VisibilityBundle::default(),
+            AccessibilityNode::from(NodeBuilder::new(Role::Grid)),
+            BoardRoot,
+        ))
+        .id();
+
+    for x in 0..BOARD_SIZE {
+        for y in 0..BOARD_SIZE {
+            let tile = commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: get_tile_color(x, y, &settings),
+                            custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, TILE_Z_INDEX),
+                        ..default()
+                    },
+                    BoardPosition::new(x, y),
+                    Tile,
+                    AccessibilityNode::from(NodeBuilder::new(Role::Cell)),
+                ))
+                .id();
+
+            let guide = commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: settings.palette().guide,
+                            custom_size: Some(Vec2::splat(10.0)),
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        transform: Transform::from_xyz(0.0, 0.0, GUIDE_Z_INDEX),
+                        ..default()
+                    },
+                    BoardPosition::new(x, y),
+                    Guide,
+                ))
+                .id();
+
+            commands.entity(board).add_child(tile);
+            commands.entity(board).add_child(guide);
+        }
+    }
+}
+
+// A dragged piece, or one sliding back from a drag, is placed by the input
+fn update_pieces_positions(
+    selection: Res<Selection>,
+    mut pieces: Query<(Entity, &mut Transform, &BoardPosition), Without<SnapBack>>,
+) {
+    for (entity, mut transform, position) in pieces.iter_mut() {
+        if selection.dragged() == Some(entity) {
+            continue;
+        }
+
+        transform.translation.x = (position.x * PIECE_SIZE + (PIECE_SIZE / 2)) as f32;
+        transform.translation.y = (position.y * PIECE_SIZE + (PIECE_SIZE / 2)) as f32;
+    }
+}
+
+fn display_possible_piece_movements(
+    selection: Res<Selection>,
+    mut guides: Query<(&BoardPosition, &mut Visibility), With<Guide>>,
+) {
+    for (guide_position, mut guide_visibility) in guides.iter_mut() {
+        *guide_visibility = if selection.moves().contains(&guide_position.square()) {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// Tiles only ever show their own color, highlights go on markers above
+fn apply_tile_colors(
+    settings: Res<Settings>,
+    mut tiles: Query<(&BoardPosition, &mut Sprite), With<Tile>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (tile_pos, mut tile_sprite) in tiles.iter_mut() {
+        tile_sprite.color = get_tile_color(tile_pos.x, tile_pos.y, &settings);
+    }
+}
+
+fn update_board_markers(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    pieces: Query<&BoardPosition, With<Piece>>,
+    markers: Query<Entity, With<BoardMarker>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    if !selection.is_changed() && !history.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    for entity in markers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let palette = settings.palette();
+    let shapes = settings.use_indicator_shapes();
+
+    if let Some(last_move) = history.moves.last() {
+        for square in [last_move.from, last_move.to] {
+            spawn_board_marker(
+                &mut commands,
+                board_root,
+                square,
+                LAST_MOVE_Z_INDEX,
+                palette.last_move,
+                if shapes {
+                    get_corner_shapes()
+                } else {
+                    Vec::new()
+                },
+                palette.last_move.with_a(1.0),
+            );
+        }
+    }
+
+    if is_king_attacked(&board, current_turn.0) {
+        if let Some((_, _, king_position)) = board
+            .pieces()
+            .find(|(piece_type, player, _)| *piece_type == Piece::King && *player == current_turn.0)
+        {
+            spawn_board_marker(
+                &mut commands,
+                board_root,
+                king_position.square(),
+                CHECK_Z_INDEX,
+                palette.check,
+                if shapes {
+                    get_cross_shapes()
+                } else {
+                    Vec::new()
+                },
+                palette.check.with_a(1.0),
+            );
+        }
+    }
+
+    // The piece itself is outlined, so the square only gets its frame
+    let selected = selection.piece().and_then(|entity| pieces.get(entity).ok());
+    if let Some(position) = selected.filter(|_| shapes) {
+        spawn_board_marker(
+            &mut commands,
+            board_root,
+            position.square(),
+            SELECTION_Z_INDEX,
+            Color::NONE,
+            get_frame_shapes(),
+            Color::BLACK,
+        );
+    }
+}
+
+fn spawn_board_marker(
+    commands: &mut Commands,
+    board_root: Entity,
+    square: Square,
+    z_index: f32,
+    fill: Color,
+    shapes: Vec<(Vec2, Transform)>,
+    shape_color: Color,
+) {
+    let marker = commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(
+                (square.0 * PIECE_SIZE + (PIECE_SIZE / 2)) as f32,
+                (square.1 * PIECE_SIZE + (PIECE_SIZE / 2)) as f32,
+                z_index,
+            )),
+            BoardMarker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: fill,
+                    custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                    ..default()
+                },
+                ..default()
+            });
+
+            for (size, transform) in shapes {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: shape_color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: transform
+                        .with_translation(transform.translation.truncate().extend(0.01)),
+                    ..default()
+                });
+            }
+        })
+        .id();
+
+    commands.entity(board_root).add_child(marker);
+}
+
+// Selection: a frame around the square
+fn get_frame_shapes() -> Vec<(Vec2, Transform)> {
+    let size = PIECE_SIZE as f32;
+    let offset = (size - MARKER_LINE_WIDTH) / 2.0;
+
+    vec![
+        (
+            Vec2::new(size, MARKER_LINE_WIDTH),
+            Transform::from_xyz(0.0, offset, 0.0),
+        ),
+        (
+            Vec2::new(size, MARKER_LINE_WIDTH),
+            Transform::from_xyz(0.0, -offset, 0.0),
+        ),
+        (
+            Vec2::new(MARKER_LINE_WIDTH, size),
+            Transform::from_xyz(offset, 0.0, 0.0),
+        ),
+        (
+            Vec2::new(MARKER_LINE_WIDTH, size),
+            Transform::from_xyz(-offset, 0.0, 0.0),
+        ),
+    ]
+}
+
+// Last move: a block in each corner
+fn get_corner_shapes() -> Vec<(Vec2, Transform)> {
+    let corner_size = PIECE_SIZE as f32 / 5.0;
+    let offset = (PIECE_SIZE as f32 - corner_size) / 2.0;
+
+    [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)]
+        .into_iter()
+        .map(|(x, y)| {
+            (
+                Vec2::splat(corner_size),
+                Transform::from_xyz(x * offset, y * offset, 0.0),
+            )
+        })
+        .collect()
+}
+
+// Check: a cross through the square, showing around the king
+fn get_cross_shapes() -> Vec<(Vec2, Transform)> {
+    let size = Vec2::new(PIECE_SIZE as f32 * 1.2, MARKER_LINE_WIDTH);
+
+    [FRAC_PI_4, -FRAC_PI_4]
+        .into_iter()
+        .map(|angle| (size, Transform::from_rotation(Quat::from_rotation_z(angle))))
+        .collect()
+}
+
+pub fn get_tile_color(x: i32, y: i32, settings: &Settings) -> Color {
+    if (x % 2 == 1 && y % 2 != 1) || (x % 2 != 1 && y % 2 == 1) {
+        settings.palette().light_tile
+    } else {
+        settings.palette().dark_tile
+    }
+}
+
+// The square a point in the board's own space falls on, which may be off
+// the board. Squares are PIECE_SIZE across there, however the board ends
+// up drawn, so this is only given points already taken into that space
+pub fn get_square_at(position: Vec2) -> Square {
+    Square(
+        (position.x / PIECE_SIZE as f32).floor() as i32,
+        (position.y / PIECE_SIZE as f32).floor() as i32,
+    )
+}
@@ -0,0 +1,382 @@
+// A 3D board in place of the sprites, turned on with --3d. The game runs
+// exactly as in 2D: the board is laid out in the same space, so clicks,
+// drags and everything else land on squares the same way, and each piece
+// only gets a model to go with its sprite, which no 2D camera draws
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use chess_core::{is_king_attacked, Board, BoardPosition, Piece, Player, BOARD_SIZE};
+
+use crate::{
+    board::{get_tile_color, PIECE_SIZE},
+    camera::GameCamera,
+    input::{Action, Actions, Selection},
+    rules::{CurrentTurn, MoveHistory},
+    settings::Settings,
+    GameSet,
+};
+
+const TILE_THICKNESS: f32 = 6.0;
+const FRAME_WIDTH: f32 = 20.0;
+const DEFAULT_PITCH: f32 = FRAC_PI_4 + 0.2;
+const DEFAULT_DISTANCE: f32 = 760.0;
+const MIN_DISTANCE: f32 = 300.0;
+const MAX_DISTANCE: f32 = 1400.0;
+// Radians of orbit for each pixel the mouse moves
+const ORBIT_SPEED: f32 = 0.005;
+const WHEEL_ZOOM_STEP: f32 = 1.2;
+const PIXELS_PER_LINE: f32 = 100.0;
+
+pub struct Board3dPlugin;
+
+impl Plugin for Board3dPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Orbit::default())
+            .add_startup_system(spawn_scene)
+            .add_systems((orbit_camera, place_camera.after(orbit_camera)))
+            .add_systems((add_piece_models, color_tiles).in_set(GameSet::Render));
+    }
+}
+
+// Where the camera looks at the board from, around its middle
+#[derive(Resource)]
+struct Orbit {
+    // Around the board, 0 being from White's side
+    yaw: f32,
+    // Up from the board's surface
+    pitch: f32,
+    distance: f32,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: DEFAULT_PITCH,
+            distance: DEFAULT_DISTANCE,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct PieceMaterials {
+    white: Handle<StandardMaterial>,
+    black: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct Tile3d;
+
+fn spawn_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+) {
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+    let square_size = PIECE_SIZE as f32;
+
+    commands.spawn((Camera3dBundle::default(), GameCamera));
+
+    commands.spawn(DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            illuminance: 20_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform::from_xyz(board_size * 0.3, -board_size * 0.2, board_size)
+            .looking_at(Vec3::new(board_size / 2.0, board_size / 2.0, 0.0), Vec3::Z),
+        ..default()
+    });
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 0.4,
+    });
+
+    // The squares' tops are at z 0, where the 2D board lies
+    let tile_mesh = meshes.add(shape::Box::new(square_size, square_size, TILE_THICKNESS).into());
+    for x in 0..BOARD_SIZE {
+        for y in 0..BOARD_SIZE {
+            commands.spawn((
+                PbrBundle {
+                    mesh: tile_mesh.clone(),
+                    material: materials.add(get_tile_color(x, y, &settings).into()),
+                    transform: Transform::from_xyz(
+                        (x * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                        (y * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                        -TILE_THICKNESS / 2.0,
+                    ),
+                    ..default()
+                },
+                BoardPosition::new(x, y),
+                Tile3d,
+            ));
+        }
+    }
+
+    let frame_size = board_size + FRAME_WIDTH * 2.0;
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Box::new(frame_size, frame_size, TILE_THICKNESS * 2.0).into()),
+        material: materials.add(Color::rgb(0.3, 0.2, 0.12).into()),
+        transform: Transform::from_xyz(board_size / 2.0, board_size / 2.0, -TILE_THICKNESS * 1.5),
+        ..default()
+    });
+
+    commands.insert_resource(PieceMaterials {
+        white: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.92, 0.88, 0.8),
+            perceptual_roughness: 0.4,
+            ..default()
+        }),
+        black: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.12, 0.12, 0.14),
+            perceptual_roughness: 0.4,
+            ..default()
+        }),
+    });
+}
+
+// Right-drag turns the camera around the board and Ctrl with the wheel
+// moves it closer, as the wheel alone steps through the moves
+fn orbit_camera(
+    mut orbit: ResMut<Orbit>,
+    actions: Res<Actions>,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut wheel_events: EventReader<MouseWheel>,
+) {
+    let motion: Vec2 = motion_events.iter().map(|event| event.delta).sum();
+    let lines: f32 = wheel_events
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+
+    if actions.just_pressed(Action::FitBoard) {
+        *orbit = Orbit::default();
+        return;
+    }
+
+    if buttons.pressed(MouseButton::Right) && motion != Vec2::ZERO {
+        orbit.yaw -= motion.x * ORBIT_SPEED;
+        orbit.pitch = (orbit.pitch + motion.y * ORBIT_SPEED).clamp(0.1, FRAC_PI_2 - 0.01);
+    }
+
+    if lines != 0.0 && keys.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        orbit.distance =
+            (orbit.distance * WHEEL_ZOOM_STEP.powf(-lines)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+}
+
+fn place_camera(orbit: Res<Orbit>, mut camera: Query<&mut Transform, With<GameCamera>>) {
+    if !orbit.is_changed() {
+        return;
+    }
+    let Ok(mut transform) = camera.get_single_mut() else {
+        return;
+    };
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+    let center = Vec3::new(board_size / 2.0, board_size / 2.0, 0.0);
+    let offset = Quat::from_rotation_z(orbit.yaw)
+        * Vec3::new(
+            0.0,
+            -orbit.pitch.cos() * orbit.distance,
+            orbit.pitch.sin() * orbit.distance,
+        );
+
+    *transform = Transform::from_translation(center + offset).looking_at(center, Vec3::Z);
+}
+
+// Each new piece, including those put back after a takeback, gets its
+// model as a child, so it moves, lifts and is dragged along with its sprite
+fn add_piece_models(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<PieceMaterials>,
+    pieces: Query<(Entity, &Piece, &Player), Added<Piece>>,
+) {
+    for (entity, piece_type, player) in pieces.iter() {
+        let material = match player {
+            Player::White => materials.white.clone(),
+            Player::Black => materials.black.clone(),
+        };
+
+        // The models' up is y, the board's z. Black's knights face White's
+        let mut rotation = Quat::from_rotation_x(FRAC_PI_2);
+        if *player == Player::Black {
+            rotation = Quat::from_rotation_z(PI) * rotation;
+        }
+
+        let parts = get_piece_parts(*piece_type);
+        commands.entity(entity).with_children(|piece| {
+            piece
+                .spawn(SpatialBundle::from_transform(Transform::from_rotation(
+                    rotation,
+                )))
+                .with_children(|model| {
+                    for (mesh, transform) in parts {
+                        model.spawn(PbrBundle {
+                            mesh: meshes.add(mesh),
+                            material: material.clone(),
+                            transform,
+                            ..default()
+                        });
+                    }
+                });
+        });
+    }
+}
+
+// Each part's mesh and where it sits, put together out of a few simple
+// shapes on a base they all share
+fn get_piece_parts(piece_type: Piece) -> Vec<(Mesh, Transform)> {
+    let cylinder = |radius: f32, height: f32, y: f32| {
+        (
+            shape::Cylinder {
+                radius,
+                height,
+                resolution: 24,
+                segments: 1,
+            }
+            .into(),
+            Transform::from_xyz(0.0, y, 0.0),
+        )
+    };
+    let sphere = |radius: f32, y: f32| {
+        (
+            shape::UVSphere {
+                radius,
+                sectors: 24,
+                stacks: 12,
+            }
+            .into(),
+            Transform::from_xyz(0.0, y, 0.0),
+        )
+    };
+    let cuboid =
+        |x: f32, y: f32, z: f32, transform: Transform| (shape::Box::new(x, y, z).into(), transform);
+
+    let mut parts = vec![cylinder(20.0, 8.0, 4.0)];
+    match piece_type {
+        Piece::Pawn => {
+            parts.push(cylinder(9.0, 18.0, 17.0));
+            parts.push(sphere(10.0, 32.0));
+        }
+        Piece::Rook => {
+            parts.push(cylinder(14.0, 32.0, 24.0));
+            parts.push(cylinder(17.0, 8.0, 44.0));
+        }
+        Piece::Knight => {
+            parts.push(cylinder(12.0, 20.0, 18.0));
+            parts.push(cuboid(
+                12.0,
+                30.0,
+                20.0,
+                Transform::from_xyz(0.0, 38.0, 0.0).with_rotation(Quat::from_rotation_x(0.3)),
+            ));
+            parts.push(cuboid(
+                12.0,
+                10.0,
+                22.0,
+                Transform::from_xyz(0.0, 48.0, -10.0),
+            ));
+        }
+        Piece::Bishop => {
+            parts.push(cylinder(10.0, 30.0, 23.0));
+            parts.push((
+                shape::Capsule {
+                    radius: 9.0,
+                    depth: 10.0,
+                    ..default()
+                }
+                .into(),
+                Transform::from_xyz(0.0, 46.0, 0.0),
+            ));
+        }
+        Piece::Queen => {
+            parts.push(cylinder(12.0, 40.0, 28.0));
+            parts.push(cylinder(16.0, 6.0, 50.0));
+            parts.push(sphere(6.0, 58.0));
+        }
+        Piece::King => {
+            parts.push(cylinder(13.0, 44.0, 30.0));
+            parts.push(cylinder(16.0, 6.0, 54.0));
+            parts.push(cuboid(4.0, 16.0, 4.0, Transform::from_xyz(0.0, 64.0, 0.0)));
+            parts.push(cuboid(12.0, 4.0, 4.0, Transform::from_xyz(0.0, 66.0, 0.0)));
+        }
+    }
+    parts
+}
+
+// The highlights the 2D board draws over its tiles, worked into the colors
+// of the squares instead
+fn color_tiles(
+    selection: Res<Selection>,
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    pieces: Query<&BoardPosition, With<Piece>>,
+    tiles: Query<(&BoardPosition, &Handle<StandardMaterial>), With<Tile3d>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !selection.is_changed() && !history.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let palette = settings.palette();
+    let selected = selection
+        .piece()
+        .and_then(|entity| pieces.get(entity).ok())
+        .map(BoardPosition::square);
+    let last_move = history.moves.last();
+    let check = is_king_attacked(&board, current_turn.0)
+        .then(|| {
+            board
+                .pieces()
+                .find(|(piece_type, player, _)| {
+                    *piece_type == Piece::King && *player == current_turn.0
+                })
+                .map(|(_, _, position)| position.square())
+        })
+        .flatten();
+
+    for (position, material) in tiles.iter() {
+        let square = position.square();
+        let mut color = get_tile_color(position.x, position.y, &settings);
+
+        if last_move.is_some_and(|mv| mv.from == square || mv.to == square) {
+            color = blend(color, palette.last_move);
+        }
+        if check == Some(square) {
+            color = blend(color, palette.check);
+        }
+        if selected == Some(square) {
+            color = blend(color, palette.selected_tile);
+        } else if selection.moves().contains(&square) {
+            color = blend(color, palette.guide);
+        }
+
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = color;
+        }
+    }
+}
+
+// The overlay laid over the base as the 2D board would draw it
+pub fn blend(base: Color, overlay: Color) -> Color {
+    let alpha = overlay.a();
+    Color::rgb(
+        base.r() + (overlay.r() - base.r()) * alpha,
+        base.g() + (overlay.g() - base.g()) * alpha,
+        base.b() + (overlay.b() - base.b()) * alpha,
+    )
+}
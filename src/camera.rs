@@ -0,0 +1,272 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::camera::ScalingMode,
+    window::PrimaryWindow,
+};
+use chess_core::BOARD_SIZE;
+
+use crate::{
+    board::PIECE_SIZE,
+    input::{Action, Actions},
+    settings::Settings,
+};
+
+const MIN_CAMERA_SCALE: f32 = 0.25;
+// How much one line of the mouse wheel zooms in or out
+const WHEEL_ZOOM_STEP: f32 = 1.2;
+// Trackpads scroll in pixels, about this many to a line
+const PIXELS_PER_LINE: f32 = 100.0;
+// How quickly the camera closes in on where it is headed, per second
+const CAMERA_EASING: f32 = 12.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_camera).add_systems((
+            handle_touch_camera,
+            zoom_camera_with_wheel,
+            fit_board,
+            move_camera_to_target.after(zoom_camera_with_wheel),
+        ));
+    }
+}
+
+// The ray into the world under a point of the window, given from the
+// bottom left as cursor_position gives it. A camera drawing to only part
+// of the window, as when letterboxed, has nothing under the rest
+pub fn get_window_ray(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    window: &Window,
+    position: Vec2,
+) -> Option<Ray> {
+    let position = to_viewport_position(position, window.height(), camera.logical_viewport_rect())?;
+    camera.viewport_to_world(camera_transform, position)
+}
+
+// From the window's bottom left to the viewport's. The viewport is given
+// by its corners from the top left, as Camera has it, or None for all of
+// the window
+pub fn to_viewport_position(
+    position: Vec2,
+    window_height: f32,
+    viewport: Option<(Vec2, Vec2)>,
+) -> Option<Vec2> {
+    let Some((min, max)) = viewport else {
+        return Some(position);
+    };
+
+    let position = position - Vec2::new(min.x, window_height - max.y);
+    (position.cmpge(Vec2::ZERO).all() && position.cmple(max - min).all()).then_some(position)
+}
+
+// Editor or debug plugins may add cameras of their own
+#[derive(Component)]
+pub struct GameCamera;
+
+// Where the camera is easing to, gone once it gets there
+#[derive(Resource)]
+struct CameraTarget {
+    center: Vec2,
+    scale: f32,
+}
+
+fn spawn_camera(mut commands: Commands) {
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+
+    // Keep the whole board in view and centered whatever the window or
+    // canvas size, instead of pinning it to the bottom left corner
+    commands.spawn((
+        Camera2dBundle {
+            transform: Transform::from_xyz(board_size / 2.0, board_size / 2.0, 999.0),
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::AutoMin {
+                    min_width: board_size,
+                    min_height: board_size,
+                },
+                ..default()
+            },
+            ..default()
+        },
+        GameCamera,
+    ));
+}
+
+fn handle_touch_camera(
+    mut commands: Commands,
+    touches: Res<Touches>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &mut Transform,
+            &mut OrthographicProjection,
+        ),
+        With<GameCamera>,
+    >,
+) {
+    let mut fingers = touches.iter();
+    let (Some(first), Some(second), None) = (fingers.next(), fingers.next(), fingers.next()) else {
+        return;
+    };
+
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_global_transform, mut camera_transform, mut projection)) =
+        camera.get_single_mut()
+    else {
+        return;
+    };
+
+    // The fingers take over from any zoom on its way
+    commands.remove_resource::<CameraTarget>();
+
+    // Touches are reported from the top left, the cursor from the bottom left
+    let to_world = |position: Vec2| {
+        get_window_ray(
+            camera,
+            camera_global_transform,
+            window,
+            Vec2::new(position.x, window.height() - position.y),
+        )
+        .map(|ray| ray.origin.truncate())
+    };
+
+    let previous_midpoint = (first.previous_position() + second.previous_position()) / 2.0;
+    let midpoint = (first.position() + second.position()) / 2.0;
+
+    let (Some(previous_anchor), Some(anchor)) = (to_world(previous_midpoint), to_world(midpoint))
+    else {
+        return;
+    };
+
+    // Drag the board along with the fingers
+    let mut center = camera_transform.translation.truncate() + previous_anchor - anchor;
+
+    let previous_distance = first
+        .previous_position()
+        .distance(second.previous_position());
+    let distance = first.position().distance(second.position());
+
+    if previous_distance > 0.0 && distance > 0.0 {
+        let scale = (projection.scale * previous_distance / distance).clamp(MIN_CAMERA_SCALE, 1.0);
+        // Zoom around the point between the fingers rather than the center
+        center = previous_anchor + (center - previous_anchor) * (scale / projection.scale);
+        projection.scale = scale;
+    }
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+    camera_transform.translation.x = center.x.clamp(0.0, board_size);
+    camera_transform.translation.y = center.y.clamp(0.0, board_size);
+}
+
+// Ctrl and the wheel zoom around the mouse, as the wheel alone steps
+// through the moves
+fn zoom_camera_with_wheel(
+    mut commands: Commands,
+    mut wheel_events: EventReader<MouseWheel>,
+    keys: Res<Input<KeyCode>>,
+    target: Option<Res<CameraTarget>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &Transform,
+            &OrthographicProjection,
+        ),
+        With<GameCamera>,
+    >,
+) {
+    let lines: f32 = wheel_events
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+
+    if lines == 0.0 || !keys.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        return;
+    }
+
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_global_transform, camera_transform, projection)) = camera.get_single()
+    else {
+        return;
+    };
+    let Some(anchor) = window
+        .cursor_position()
+        .and_then(|cursor| get_window_ray(camera, camera_global_transform, window, cursor))
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    // Further on from wherever the camera is already headed
+    let (center, scale) = match target {
+        Some(target) => (target.center, target.scale),
+        None => (camera_transform.translation.truncate(), projection.scale),
+    };
+
+    let new_scale = (scale * WHEEL_ZOOM_STEP.powf(-lines)).clamp(MIN_CAMERA_SCALE, 1.0);
+    let center = anchor + (center - anchor) * (new_scale / scale);
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+    commands.insert_resource(CameraTarget {
+        center: center.clamp(Vec2::ZERO, Vec2::splat(board_size)),
+        scale: new_scale,
+    });
+}
+
+// Back to the whole board, as the camera started out
+fn fit_board(mut commands: Commands, actions: Res<Actions>) {
+    if actions.just_pressed(Action::FitBoard) {
+        let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+        commands.insert_resource(CameraTarget {
+            center: Vec2::splat(board_size / 2.0),
+            scale: 1.0,
+        });
+    }
+}
+
+fn move_camera_to_target(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    target: Option<Res<CameraTarget>>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<GameCamera>>,
+) {
+    let Some(target) = target else {
+        return;
+    };
+    let Ok((mut camera_transform, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+
+    let center = camera_transform.translation.truncate();
+    let close_enough = settings.reduced_motion
+        || center.distance(target.center) < 0.5 && (projection.scale - target.scale).abs() < 0.001;
+
+    let (center, scale) = if close_enough {
+        commands.remove_resource::<CameraTarget>();
+        (target.center, target.scale)
+    } else {
+        // Eased the same whatever the frame rate
+        let t = 1.0 - (-CAMERA_EASING * time.delta_seconds()).exp();
+        (
+            center.lerp(target.center, t),
+            projection.scale + (target.scale - projection.scale) * t,
+        )
+    };
+
+    camera_transform.translation.x = center.x;
+    camera_transform.translation.y = center.y;
+    projection.scale = scale;
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use chess_core::get_board_after_moves;
+
+use crate::{
+    analysis::ViewedPly,
+    input::{Action, Actions},
+    move_panel::get_move_label,
+    pieces::GameAssets,
+    rules::MoveHistory,
+    settings::Settings,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+// The comment on the move being looked at, below the board, for reading an
+// annotated game while stepping through it
+pub struct CommentBoxPlugin;
+
+impl Plugin for CommentBoxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_comment_box).add_system(
+            update_comment_box
+                .run_if(
+                    resource_changed::<Settings>()
+                        .or_else(resource_changed::<MoveHistory>())
+                        .or_else(resource_exists_and_changed::<ViewedPly>())
+                        .or_else(resource_changed::<UiTheme>()),
+                )
+                .in_set(GameSet::Render),
+        );
+    }
+}
+
+#[derive(Component)]
+struct CommentBox;
+
+fn toggle_comment_box(actions: Res<Actions>, mut settings: ResMut<Settings>) {
+    if actions.just_pressed(Action::ToggleCommentBox) {
+        settings.comment_box = !settings.comment_box;
+    }
+}
+
+fn update_comment_box(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    history: Res<MoveHistory>,
+    viewed_ply: Option<Res<ViewedPly>>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    boxes: Query<Entity, With<CommentBox>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in boxes.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(game_assets), Ok(status_bar)) = (
+        game_assets.filter(|_| settings.comment_box),
+        status_bars.get_single(),
+    ) else {
+        return;
+    };
+
+    let shown_moves = match &viewed_ply {
+        Some(viewed_ply) => &history.moves[..viewed_ply.0],
+        None => &history.moves,
+    };
+
+    // Before the first move, the comment on the game itself
+    let text = match history.tree.find(shown_moves) {
+        Some(id) => {
+            let node = history.tree.node(id);
+            let Some(comment) = &node.comment else {
+                return;
+            };
+            let ply = shown_moves.len() - 1;
+            let board = get_board_after_moves(&shown_moves[..ply]);
+            format!("{} {comment}", get_move_label(&board, node, ply, true))
+        }
+        None if shown_moves.is_empty() => match &history.tree.comment {
+            Some(comment) => comment.clone(),
+            None => return,
+        },
+        None => return,
+    };
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Comment,
+        &game_assets,
+        &theme,
+        CommentBox,
+    );
+}
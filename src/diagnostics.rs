@@ -0,0 +1,136 @@
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticId, Diagnostics, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin,
+    },
+    prelude::*,
+};
+use chess_core::{get_fen, get_repetition_count, Board};
+
+use crate::{
+    input::{Action, Actions},
+    pieces::GameAssets,
+    rules::MoveHistory,
+    ui_theme::{ThemedBackground, ThemedText, UiTheme},
+    GameSet,
+};
+
+// How long rebuilding the move cache took, in milliseconds
+pub const POSSIBLE_MOVES_TIME: DiagnosticId =
+    DiagnosticId::from_u128(246264182590360753278613815100668567044);
+// White's material minus Black's, in pawns
+pub const MATERIAL_BALANCE: DiagnosticId =
+    DiagnosticId::from_u128(119039366991917870495946466004617578065);
+// How often the current position has come up
+pub const REPETITIONS: DiagnosticId =
+    DiagnosticId::from_u128(245591106163541924649227505646101561862);
+
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .add_startup_system(add_game_diagnostics)
+            .add_system(
+                measure_position
+                    .run_if(resource_changed::<MoveHistory>())
+                    .in_set(GameSet::Render),
+            )
+            .add_system(toggle_diagnostics_overlay)
+            .add_system(update_diagnostics_overlay.after(toggle_diagnostics_overlay));
+    }
+}
+
+#[derive(Component)]
+struct DiagnosticsOverlay;
+
+fn add_game_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics
+        .add(Diagnostic::new(POSSIBLE_MOVES_TIME, "possible_moves_time", 20).with_suffix(" ms"));
+    diagnostics.add(Diagnostic::new(MATERIAL_BALANCE, "material_balance", 1));
+    diagnostics.add(Diagnostic::new(REPETITIONS, "repetitions", 1));
+}
+
+fn measure_position(
+    history: Res<MoveHistory>,
+    board: Res<Board>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    diagnostics.add_measurement(MATERIAL_BALANCE, || board.material_balance() as f64);
+    diagnostics.add_measurement(REPETITIONS, || get_repetition_count(&history.moves) as f64);
+}
+
+fn toggle_diagnostics_overlay(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    game_assets: Res<GameAssets>,
+    theme: Res<UiTheme>,
+    overlays: Query<Entity, With<DiagnosticsOverlay>>,
+) {
+    if !actions.just_pressed(Action::ToggleDiagnostics) {
+        return;
+    }
+
+    if !overlays.is_empty() {
+        for entity in overlays.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    // In the bottom corner, clear of the move panel at the top
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: game_assets.font.clone(),
+                font_size: 14.0,
+                color: theme.text,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(8.0),
+                bottom: Val::Px(8.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        })
+        .with_background_color(theme.panel),
+        ThemedBackground(|theme| theme.panel),
+        ThemedText(|theme| theme.text),
+        DiagnosticsOverlay,
+    ));
+}
+
+// Developer facing, so left in English
+fn update_diagnostics_overlay(
+    mut overlays: Query<&mut Text, With<DiagnosticsOverlay>>,
+    diagnostics: Res<Diagnostics>,
+    history: Res<MoveHistory>,
+) {
+    let Ok(mut text) = overlays.get_single_mut() else {
+        return;
+    };
+
+    let value = |id: DiagnosticId| diagnostics.get(id).and_then(Diagnostic::value);
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(Diagnostic::smoothed);
+    let format = |value: Option<f64>, precision: usize| {
+        value.map_or("-".to_string(), |value| format!("{value:.precision$}"))
+    };
+
+    text.sections[0].value = format!(
+        "FPS: {}\nEntities: {}\nFEN: {}\nMaterial: {}\nRepetitions: {}\nMove cache: {} ms",
+        format(fps, 0),
+        format(value(EntityCountDiagnosticsPlugin::ENTITY_COUNT), 0),
+        get_fen(&history.moves),
+        value(MATERIAL_BALANCE).map_or("-".to_string(), |value| format!("{value:+}")),
+        format(value(REPETITIONS), 0),
+        format(value(POSSIBLE_MOVES_TIME), 2),
+    );
+}
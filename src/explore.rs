@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::{
+    analysis::ViewedPly,
+    input::{Action, Actions},
+    locale::Localizer,
+    maze::MazeRun,
+    pieces::GameAssets,
+    puzzles::PuzzleAttempt,
+    rules::{MoveHistory, ReplaceHistoryEvent, TakebackEvent},
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+// Moves tried out on the board shown, during the game or after it, which
+// are taken off the board on the way back, though kept as variations. The
+// real game is kept aside until then, and nothing is saved or played by
+// the engine meanwhile
+pub struct ExplorationPlugin;
+
+impl Plugin for ExplorationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_exploration.in_set(GameSet::Input))
+            .add_system(
+                update_exploration_banner
+                    .run_if(
+                        resource_added::<Exploration>()
+                            .or_else(resource_removed::<Exploration>())
+                            .or_else(resource_changed::<UiTheme>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct Exploration {
+    // The game as it was before exploring
+    pub game: MoveHistory,
+}
+
+#[derive(Component)]
+struct ExplorationBanner;
+
+fn toggle_exploration(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    exploration: Option<Res<Exploration>>,
+    viewed_ply: Option<Res<ViewedPly>>,
+    puzzle: Option<Res<PuzzleAttempt>>,
+    maze: Option<Res<MazeRun>>,
+    mut takeback_events: EventWriter<TakebackEvent>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+) {
+    // A puzzle's moves are checked against its solution, and a maze's
+    // aren't chess
+    if !actions.just_pressed(Action::ToggleExploration) || puzzle.is_some() || maze.is_some() {
+        return;
+    }
+
+    if let Some(exploration) = exploration {
+        info!("back to the game");
+        // The lines tried out are kept as variations
+        replace_events.send(ReplaceHistoryEvent(MoveHistory {
+            tree: history.tree.clone(),
+            ..exploration.game.clone()
+        }));
+        commands.remove_resource::<Exploration>();
+        return;
+    }
+
+    info!("exploring from move {}", history.moves.len());
+    commands.insert_resource(Exploration {
+        game: history.clone(),
+    });
+
+    // After the game, from the position being looked at
+    if let Some(viewed_ply) = viewed_ply {
+        takeback_events.send(TakebackEvent(history.moves.len() - viewed_ply.0));
+    }
+}
+
+fn update_exploration_banner(
+    mut commands: Commands,
+    exploration: Option<Res<Exploration>>,
+    localizer: Res<Localizer>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    banners: Query<Entity, With<ExplorationBanner>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(_), Some(game_assets), Ok(status_bar)) =
+        (exploration, game_assets, status_bars.get_single())
+    else {
+        return;
+    };
+
+    let text = localizer.get("exploration-banner");
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Mode,
+        &game_assets,
+        &theme,
+        ExplorationBanner,
+    );
+}
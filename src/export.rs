@@ -0,0 +1,439 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ab_glyph::{Font as _, FontArc, PxScale};
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_core::{get_board_after_moves, get_fen, Board, Move, Square, BOARD_SIZE};
+use futures_lite::future;
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops, Delay, DynamicImage, Frame, Pixel, Rgba, RgbaImage,
+};
+use qrcode::QrCode;
+
+use crate::{
+    board::{get_tile_color, PIECE_SIZE, QR_CODE_Z_INDEX},
+    input::{Action, Actions},
+    pieces::{get_atlas_index, GameAssets},
+    rules::MoveHistory,
+    settings::Settings,
+    toast::Toast,
+};
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(export_board_image)
+            .add_system(finish_animation_exports)
+            .add_system(toggle_position_qr_code);
+    }
+}
+
+#[derive(Component)]
+struct PositionQrCode;
+
+// A game animation being encoded, to report on once it is done
+#[derive(Component)]
+struct AnimationExport(Task<Result<PathBuf, String>>);
+
+fn export_board_image(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    game_assets: Res<GameAssets>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    images: Res<Assets<Image>>,
+    fonts: Res<Assets<Font>>,
+    board: Res<Board>,
+    history: Res<MoveHistory>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let export_animation = actions.just_pressed(Action::SaveAnimation);
+
+    if !actions.just_pressed(Action::SaveScreenshot) && !export_animation {
+        return;
+    }
+
+    let Some(atlas) = texture_atlases.get(&game_assets.piece_atlas) else {
+        return;
+    };
+
+    let Some(atlas_image) = images
+        .get(&atlas.texture)
+        .and_then(|image| image.clone().try_into_dynamic().ok())
+    else {
+        warn!("piece images are not loaded yet, cannot save the board");
+        toasts.send(Toast::new("toast-pieces-not-loaded"));
+        return;
+    };
+
+    let font = fonts
+        .get(&game_assets.font)
+        .filter(|_| settings.screenshot_coordinates)
+        .map(|font| font.font.clone());
+
+    // Browser builds have no file system to export to
+    let Some(path) = get_export_path(if export_animation { "gif" } else { "png" }) else {
+        warn!("there is no pictures directory to export to");
+        toasts.send(Toast::new("toast-no-pictures-directory"));
+        return;
+    };
+
+    if export_animation {
+        // Encoding a whole game takes a while, keep it off the frame
+        let settings = settings.clone();
+        let game_assets = game_assets.clone();
+        let atlas = atlas.clone();
+        let moves = history.moves.clone();
+
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            save_game_animation(
+                &path,
+                &settings,
+                &game_assets,
+                &atlas,
+                &atlas_image,
+                &moves,
+                font.as_ref(),
+            )
+            .map(|()| path.clone())
+            .map_err(|err| format!("could not save game animation to {}: {err}", path.display()))
+        });
+        commands.spawn(AnimationExport(task));
+
+        return;
+    }
+
+    let last_move = history
+        .moves
+        .last()
+        .filter(|_| settings.screenshot_last_move);
+
+    let board_image = render_board_image(
+        settings.screenshot_size,
+        &settings,
+        &game_assets,
+        atlas,
+        &atlas_image,
+        &board,
+        last_move,
+        font.as_ref(),
+    );
+
+    match board_image.save(&path) {
+        Ok(()) => {
+            info!("saved board image to {}", path.display());
+            toasts.send(Toast::new("toast-board-image-saved").with_arg("path", path.display()));
+        }
+        Err(err) => {
+            warn!("could not save board image to {}: {err}", path.display());
+            toasts.send(Toast::new("toast-board-image-failed"));
+        }
+    }
+}
+
+fn finish_animation_exports(
+    mut commands: Commands,
+    mut exports: Query<(Entity, &mut AnimationExport)>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (entity, mut export) in exports.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut export.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+
+        match result {
+            Ok(path) => {
+                info!("saved game animation to {}", path.display());
+                toasts.send(Toast::new("toast-animation-saved").with_arg("path", path.display()));
+            }
+            Err(err) => {
+                warn!("{err}");
+                toasts.send(Toast::new("toast-animation-failed"));
+            }
+        }
+    }
+}
+
+fn save_game_animation(
+    path: &Path,
+    settings: &Settings,
+    game_assets: &GameAssets,
+    atlas: &TextureAtlas,
+    atlas_image: &DynamicImage,
+    moves: &[Move],
+    font: Option<&FontArc>,
+) -> Result<(), String> {
+    let delay = Delay::from_numer_denom_ms(settings.animation_frame_delay_ms, 1);
+    let mut frames = Vec::new();
+
+    for i in 0..=moves.len() {
+        let board = get_board_after_moves(&moves[..i]);
+        let last_move = i
+            .checked_sub(1)
+            .map(|last| &moves[last])
+            .filter(|_| settings.screenshot_last_move);
+
+        let frame_image = render_board_image(
+            settings.animation_size,
+            settings,
+            game_assets,
+            atlas,
+            atlas_image,
+            &board,
+            last_move,
+            font,
+        );
+
+        frames.push(Frame::from_parts(frame_image, 0, 0, delay));
+    }
+
+    let file = fs::File::create(path).map_err(|err| err.to_string())?;
+    let mut encoder = GifEncoder::new(file);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|err| err.to_string())?;
+    encoder.encode_frames(frames).map_err(|err| err.to_string())
+}
+
+fn render_board_image(
+    size: u32,
+    settings: &Settings,
+    game_assets: &GameAssets,
+    atlas: &TextureAtlas,
+    atlas_image: &DynamicImage,
+    board: &Board,
+    last_move: Option<&Move>,
+    font: Option<&FontArc>,
+) -> RgbaImage {
+    let tile_size = (size / BOARD_SIZE as u32).max(1);
+    let mut board_image =
+        RgbaImage::new(tile_size * BOARD_SIZE as u32, tile_size * BOARD_SIZE as u32);
+
+    // Image rows grow downwards while board ranks grow upwards
+    let tile_origin = |x: i32, y: i32| {
+        (
+            x as u32 * tile_size,
+            (BOARD_SIZE - 1 - y) as u32 * tile_size,
+        )
+    };
+
+    for x in 0..BOARD_SIZE {
+        for y in 0..BOARD_SIZE {
+            let mut color = to_image_color(get_tile_color(x, y, settings));
+
+            if let Some(last_move) = last_move {
+                if last_move.from == Square(x, y) || last_move.to == Square(x, y) {
+                    color.blend(&to_image_color(settings.palette().last_move));
+                }
+            }
+
+            let (left, top) = tile_origin(x, y);
+            for px in left..left + tile_size {
+                for py in top..top + tile_size {
+                    board_image.put_pixel(px, py, color);
+                }
+            }
+        }
+    }
+
+    for (piece_type, player, position) in board.pieces() {
+        let rect = atlas.textures[get_atlas_index(game_assets, piece_type, player)];
+        let piece_image = atlas_image
+            .crop_imm(
+                rect.min.x as u32,
+                rect.min.y as u32,
+                rect.width() as u32,
+                rect.height() as u32,
+            )
+            .resize_exact(tile_size, tile_size, imageops::FilterType::Triangle)
+            .into_rgba8();
+
+        let (left, top) = tile_origin(position.x, position.y);
+        imageops::overlay(&mut board_image, &piece_image, left as i64, top as i64);
+    }
+
+    if let Some(font) = font {
+        let scale = PxScale::from(tile_size as f32 * 0.22);
+        let margin = tile_size as f32 * 0.05;
+
+        for i in 0..BOARD_SIZE {
+            // Label in the color of the other square so it reads on both
+            let (left, top) = tile_origin(i, 0);
+            let file = (b'a' + i as u8) as char;
+            draw_glyph(
+                &mut board_image,
+                font,
+                file,
+                scale,
+                (left + tile_size) as f32 - scale.x * 0.6 - margin,
+                (top + tile_size) as f32 - margin,
+                to_image_color(get_tile_color(i, 1, settings)),
+            );
+
+            let (left, top) = tile_origin(0, i);
+            let rank = char::from_digit((i + 1) as u32, 10).unwrap();
+            draw_glyph(
+                &mut board_image,
+                font,
+                rank,
+                scale,
+                left as f32 + margin,
+                top as f32 + margin + scale.y * 0.8,
+                to_image_color(get_tile_color(1, i, settings)),
+            );
+        }
+    }
+
+    board_image
+}
+
+fn draw_glyph(
+    image: &mut RgbaImage,
+    font: &FontArc,
+    character: char,
+    scale: PxScale,
+    x: f32,
+    baseline: f32,
+    color: Rgba<u8>,
+) {
+    let glyph = font
+        .glyph_id(character)
+        .with_scale_and_position(scale, ab_glyph::point(x, baseline));
+
+    let Some(outline) = font.outline_glyph(glyph) else {
+        return;
+    };
+
+    let bounds = outline.px_bounds();
+    outline.draw(|gx, gy, coverage| {
+        let px = bounds.min.x as i64 + gx as i64;
+        let py = bounds.min.y as i64 + gy as i64;
+
+        if px >= 0 && py >= 0 && (px as u32) < image.width() && (py as u32) < image.height() {
+            let mut pixel_color = color;
+            pixel_color.0[3] = (coverage * 255.0) as u8;
+            image
+                .get_pixel_mut(px as u32, py as u32)
+                .blend(&pixel_color);
+        }
+    });
+}
+
+fn to_image_color(color: Color) -> Rgba<u8> {
+    Rgba(color.as_rgba_u32().to_le_bytes())
+}
+
+fn get_export_path(extension: &str) -> Option<PathBuf> {
+    let dir = dirs::picture_dir()?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    Some(dir.join(format!("chess-{timestamp}.{extension}")))
+}
+
+fn toggle_position_qr_code(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    history: Res<MoveHistory>,
+    mut images: ResMut<Assets<Image>>,
+    qr_codes: Query<Entity, With<PositionQrCode>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    // A code for a position that is no longer on the board is worse than none
+    let toggle = actions.just_pressed(Action::ToggleQrCode);
+
+    if !toggle && !history.is_changed() {
+        return;
+    }
+
+    let was_shown = !qr_codes.is_empty();
+
+    for entity in qr_codes.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if !toggle || was_shown {
+        return;
+    }
+
+    let fen = get_fen(&history.moves);
+    info!("position: {fen}");
+
+    let contents = if settings.qr_code_lichess_url {
+        format!("https://lichess.org/analysis/{}", fen.replace(' ', "_"))
+    } else {
+        fen
+    };
+
+    let code = match QrCode::new(contents) {
+        Ok(code) => code,
+        Err(err) => {
+            warn!("could not encode the position as a QR code: {err}");
+            toasts.send(Toast::new("toast-qr-code-failed"));
+            return;
+        }
+    };
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+
+    commands.spawn((
+        SpriteBundle {
+            texture: images.add(get_qr_code_image(&code)),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(board_size * 0.8)),
+                ..default()
+            },
+            transform: Transform::from_xyz(board_size / 2.0, board_size / 2.0, QR_CODE_Z_INDEX),
+            ..default()
+        },
+        PositionQrCode,
+    ));
+}
+
+fn get_qr_code_image(code: &QrCode) -> Image {
+    // Scanners need a light margin of four modules around the code
+    let quiet_zone = 4;
+    let code_width = code.width();
+    let image_width = code_width + 2 * quiet_zone;
+    let mut data = vec![255; image_width * image_width * 4];
+
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == qrcode::Color::Dark {
+            let x = i % code_width + quiet_zone;
+            let y = i / code_width + quiet_zone;
+            let offset = (y * image_width + x) * 4;
+            data[offset..offset + 3].fill(0);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: image_width as u32,
+            height: image_width as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+
+    image
+}
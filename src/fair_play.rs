@@ -0,0 +1,55 @@
+// Prints the fair-play signals of a saved game, for whoever runs games for
+// others to look over. Started with --fair-play=<game.ron>, e.g. on one of
+// the finished games kept in the data directory. Nothing is decided from
+// them: they only point at games worth a closer look
+
+use std::path::PathBuf;
+
+use chess_core::{get_fair_play_reports, EngineConfig, FairPlayFlag};
+
+use crate::save::GameSnapshot;
+
+pub fn get_fair_play_path_from_args() -> Option<PathBuf> {
+    std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--fair-play=").map(PathBuf::from))
+}
+
+pub fn print_fair_play_report(path: PathBuf) {
+    let snapshot = GameSnapshot::read(&path).unwrap_or_else(|err| {
+        eprintln!("could not read the game {}: {err}", path.display());
+        std::process::exit(1);
+    });
+
+    let reports = get_fair_play_reports(
+        &snapshot.history,
+        &snapshot.move_times,
+        &EngineConfig::default(),
+    );
+
+    for report in reports {
+        println!(
+            "{}: {} moves judged, {} ({:.0}%) the engine's choice, {:.0} centipawns lost on average",
+            report.player.name(),
+            report.judged_moves,
+            report.engine_matches,
+            report.engine_match_rate() * 100.0,
+            report.average_loss,
+        );
+        if report.average_think_time > 0.0 {
+            println!(
+                "  {:.1}s a move on average, spread {:.0}%",
+                report.average_think_time,
+                report.think_time_spread * 100.0,
+            );
+        }
+
+        for flag in &report.flags {
+            let text = match flag {
+                FairPlayFlag::EngineCorrelation => "plays the engine's moves unusually often",
+                FairPlayFlag::UniformMoveTimes => "takes nearly the same time over every move",
+            };
+            println!("  flagged: {text}");
+        }
+    }
+}
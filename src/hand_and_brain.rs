@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+use chess_core::{get_legal_moves, Board, Piece, Player};
+use fluent::fluent_args;
+
+use crate::{
+    input::{Action, Actions},
+    locale::Localizer,
+    pieces::GameAssets,
+    rules::{CurrentTurn, GameOver, MoveHistory},
+    settings::Settings,
+    toast::Toast,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+// Team play, where each side is a brain who only names the kind of piece
+// to move, and a hand who then picks the move with a piece of that kind.
+// The pieces the hand can't move get no possible moves, so nothing else
+// can be picked. Calls go through BrainMessage, so a connection to another
+// machine only has to carry those; at one machine the teammates take turns
+// at the keyboard
+pub struct HandAndBrainPlugin;
+
+impl Plugin for HandAndBrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BrainMessage>()
+            .add_system(
+                toggle_hand_and_brain
+                    .run_if(resource_changed::<Settings>())
+                    .in_set(GameSet::Input),
+            )
+            .add_systems(
+                (
+                    send_brain_messages.run_if(not(resource_exists::<GameOver>())),
+                    handle_brain_messages,
+                )
+                    .chain()
+                    .distributive_run_if(resource_exists::<BrainCall>())
+                    .after(toggle_hand_and_brain)
+                    .in_set(GameSet::Input),
+            )
+            .add_system(
+                update_hand_and_brain_prompt
+                    .run_if(
+                        resource_exists_and_changed::<BrainCall>()
+                            .or_else(resource_removed::<BrainCall>())
+                            .or_else(resource_changed::<MoveHistory>())
+                            .or_else(resource_added::<GameOver>())
+                            .or_else(resource_changed::<Settings>())
+                            .or_else(resource_changed::<Localizer>())
+                            .or_else(resource_changed::<UiTheme>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+pub struct BrainMessage {
+    pub by: Player,
+    pub piece: Piece,
+}
+
+// Present while hand and brain is played. The piece named, with the number
+// of moves played when it was, so a call runs out once the hand moves
+#[derive(Resource, Default)]
+pub struct BrainCall(Option<(usize, Piece)>);
+
+impl BrainCall {
+    pub fn get(&self, ply: usize) -> Option<Piece> {
+        self.0
+            .filter(|(called_at, _)| *called_at == ply)
+            .map(|(_, piece)| piece)
+    }
+}
+
+#[derive(Component)]
+struct HandAndBrainPrompt;
+
+fn toggle_hand_and_brain(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    brain_call: Option<Res<BrainCall>>,
+) {
+    if settings.hand_and_brain && brain_call.is_none() {
+        info!("playing hand and brain");
+        commands.init_resource::<BrainCall>();
+    } else if !settings.hand_and_brain && brain_call.is_some() {
+        commands.remove_resource::<BrainCall>();
+    }
+}
+
+fn send_brain_messages(
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    current_turn: Res<CurrentTurn>,
+    mut messages: EventWriter<BrainMessage>,
+) {
+    // The engine plays alone
+    if settings.engine_opponent == Some(current_turn.0) {
+        return;
+    }
+
+    let piece = [
+        (Action::CallPawn, Piece::Pawn),
+        (Action::CallKnight, Piece::Knight),
+        (Action::CallBishop, Piece::Bishop),
+        (Action::CallRook, Piece::Rook),
+        (Action::CallQueen, Piece::Queen),
+        (Action::CallKing, Piece::King),
+    ]
+    .into_iter()
+    .find(|(action, _)| actions.just_pressed(*action))
+    .map(|(_, piece)| piece);
+
+    if let Some(piece) = piece {
+        messages.send(BrainMessage {
+            by: current_turn.0,
+            piece,
+        });
+    }
+}
+
+// A call stands until the hand moves, and one naming a piece that has no
+// moves is turned down, as the hand couldn't follow it
+fn handle_brain_messages(
+    mut messages: EventReader<BrainMessage>,
+    mut brain_call: ResMut<BrainCall>,
+    current_turn: Res<CurrentTurn>,
+    history: Res<MoveHistory>,
+    board: Res<Board>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let ply = history.moves.len();
+
+    for message in messages.iter() {
+        if message.by != current_turn.0 || brain_call.get(ply).is_some() {
+            continue;
+        }
+
+        let can_move = board.pieces().any(|(piece, player, position)| {
+            piece == message.piece
+                && player == message.by
+                && !get_legal_moves(&piece, &position, &player, &board).is_empty()
+        });
+        if !can_move {
+            toasts.send(Toast::new("toast-brain-no-moves").with_arg("piece", message.piece.name()));
+            continue;
+        }
+
+        info!(
+            "{}'s brain calls a {}",
+            message.by.name(),
+            message.piece.name()
+        );
+        brain_call.0 = Some((ply, message.piece));
+    }
+}
+
+fn update_hand_and_brain_prompt(
+    mut commands: Commands,
+    brain_call: Option<Res<BrainCall>>,
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    settings: Res<Settings>,
+    localizer: Res<Localizer>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    game_over: Option<Res<GameOver>>,
+    prompts: Query<Entity, With<HandAndBrainPrompt>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in prompts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(brain_call), Some(game_assets), Ok(status_bar)) =
+        (brain_call, game_assets, status_bars.get_single())
+    else {
+        return;
+    };
+    if game_over.is_some() || settings.engine_opponent == Some(current_turn.0) {
+        return;
+    }
+
+    let player = current_turn.0.name();
+    let text = match brain_call.get(history.moves.len()) {
+        Some(piece) => localizer.format(
+            "hand-prompt",
+            &fluent_args!["player" => player, "piece" => piece.name()],
+        ),
+        None => localizer.format("brain-prompt", &fluent_args!["player" => player]),
+    };
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Mode,
+        &game_assets,
+        &theme,
+        HandAndBrainPrompt,
+    );
+}
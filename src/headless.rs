@@ -0,0 +1,329 @@
+// Engine against engine games with no window, as fast as they can be played.
+// Started with --headless-match; finished games go to stdout as PGN and the
+// running score to stderr. Two engine settings can be matched against each
+// other, and with --sprt the match stops once the result is clear. With
+// --armageddon a match that ends level is decided by one more game, which
+// Black wins if it's drawn
+
+use bevy::{app::AppExit, prelude::*};
+use chess_core::{
+    get_all_legal_moves, get_armageddon_pgn, get_best_move, get_game_result, get_pgn,
+    get_starting_pieces, Board, EngineConfig, GameResult, Move, Piece, Player,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    input::Selection,
+    rules::{CurrentTurn, MoveEvent, MoveHistory, RulesPlugin},
+    sprt::{Sprt, SprtOutcome, Tally},
+    GameSet, GameSetsPlugin, GameState,
+};
+
+// Games that get this long are stopped and written as unfinished
+const MAX_PLIES: usize = 1000;
+
+#[derive(Resource, Clone)]
+pub struct HeadlessMatch {
+    // The most games played. An SPRT match can stop before
+    pub games: u32,
+    // Engine A, then engine B. They swap colors every game, A starting as
+    // White
+    pub engines: [EngineConfig; 2],
+    // Random moves at the start of each game, so they don't all play out
+    // the same way
+    pub opening_plies: usize,
+    pub seed: u64,
+    pub sprt: Option<Sprt>,
+    pub armageddon: bool,
+}
+
+impl HeadlessMatch {
+    // None unless --headless-match is given. The other options are
+    // --games=N, --opening-plies=N, --seed=N, the engine settings --depth=N
+    // and --center-weight=N, the same with a -b suffix to set engine B
+    // apart, --sprt with --elo0=N and --elo1=N, and --armageddon
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if !args.iter().any(|arg| arg == "--headless-match") {
+            return None;
+        }
+
+        let mut games = None;
+        let mut engine = EngineConfig::default();
+        let mut depth_b = None;
+        let mut center_weight_b = None;
+        let mut opening_plies = 4;
+        let mut seed = 0;
+        let mut elo0 = None;
+        let mut elo1 = None;
+
+        for arg in &args {
+            let Some((name, value)) = arg.split_once('=') else {
+                continue;
+            };
+
+            let parsed = match name {
+                "--games" => value.parse().map(|value| games = Some(value)).is_ok(),
+                "--depth" => value.parse().map(|value| engine.depth = value).is_ok(),
+                "--depth-b" => value.parse().map(|value| depth_b = Some(value)).is_ok(),
+                "--center-weight" => value
+                    .parse()
+                    .map(|value| engine.center_weight = value)
+                    .is_ok(),
+                "--center-weight-b" => value
+                    .parse()
+                    .map(|value| center_weight_b = Some(value))
+                    .is_ok(),
+                "--opening-plies" => value.parse().map(|value| opening_plies = value).is_ok(),
+                "--seed" => value.parse().map(|value| seed = value).is_ok(),
+                "--elo0" => value.parse().map(|value| elo0 = Some(value)).is_ok(),
+                "--elo1" => value.parse().map(|value| elo1 = Some(value)).is_ok(),
+                _ => true,
+            };
+
+            if !parsed {
+                eprintln!("{name} needs a number, not {value:?}");
+                std::process::exit(1);
+            }
+        }
+
+        let sprt = args.iter().any(|arg| arg == "--sprt").then(|| {
+            let default = Sprt::default();
+            Sprt {
+                elo0: elo0.unwrap_or(default.elo0),
+                elo1: elo1.unwrap_or(default.elo1),
+                ..default
+            }
+        });
+
+        let engine_b = EngineConfig {
+            depth: depth_b.unwrap_or(engine.depth),
+            center_weight: center_weight_b.unwrap_or(engine.center_weight),
+        };
+
+        Some(Self {
+            // An SPRT match usually settles well before this
+            games: games.unwrap_or(if sprt.is_some() { 20_000 } else { 10 }),
+            engines: [engine, engine_b],
+            opening_plies,
+            seed,
+            sprt,
+            armageddon: args.iter().any(|arg| arg == "--armageddon"),
+        })
+    }
+
+    // The engine playing the color in the game, counted from 0, and its
+    // index in engines
+    fn get_engine(&self, game: u32, player: Player) -> (usize, &EngineConfig) {
+        let index = match player {
+            Player::White => game as usize % 2,
+            Player::Black => (game as usize + 1) % 2,
+        };
+        (index, &self.engines[index])
+    }
+
+    fn get_engine_name(&self, game: u32, player: Player) -> String {
+        let (index, engine) = self.get_engine(game, player);
+        format!(
+            "Engine {} (depth {}, center weight {})",
+            ["A", "B"][index],
+            engine.depth,
+            engine.center_weight
+        )
+    }
+}
+
+pub fn run_headless_match(headless_match: HeadlessMatch) {
+    // MinimalPlugins loops without waiting between frames
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_plugin(GameSetsPlugin)
+        .init_resource::<Selection>()
+        .add_plugin(RulesPlugin)
+        .insert_resource(MatchScore {
+            tally: Tally::default(),
+            rng: StdRng::seed_from_u64(headless_match.seed),
+            tiebreak: false,
+        })
+        .insert_resource(headless_match)
+        .insert_resource(NextState(Some(GameState::Playing)))
+        .add_startup_system(start_game)
+        .add_system(play_engine_moves.in_set(GameSet::Rules))
+        .add_system(finish_games.in_set(GameSet::Render))
+        .run();
+}
+
+#[derive(Resource)]
+struct MatchScore {
+    // From engine A's side
+    tally: Tally,
+    rng: StdRng,
+    // Playing the armageddon game, which isn't counted in the tally
+    tiebreak: bool,
+}
+
+// What the board and pieces plugins would spawn, minus the sprites
+fn start_game(
+    mut commands: Commands,
+    pieces: Query<Entity, With<Piece>>,
+    mut board: ResMut<Board>,
+    mut history: ResMut<MoveHistory>,
+    mut current_turn: ResMut<CurrentTurn>,
+) {
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (piece_type, player, position) in get_starting_pieces() {
+        commands.spawn((piece_type, player, position));
+    }
+
+    *board = Board::from_pieces(&get_starting_pieces());
+    *history = MoveHistory::default();
+    current_turn.0 = Player::White;
+}
+
+fn play_engine_moves(
+    headless_match: Res<HeadlessMatch>,
+    mut score: ResMut<MatchScore>,
+    board: Res<Board>,
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    mut move_events: EventWriter<MoveEvent>,
+) {
+    let mv = if history.moves.len() < headless_match.opening_plies {
+        get_all_legal_moves(&board, current_turn.0)
+            .choose(&mut score.rng)
+            .copied()
+    } else {
+        let (_, engine) = headless_match.get_engine(score.tally.games(), current_turn.0);
+        get_best_move(&board, current_turn.0, engine)
+    };
+
+    if let Some(mv) = mv {
+        move_events.send(MoveEvent(mv));
+    }
+}
+
+fn finish_games(
+    commands: Commands,
+    headless_match: Res<HeadlessMatch>,
+    mut score: ResMut<MatchScore>,
+    pieces: Query<Entity, With<Piece>>,
+    board: ResMut<Board>,
+    history: ResMut<MoveHistory>,
+    current_turn: ResMut<CurrentTurn>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let result = get_game_result(&history.moves);
+    if result.is_none() && history.moves.len() < MAX_PLIES {
+        return;
+    }
+
+    let game = score.tally.games();
+    if score.tiebreak {
+        finish_tiebreak(&headless_match, game, &history.moves, result);
+        app_exit_events.send(AppExit);
+        return;
+    }
+
+    println!(
+        "{}",
+        get_pgn(
+            &history.moves,
+            &[
+                ("Event", "Headless match".to_string()),
+                ("Site", "?".to_string()),
+                ("Date", "????.??.??".to_string()),
+                ("Round", (game + 1).to_string()),
+                ("White", headless_match.get_engine_name(game, Player::White)),
+                ("Black", headless_match.get_engine_name(game, Player::Black)),
+            ],
+        )
+    );
+
+    match result.as_ref().and_then(GameResult::winner) {
+        Some(winner) if headless_match.get_engine(game, winner).0 == 0 => score.tally.wins += 1,
+        Some(_) => score.tally.losses += 1,
+        None => score.tally.draws += 1,
+    }
+
+    let tally = score.tally;
+    let elo = tally.elo().map_or("-".to_string(), |(elo, margin)| {
+        format!("{elo:+.1} +/- {margin:.1}")
+    });
+    eprint!(
+        "game {}: {} ({}), A +{} ={} -{}, Elo {elo}",
+        tally.games(),
+        result.map_or("*", |result| result.score()),
+        result.map_or("move limit", |result| result.name()),
+        tally.wins,
+        tally.draws,
+        tally.losses,
+    );
+
+    let mut outcome = None;
+    if let Some(sprt) = &headless_match.sprt {
+        let (lower, upper) = sprt.bounds();
+        eprint!(", LLR {:.2} [{lower:.2}, {upper:.2}]", sprt.llr(&tally));
+        outcome = sprt.get_outcome(&tally);
+    }
+    eprintln!();
+
+    if let (Some(outcome), Some(sprt)) = (outcome, &headless_match.sprt) {
+        let accepted = match outcome {
+            SprtOutcome::AcceptElo0 => sprt.elo0,
+            SprtOutcome::AcceptElo1 => sprt.elo1,
+        };
+        eprintln!(
+            "SPRT accepts A - B = {accepted:+} Elo after {} games",
+            tally.games()
+        );
+        app_exit_events.send(AppExit);
+    } else if tally.games() >= headless_match.games {
+        // Colors keep alternating, so whichever engine had Black in the
+        // last game gets the extra time now
+        if headless_match.armageddon && tally.wins == tally.losses {
+            eprintln!("match level, playing an armageddon game");
+            score.tiebreak = true;
+            start_game(commands, pieces, board, history, current_turn);
+        } else {
+            app_exit_events.send(AppExit);
+        }
+    } else {
+        start_game(commands, pieces, board, history, current_turn);
+    }
+}
+
+// Unfinished games count as draws, so the armageddon game always has a
+// winner. There are no clocks to give White more time on, so it's Black's
+// draw odds alone that make up for moving second
+fn finish_tiebreak(
+    headless_match: &HeadlessMatch,
+    game: u32,
+    moves: &[Move],
+    result: Option<GameResult>,
+) {
+    println!(
+        "{}",
+        get_armageddon_pgn(
+            moves,
+            &[
+                ("Event", "Headless match, armageddon".to_string()),
+                ("Site", "?".to_string()),
+                ("Date", "????.??.??".to_string()),
+                ("Round", (game + 1).to_string()),
+                ("White", headless_match.get_engine_name(game, Player::White)),
+                ("Black", headless_match.get_engine_name(game, Player::Black)),
+            ],
+        )
+    );
+
+    let winner = result.map_or(Player::Black, |result| result.armageddon_winner());
+    let (index, _) = headless_match.get_engine(game, winner);
+    eprintln!(
+        "armageddon: {} ({}), engine {} wins the match",
+        result.map_or("0-1", |result| result.armageddon_score()),
+        result.map_or("move limit", |result| result.name()),
+        ["A", "B"][index],
+    );
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use chess_core::{get_attack_map, get_starting_pieces, Board, Move, Player, BOARD_SIZE};
+
+use crate::{
+    board::{BoardRoot, HEATMAP_Z_INDEX, PIECE_SIZE},
+    input::{Action, Actions},
+    rules::{GameOver, MoveHistory},
+    GameSet,
+};
+
+// Each side shades its own half of the square, so the two stay apart
+// whatever the colors look like
+const WHITE_HEAT_COLOR: Color = Color::rgb(0.1, 0.3, 1.0);
+const BLACK_HEAT_COLOR: Color = Color::rgb(1.0, 0.2, 0.1);
+// Opacity added per attacker, up to the most
+const ATTACKER_ALPHA: f32 = 0.2;
+const MAX_ALPHA: f32 = 0.8;
+
+type HeatMap = [[f32; BOARD_SIZE as usize]; BOARD_SIZE as usize];
+
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Heatmap>()
+            .add_system(toggle_heatmap)
+            .add_system(show_activity_heatmap.run_if(resource_added::<GameOver>()))
+            .add_system(
+                update_heatmap
+                    .after(toggle_heatmap)
+                    .after(show_activity_heatmap)
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+// While the game goes on the heatmap shows the attacks on each square.
+// Once it is over, it sums up where each side's pieces went and attacked
+// over the whole game
+#[derive(Resource, Default)]
+struct Heatmap {
+    shown: bool,
+}
+
+#[derive(Component)]
+struct HeatmapSquare;
+
+fn toggle_heatmap(actions: Res<Actions>, mut heatmap: ResMut<Heatmap>) {
+    if actions.just_pressed(Action::ToggleHeatmap) {
+        heatmap.shown = !heatmap.shown;
+    }
+}
+
+fn show_activity_heatmap(mut heatmap: ResMut<Heatmap>) {
+    heatmap.shown = true;
+}
+
+fn update_heatmap(
+    mut commands: Commands,
+    heatmap: Res<Heatmap>,
+    board: Res<Board>,
+    history: Res<MoveHistory>,
+    game_over: Option<Res<GameOver>>,
+    squares: Query<Entity, With<HeatmapSquare>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    if !heatmap.is_changed() && !history.is_changed() {
+        return;
+    }
+
+    for entity in squares.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    if !heatmap.shown {
+        return;
+    }
+
+    let heat_maps = if game_over.is_some() {
+        get_activity_heat_maps(&history.moves)
+    } else {
+        [Player::White, Player::Black].map(|player| {
+            get_attack_map(&board, player)
+                .map(|file| file.map(|attackers| attackers as f32 * ATTACKER_ALPHA))
+        })
+    };
+
+    let half_size = Vec2::new(PIECE_SIZE as f32 / 2.0, PIECE_SIZE as f32);
+
+    for (heat_map, color, offset) in [
+        (heat_maps[0], WHITE_HEAT_COLOR, 0),
+        (heat_maps[1], BLACK_HEAT_COLOR, PIECE_SIZE / 2),
+    ] {
+        for x in 0..BOARD_SIZE {
+            for y in 0..BOARD_SIZE {
+                let heat = heat_map[x as usize][y as usize];
+                if heat <= 0.0 {
+                    continue;
+                }
+
+                let square = commands
+                    .spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: color.with_a(heat.min(MAX_ALPHA)),
+                                custom_size: Some(half_size),
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(
+                                (x * PIECE_SIZE + offset + PIECE_SIZE / 4) as f32,
+                                (y * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                                HEATMAP_Z_INDEX,
+                            ),
+                            ..default()
+                        },
+                        HeatmapSquare,
+                    ))
+                    .id();
+
+                commands.entity(board_root).add_child(square);
+            }
+        }
+    }
+}
+
+// Squares each side moved to, plus the squares it attacked after each of
+// its moves, scaled so the busiest square of either side is darkest
+fn get_activity_heat_maps(moves: &[Move]) -> [HeatMap; 2] {
+    let mut activity = [[[0u32; BOARD_SIZE as usize]; BOARD_SIZE as usize]; 2];
+    let mut board = Board::from_pieces(&get_starting_pieces());
+
+    for (ply, mv) in moves.iter().enumerate() {
+        let (side, player) = if ply % 2 == 0 {
+            (0, Player::White)
+        } else {
+            (1, Player::Black)
+        };
+
+        board.apply_move(mv);
+        activity[side][mv.to.0 as usize][mv.to.1 as usize] += 1;
+
+        for (x, file) in get_attack_map(&board, player).iter().enumerate() {
+            for (y, attackers) in file.iter().enumerate() {
+                activity[side][x][y] += *attackers as u32;
+            }
+        }
+    }
+
+    let busiest = activity
+        .iter()
+        .flatten()
+        .flatten()
+        .max()
+        .copied()
+        .unwrap_or(0);
+    activity.map(|side| {
+        side.map(|file| file.map(|count| MAX_ALPHA * count as f32 / busiest.max(1) as f32))
+    })
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use chess_core::{
+    get_halfmove_clock, get_king_safety, get_repetition_count, Board, KingSafety, Player,
+};
+use fluent::fluent_args;
+
+use crate::{
+    locale::Localizer,
+    pieces::GameAssets,
+    rules::MoveHistory,
+    ui_theme::{ThemedBackground, UiTheme},
+    GameSet, GameState,
+};
+
+// A draw can be claimed at these counts
+const FIFTY_MOVE_LIMIT: usize = 100;
+const REPETITION_LIMIT: usize = 3;
+// Counters this close to their limit, and kings rated this low, are drawn
+// in the theme's warning color
+const FIFTY_MOVE_WARNING: usize = 80;
+const REPETITION_WARNING: usize = 2;
+const KING_SAFETY_WARNING: u32 = 1;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_hud.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(update_hud.in_set(GameSet::Render));
+    }
+}
+
+// The status area in the top right corner
+#[derive(Component)]
+struct Hud;
+
+fn spawn_hud(mut commands: Commands, game_assets: Res<GameAssets>, theme: Res<UiTheme>) {
+    let style = TextStyle {
+        font: game_assets.font.clone(),
+        font_size: 12.0,
+        color: theme.text,
+    };
+
+    commands.spawn((
+        // The two counters, then a king safety meter for each side
+        TextBundle::from_sections([
+            TextSection::new("", style.clone()),
+            TextSection::new("", style.clone()),
+            TextSection::new("", style.clone()),
+            TextSection::new("", style),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(8.0),
+                top: Val::Px(8.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        })
+        .with_background_color(theme.panel),
+        ThemedBackground(|theme| theme.panel),
+        Hud,
+    ));
+}
+
+fn update_hud(
+    history: Res<MoveHistory>,
+    board: Res<Board>,
+    localizer: Res<Localizer>,
+    theme: Res<UiTheme>,
+    mut huds: Query<&mut Text, With<Hud>>,
+    added_huds: Query<(), Added<Hud>>,
+) {
+    if !board.is_changed()
+        && !history.is_changed()
+        && !localizer.is_changed()
+        && !theme.is_changed()
+        && added_huds.is_empty()
+    {
+        return;
+    }
+
+    let halfmoves = get_halfmove_clock(&history.moves);
+    let repetitions = get_repetition_count(&history.moves);
+
+    for mut text in huds.iter_mut() {
+        text.sections[0].value = localizer.format(
+            "hud-fifty-moves",
+            &fluent_args!["halfmoves" => halfmoves, "limit" => FIFTY_MOVE_LIMIT],
+        ) + "\n";
+        text.sections[0].style.color = if halfmoves >= FIFTY_MOVE_WARNING {
+            theme.warning_text
+        } else {
+            theme.text
+        };
+
+        text.sections[1].value = localizer.format(
+            "hud-repetitions",
+            &fluent_args!["count" => repetitions, "limit" => REPETITION_LIMIT],
+        ) + "\n";
+        text.sections[1].style.color = if repetitions >= REPETITION_WARNING {
+            theme.warning_text
+        } else {
+            theme.text
+        };
+
+        for (section, player) in text.sections[2..]
+            .iter_mut()
+            .zip([Player::White, Player::Black])
+        {
+            let rating = get_king_safety(&board, player).map_or(0, |safety| safety.rating());
+            let meter = "■".repeat(rating as usize)
+                + &"□".repeat((KingSafety::MAX_RATING - rating) as usize);
+
+            section.value = localizer.format(
+                "hud-king-safety",
+                &fluent_args!["player" => player.name(), "meter" => meter],
+            );
+            if player == Player::White {
+                section.value.push('\n');
+            }
+            section.style.color = if rating <= KING_SAFETY_WARNING {
+                theme.warning_text
+            } else {
+                theme.text
+            };
+        }
+    }
+}
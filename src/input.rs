@@ -0,0 +1,1147 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    input::{mouse::MouseWheel, InputSystem},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use chess_core::{
+    get_possible_moves, is_promotion, Board, BoardPosition, Move, Piece, Player, Square,
+    BOARD_SIZE, PROMOTION_PIECES,
+};
+use fluent::fluent_args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::ForkedGame,
+    board::{
+        get_square_at, BoardRoot, CURSOR_Z_INDEX, GHOST_Z_INDEX, PIECE_SIZE, PIECE_Z_INDEX,
+        PROMOTION_Z_INDEX,
+    },
+    camera::{get_window_ray, GameCamera},
+    locale::Localizer,
+    opponent::get_engine_player,
+    pieces::{get_atlas_index, GameAssets},
+    rules::{CurrentTurn, GameOver, MoveEvent, PossibleMoves},
+    save::ReplayPlayback,
+    settings::Settings,
+    toast::Toast,
+    ui::KeyRemapping,
+    GameSet,
+};
+
+// How much a piece that can be moved grows under the mouse
+const HOVER_SCALE: f32 = 1.1;
+// Above everything on the board but the guides, while carried
+const DRAG_Z_INDEX: f32 = 1.9;
+const DRAG_GHOST_ALPHA: f32 = 0.4;
+// How long a piece dropped where it can't go takes to slide back
+const SNAP_BACK_SECONDS: f32 = 0.15;
+const PROMOTION_BACKDROP_COLOR: Color = Color::rgba(0.95, 0.95, 0.95, 0.9);
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .init_resource::<HoveredPiece>()
+            .insert_resource(Actions::default())
+            .add_event::<SquareClicked>()
+            // After the board it goes on has been spawned
+            .add_startup_system(spawn_keyboard_cursor.in_base_set(StartupSet::PostStartup))
+            .add_system(
+                update_actions
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem),
+            )
+            .add_systems(
+                (
+                    handle_mouse_clicks.run_if(not(resource_exists::<ReplayPlayback>())),
+                    handle_keyboard_cursor.run_if(not(resource_exists::<ReplayPlayback>())),
+                )
+                    .in_set(GameSet::Input),
+            )
+            .add_system(
+                handle_square_clicks
+                    .run_if(not(resource_exists::<GameOver>()))
+                    .in_set(GameSet::Rules),
+            )
+            .add_systems(
+                (show_staged_move, show_promotion_choices)
+                    .distributive_run_if(resource_changed::<Selection>())
+                    .in_set(GameSet::Render),
+            )
+            .add_system(show_hovered_piece.in_set(GameSet::Render))
+            .add_system(drop_dragged_piece.in_set(GameSet::Input))
+            .add_system(start_piece_drag.in_set(GameSet::Apply))
+            .add_systems((move_dragged_piece, snap_back_pieces).in_set(GameSet::Render));
+    }
+}
+
+#[derive(Component)]
+pub struct KeyboardCursor;
+
+// The piece under the mouse, if it can be moved
+#[derive(Resource, Default)]
+pub struct HoveredPiece(pub Option<Entity>);
+
+// A dragged piece let go where it can't go, sliding back to its square.
+// Until it gets there it is placed here rather than by its square
+#[derive(Component)]
+pub struct SnapBack {
+    // Where it was let go
+    from: Vec2,
+    timer: Timer,
+}
+
+// The faint copy of a dragged piece left where it stood
+#[derive(Component)]
+struct DragGhost;
+
+// The piece of a staged move, drawn faintly where it would land
+#[derive(Component)]
+struct StagedMoveGhost;
+
+// One of the pieces offered to a pawn reaching the last rank
+#[derive(Component)]
+struct PromotionChoice;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    Select,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    Confirm,
+    NextPiece,
+    PreviousPiece,
+    ClearSelection,
+    SaveScreenshot,
+    SaveAnimation,
+    ToggleQrCode,
+    RemapKeys,
+    CycleTheme,
+    CycleLanguage,
+    IncreaseUiScale,
+    DecreaseUiScale,
+    ToggleDiagnostics,
+    FindMate,
+    ToggleHeatmap,
+    PreviousMove,
+    NextMove,
+    FirstMove,
+    LastMove,
+    PreviewLine,
+    RequestTakeback,
+    AcceptTakeback,
+    DeclineTakeback,
+    PlayFromHere,
+    ToggleExploration,
+    NextVariation,
+    PreviousVariation,
+    PromoteVariation,
+    DeleteVariation,
+    ToggleMovePanel,
+    ToggleCommentBox,
+    ClearArrows,
+    FitBoard,
+    ToggleIsometricView,
+    ToggleAnalysisWindow,
+    CallPawn,
+    CallKnight,
+    CallBishop,
+    CallRook,
+    CallQueen,
+    CallKing,
+    SavePuzzle,
+    NextPuzzle,
+    LeavePuzzles,
+    NextMaze,
+    LeaveMaze,
+    SealMove,
+    ResumeLastGame,
+}
+
+impl Action {
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            Action::Select => "action-select",
+            Action::CursorLeft => "action-cursor-left",
+            Action::CursorRight => "action-cursor-right",
+            Action::CursorUp => "action-cursor-up",
+            Action::CursorDown => "action-cursor-down",
+            Action::Confirm => "action-confirm",
+            Action::NextPiece => "action-next-piece",
+            Action::PreviousPiece => "action-previous-piece",
+            Action::ClearSelection => "action-clear-selection",
+            Action::SaveScreenshot => "action-save-screenshot",
+            Action::SaveAnimation => "action-save-animation",
+            Action::ToggleQrCode => "action-toggle-qr-code",
+            Action::RemapKeys => "action-remap-keys",
+            Action::CycleTheme => "action-cycle-theme",
+            Action::CycleLanguage => "action-cycle-language",
+            Action::IncreaseUiScale => "action-increase-ui-scale",
+            Action::DecreaseUiScale => "action-decrease-ui-scale",
+            Action::ToggleDiagnostics => "action-toggle-diagnostics",
+            Action::FindMate => "action-find-mate",
+            Action::ToggleHeatmap => "action-toggle-heatmap",
+            Action::PreviousMove => "action-previous-move",
+            Action::NextMove => "action-next-move",
+            Action::FirstMove => "action-first-move",
+            Action::LastMove => "action-last-move",
+            Action::PreviewLine => "action-preview-line",
+            Action::RequestTakeback => "action-request-takeback",
+            Action::AcceptTakeback => "action-accept-takeback",
+            Action::DeclineTakeback => "action-decline-takeback",
+            Action::PlayFromHere => "action-play-from-here",
+            Action::ToggleExploration => "action-toggle-exploration",
+            Action::NextVariation => "action-next-variation",
+            Action::PreviousVariation => "action-previous-variation",
+            Action::PromoteVariation => "action-promote-variation",
+            Action::DeleteVariation => "action-delete-variation",
+            Action::ToggleMovePanel => "action-toggle-move-panel",
+            Action::ToggleCommentBox => "action-toggle-comment-box",
+            Action::ClearArrows => "action-clear-arrows",
+            Action::FitBoard => "action-fit-board",
+            Action::ToggleIsometricView => "action-toggle-isometric-view",
+            Action::ToggleAnalysisWindow => "action-toggle-analysis-window",
+            Action::CallPawn => "action-call-pawn",
+            Action::CallKnight => "action-call-knight",
+            Action::CallBishop => "action-call-bishop",
+            Action::CallRook => "action-call-rook",
+            Action::CallQueen => "action-call-queen",
+            Action::CallKing => "action-call-king",
+            Action::SavePuzzle => "action-save-puzzle",
+            Action::NextPuzzle => "action-next-puzzle",
+            Action::LeavePuzzles => "action-leave-puzzles",
+            Action::NextMaze => "action-next-maze",
+            Action::LeaveMaze => "action-leave-maze",
+            Action::SealMove => "action-seal-move",
+            Action::ResumeLastGame => "action-resume-last-game",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    // Shift has to be held, and plain keys only fire while it is not
+    ShiftKey(KeyCode),
+    Mouse(MouseButton),
+    // The mouse wheel turned while over the board
+    ScrollUp,
+    ScrollDown,
+}
+
+impl Binding {
+    // The scroll is how far the wheel turned this frame, up being positive
+    pub fn just_pressed(
+        &self,
+        keys: &Input<KeyCode>,
+        buttons: &Input<MouseButton>,
+        scroll: f32,
+    ) -> bool {
+        let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+        match self {
+            Binding::Key(key) => !shift && keys.just_pressed(*key),
+            Binding::ShiftKey(key) => shift && keys.just_pressed(*key),
+            Binding::Mouse(button) => buttons.just_pressed(*button),
+            Binding::ScrollUp => scroll > 0.0,
+            Binding::ScrollDown => scroll < 0.0,
+        }
+    }
+
+    // Shift doesn't matter here, as it may have been let go first
+    pub fn just_released(&self, keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> bool {
+        match self {
+            Binding::Key(key) | Binding::ShiftKey(key) => keys.just_released(*key),
+            Binding::Mouse(button) => buttons.just_released(*button),
+            Binding::ScrollUp | Binding::ScrollDown => false,
+        }
+    }
+
+    pub fn label(&self, localizer: &Localizer) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::ShiftKey(key) => {
+                localizer.format("binding-shift", &fluent_args!["key" => format!("{key:?}")])
+            }
+            Binding::Mouse(button) => localizer.format(
+                "binding-mouse",
+                &fluent_args!["button" => format!("{button:?}")],
+            ),
+            Binding::ScrollUp => localizer.get("binding-scroll-up"),
+            Binding::ScrollDown => localizer.get("binding-scroll-down"),
+        }
+    }
+}
+
+// Actions triggered this frame, so systems never look at raw input
+#[derive(Resource, Default)]
+pub struct Actions {
+    pressed: Vec<Action>,
+    // Only dragging a piece cares when its input is let go
+    released: Vec<Action>,
+}
+
+impl Actions {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.released.contains(&action)
+    }
+}
+
+// Where the player is in picking a move. A piece's moves are worked out
+// once, when it is picked, and dropped along with the selection
+#[derive(Resource, Default, Clone, PartialEq, Eq)]
+pub enum Selection {
+    #[default]
+    Idle,
+    PieceSelected {
+        entity: Entity,
+        moves: Vec<Square>,
+    },
+    // Picked up with the mouse, and following it instead of its square
+    // until it is let go
+    Dragging {
+        entity: Entity,
+        moves: Vec<Square>,
+    },
+    // Picked with confirm_moves on, and waiting to be confirmed
+    MoveStaged {
+        entity: Entity,
+        mv: Move,
+    },
+    // A pawn's move to the last rank, waiting for what it becomes to be
+    // picked from the squares of get_promotion_squares
+    AwaitingPromotion {
+        entity: Entity,
+        mv: Move,
+    },
+}
+
+impl Selection {
+    pub fn piece(&self) -> Option<Entity> {
+        match self {
+            Selection::Idle => None,
+            Selection::PieceSelected { entity, .. }
+            | Selection::Dragging { entity, .. }
+            | Selection::MoveStaged { entity, .. }
+            | Selection::AwaitingPromotion { entity, .. } => Some(*entity),
+        }
+    }
+
+    pub fn moves(&self) -> &[Square] {
+        match self {
+            Selection::Idle => &[],
+            Selection::PieceSelected { moves, .. } | Selection::Dragging { moves, .. } => moves,
+            Selection::MoveStaged { mv, .. } | Selection::AwaitingPromotion { mv, .. } => {
+                std::slice::from_ref(&mv.to)
+            }
+        }
+    }
+
+    pub fn dragged(&self) -> Option<Entity> {
+        match self {
+            Selection::Dragging { entity, .. } => Some(*entity),
+            _ => None,
+        }
+    }
+}
+
+// A square picked with the mouse or the keyboard cursor
+pub struct SquareClicked(pub Square);
+
+// Where the pieces of PROMOTION_PIECES are offered, in order, from the
+// square the pawn reaches toward the middle of the board
+pub fn get_promotion_squares(to: Square) -> [Square; 4] {
+    let step = if to.1 == 0 { 1 } else { -1 };
+    [0, 1, 2, 3].map(|i| Square(to.0, to.1 + i * step))
+}
+
+pub fn get_default_key_bindings() -> BTreeMap<Action, Vec<Binding>> {
+    BTreeMap::from([
+        (Action::Select, vec![Binding::Mouse(MouseButton::Left)]),
+        (Action::CursorLeft, vec![Binding::Key(KeyCode::Left)]),
+        (Action::CursorRight, vec![Binding::Key(KeyCode::Right)]),
+        (Action::CursorUp, vec![Binding::Key(KeyCode::Up)]),
+        (Action::CursorDown, vec![Binding::Key(KeyCode::Down)]),
+        (
+            Action::Confirm,
+            vec![
+                Binding::Key(KeyCode::Return),
+                Binding::Key(KeyCode::NumpadEnter),
+            ],
+        ),
+        (Action::NextPiece, vec![Binding::Key(KeyCode::Tab)]),
+        (Action::PreviousPiece, vec![Binding::ShiftKey(KeyCode::Tab)]),
+        (Action::ClearSelection, vec![Binding::Key(KeyCode::Escape)]),
+        (Action::SaveScreenshot, vec![Binding::Key(KeyCode::F12)]),
+        (Action::SaveAnimation, vec![Binding::ShiftKey(KeyCode::F12)]),
+        (Action::ToggleQrCode, vec![Binding::Key(KeyCode::Q)]),
+        (Action::RemapKeys, vec![Binding::Key(KeyCode::F1)]),
+        (Action::CycleTheme, vec![Binding::Key(KeyCode::F2)]),
+        (Action::CycleLanguage, vec![Binding::Key(KeyCode::F3)]),
+        (
+            Action::IncreaseUiScale,
+            vec![
+                Binding::Key(KeyCode::Equals),
+                Binding::Key(KeyCode::NumpadAdd),
+            ],
+        ),
+        (
+            Action::DecreaseUiScale,
+            vec![
+                Binding::Key(KeyCode::Minus),
+                Binding::Key(KeyCode::NumpadSubtract),
+            ],
+        ),
+        // F3 already cycles the language
+        (Action::ToggleDiagnostics, vec![Binding::Key(KeyCode::F4)]),
+        (Action::FindMate, vec![Binding::Key(KeyCode::F5)]),
+        (Action::ToggleHeatmap, vec![Binding::Key(KeyCode::F6)]),
+        // The arrow keys already move the cursor
+        (
+            Action::PreviousMove,
+            vec![Binding::Key(KeyCode::Comma), Binding::ScrollUp],
+        ),
+        (
+            Action::NextMove,
+            vec![Binding::Key(KeyCode::Period), Binding::ScrollDown],
+        ),
+        (Action::FirstMove, vec![Binding::Key(KeyCode::Home)]),
+        (Action::LastMove, vec![Binding::Key(KeyCode::End)]),
+        (Action::PreviewLine, vec![Binding::Key(KeyCode::P)]),
+        (Action::RequestTakeback, vec![Binding::Key(KeyCode::Back)]),
+        (Action::AcceptTakeback, vec![Binding::Key(KeyCode::Y)]),
+        (Action::DeclineTakeback, vec![Binding::Key(KeyCode::N)]),
+        (Action::PlayFromHere, vec![Binding::Key(KeyCode::F7)]),
+        (Action::ToggleExploration, vec![Binding::Key(KeyCode::X)]),
+        (Action::NextVariation, vec![Binding::Key(KeyCode::RBracket)]),
+        (
+            Action::PreviousVariation,
+            vec![Binding::Key(KeyCode::LBracket)],
+        ),
+        (
+            Action::PromoteVariation,
+            vec![Binding::ShiftKey(KeyCode::RBracket)],
+        ),
+        (Action::DeleteVariation, vec![Binding::Key(KeyCode::Delete)]),
+        (Action::ToggleMovePanel, vec![Binding::Key(KeyCode::M)]),
+        (Action::ToggleCommentBox, vec![Binding::Key(KeyCode::C)]),
+        (
+            Action::ClearArrows,
+            vec![Binding::Mouse(MouseButton::Middle)],
+        ),
+        (Action::FitBoard, vec![Binding::Key(KeyCode::Key0)]),
+        (Action::ToggleIsometricView, vec![Binding::Key(KeyCode::F8)]),
+        (
+            Action::ToggleAnalysisWindow,
+            vec![Binding::Key(KeyCode::F9)],
+        ),
+        // 0 already fits the board
+        (Action::CallPawn, vec![Binding::Key(KeyCode::Key1)]),
+        (Action::CallKnight, vec![Binding::Key(KeyCode::Key2)]),
+        (Action::CallBishop, vec![Binding::Key(KeyCode::Key3)]),
+        (Action::CallRook, vec![Binding::Key(KeyCode::Key4)]),
+        (Action::CallQueen, vec![Binding::Key(KeyCode::Key5)]),
+        (Action::CallKing, vec![Binding::Key(KeyCode::Key6)]),
+        (Action::SavePuzzle, vec![Binding::Key(KeyCode::F10)]),
+        (Action::NextPuzzle, vec![Binding::Key(KeyCode::F11)]),
+        (Action::LeavePuzzles, vec![Binding::ShiftKey(KeyCode::F11)]),
+        (Action::NextMaze, vec![Binding::Key(KeyCode::Z)]),
+        (Action::LeaveMaze, vec![Binding::ShiftKey(KeyCode::Z)]),
+        (Action::SealMove, vec![Binding::Key(KeyCode::S)]),
+        (Action::ResumeLastGame, vec![Binding::Key(KeyCode::R)]),
+    ])
+}
+
+fn update_actions(
+    mut actions: ResMut<Actions>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: Query<&GlobalTransform, With<BoardRoot>>,
+    settings: Option<Res<Settings>>,
+    remapping: Option<Res<KeyRemapping>>,
+) {
+    actions.pressed.clear();
+    actions.released.clear();
+    let scroll: f32 = wheel_events.iter().map(|event| event.y).sum();
+
+    // The remapping screen reads the raw keys itself
+    let (Some(settings), None) = (settings, remapping) else {
+        return;
+    };
+
+    // Keys pressed in another window, like the analysis window, are its own
+    if window.get_single().is_ok_and(|window| !window.focused) {
+        return;
+    }
+
+    // The wheel is left alone anywhere but over the board, and zooms the
+    // camera with Ctrl held
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let over_board = scroll != 0.0
+        && !ctrl
+        && window
+            .get_single()
+            .ok()
+            .and_then(|window| get_cursor_square(window, &camera, &board_root))
+            .is_some_and(|square| square.is_on_board());
+    let scroll = if over_board { scroll } else { 0.0 };
+
+    for (action, bindings) in settings.key_bindings.iter() {
+        if bindings
+            .iter()
+            .any(|binding| binding.just_pressed(&keys, &buttons, scroll))
+        {
+            actions.pressed.push(*action);
+        }
+        if bindings
+            .iter()
+            .any(|binding| binding.just_released(&keys, &buttons))
+        {
+            actions.released.push(*action);
+        }
+    }
+}
+
+fn spawn_keyboard_cursor(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    // Hidden until the keyboard is used
+    let cursor = commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: settings.palette().cursor,
+                    custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                transform: Transform::from_xyz(0.0, 0.0, CURSOR_Z_INDEX),
+                ..default()
+            },
+            BoardPosition::new(0, 0),
+            KeyboardCursor,
+        ))
+        .id();
+
+    commands.entity(board_root).add_child(cursor);
+}
+
+fn handle_mouse_clicks(
+    actions: Res<Actions>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: Query<&GlobalTransform, With<BoardRoot>>,
+    mut cursor: Query<&mut Visibility, With<KeyboardCursor>>,
+    mut square_clicks: EventWriter<SquareClicked>,
+) {
+    if !actions.just_pressed(Action::Select) {
+        return;
+    }
+
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let Some(square) = get_cursor_square(window, &camera, &board_root) else {
+        return;
+    };
+
+    // The keyboard cursor only gets in the way of someone using the mouse
+    for mut visibility in cursor.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+
+    square_clicks.send(SquareClicked(square));
+}
+
+// Lifts the piece under the mouse when it can be moved, and shows the
+// hand cursor over it, before anything is clicked
+fn show_hovered_piece(
+    mut hovered_piece: ResMut<HoveredPiece>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: Query<&GlobalTransform, With<BoardRoot>>,
+    mut pieces: Query<(Entity, &BoardPosition, &Player, &mut Transform), With<Piece>>,
+    possible_moves: Res<PossibleMoves>,
+    current_turn: Res<CurrentTurn>,
+    settings: Res<Settings>,
+    forked: Option<Res<ForkedGame>>,
+    game_over: Option<Res<GameOver>>,
+    replay: Option<Res<ReplayPlayback>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+
+    let can_move = game_over.is_none()
+        && replay.is_none()
+        && get_engine_player(&settings, forked.as_deref()) != Some(current_turn.0);
+    let hovered = get_cursor_square(&window, &camera, &board_root)
+        .filter(|&square| can_move && !possible_moves.get(square).is_empty());
+
+    let mut over_piece = None;
+    for (entity, position, player, mut transform) in pieces.iter_mut() {
+        let lifted = hovered == Some(position.square()) && *player == current_turn.0;
+        if lifted {
+            over_piece = Some(entity);
+        }
+
+        // Only touched when it changes, so nothing redraws every frame
+        let scale = if lifted { HOVER_SCALE } else { 1.0 };
+        if transform.scale.x != scale {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+
+    let icon = if over_piece.is_some() {
+        CursorIcon::Hand
+    } else {
+        CursorIcon::Default
+    };
+    if window.cursor.icon != icon {
+        window.cursor.icon = icon;
+    }
+    if hovered_piece.0 != over_piece {
+        hovered_piece.0 = over_piece;
+    }
+}
+
+// The square under the mouse, which may be off the board
+fn get_cursor_square(
+    window: &Window,
+    camera: &Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: &Query<&GlobalTransform, With<BoardRoot>>,
+) -> Option<Square> {
+    get_cursor_position(window, camera, board_root).map(get_square_at)
+}
+
+// Where the mouse is in the board's own space, wherever it is drawn: from
+// the window into the camera's viewport, out into the world, and back
+// through the board's transform, which takes zoom, turns and the flipped
+// or isometric board into account
+fn get_cursor_position(
+    window: &Window,
+    camera: &Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: &Query<&GlobalTransform, With<BoardRoot>>,
+) -> Option<Vec2> {
+    let (camera, camera_transform) = camera.get_single().ok()?;
+    let board_transform = board_root.get_single().ok()?;
+
+    let ray = window
+        .cursor_position()
+        .and_then(|cursor| get_window_ray(camera, camera_transform, window, cursor))?;
+
+    get_board_point(ray, board_transform)
+}
+
+pub fn get_board_point(ray: Ray, board_transform: &GlobalTransform) -> Option<Vec2> {
+    let to_board = board_transform.compute_matrix().inverse();
+    let origin = to_board.transform_point3(ray.origin);
+    let direction = to_board.transform_vector3(ray.direction);
+
+    // Where the ray meets the board's surface, straight below the mouse
+    // when looking down at it in 2D and anywhere at an angle in 3D
+    if direction.z.abs() < f32::EPSILON {
+        return None;
+    }
+    let distance = -origin.z / direction.z;
+    (distance >= 0.0).then(|| (origin + direction * distance).truncate())
+}
+
+// Picks up the piece just selected with the mouse, if it is still under it
+fn start_piece_drag(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut selection: ResMut<Selection>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: Query<&GlobalTransform, With<BoardRoot>>,
+    board_entity: Query<Entity, With<BoardRoot>>,
+    mut pieces: Query<(
+        &BoardPosition,
+        &TextureAtlasSprite,
+        &Handle<TextureAtlas>,
+        &mut Transform,
+    )>,
+) {
+    if !actions.just_pressed(Action::Select) {
+        return;
+    }
+    let Selection::PieceSelected { entity, moves } = &*selection else {
+        return;
+    };
+    let (entity, moves) = (*entity, moves.clone());
+    let Ok(board_entity) = board_entity.get_single() else {
+        return;
+    };
+    let Ok((position, sprite, atlas, mut transform)) = pieces.get_mut(entity) else {
+        return;
+    };
+
+    let from = position.square();
+    let cursor_square = window
+        .get_single()
+        .ok()
+        .and_then(|window| get_cursor_square(window, &camera, &board_root));
+    if cursor_square != Some(from) {
+        return;
+    }
+
+    transform.translation.z = DRAG_Z_INDEX;
+
+    let ghost = commands
+        .spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    color: sprite.color.with_a(DRAG_GHOST_ALPHA),
+                    ..sprite.clone()
+                },
+                texture_atlas: atlas.clone(),
+                transform: Transform::from_xyz(
+                    (from.0 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                    (from.1 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                    PIECE_Z_INDEX,
+                ),
+                ..default()
+            },
+            DragGhost,
+        ))
+        .id();
+    commands.entity(board_entity).add_child(ghost);
+    commands.entity(entity).remove::<SnapBack>();
+    *selection = Selection::Dragging { entity, moves };
+}
+
+fn move_dragged_piece(
+    selection: Res<Selection>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: Query<&GlobalTransform, With<BoardRoot>>,
+    mut pieces: Query<&mut Transform>,
+) {
+    let Some(mut transform) = selection
+        .dragged()
+        .and_then(|entity| pieces.get_mut(entity).ok())
+    else {
+        return;
+    };
+    let Some(position) = window
+        .get_single()
+        .ok()
+        .and_then(|window| get_cursor_position(window, &camera, &board_root))
+    else {
+        return;
+    };
+
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+}
+
+// Letting go over a square the piece can go to plays the move as a second
+// click would. Anywhere else, it slides back and stays selected for clicking
+fn drop_dragged_piece(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut selection: ResMut<Selection>,
+    // The piece being dragged as of the last frame
+    mut carried: Local<Option<Entity>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    board_root: Query<&GlobalTransform, With<BoardRoot>>,
+    mut pieces: Query<&mut Transform>,
+    ghosts: Query<Entity, With<DragGhost>>,
+    mut square_clicks: EventWriter<SquareClicked>,
+) {
+    // Dropped along with the selection, e.g. by Escape
+    if let Some(entity) = carried.filter(|&entity| selection.dragged() != Some(entity)) {
+        if let Ok(transform) = pieces.get(entity) {
+            snap_back(&mut commands, entity, transform);
+        }
+        for ghost in ghosts.iter() {
+            commands.entity(ghost).despawn_recursive();
+        }
+    }
+
+    let Selection::Dragging { entity, moves } = &*selection else {
+        *carried = None;
+        return;
+    };
+    let (entity, moves) = (*entity, moves.clone());
+    *carried = Some(entity);
+
+    if !actions.just_released(Action::Select) {
+        return;
+    }
+    *carried = None;
+
+    let target = window
+        .get_single()
+        .ok()
+        .and_then(|window| get_cursor_square(window, &camera, &board_root))
+        .filter(|target| moves.contains(target));
+
+    for ghost in ghosts.iter() {
+        commands.entity(ghost).despawn_recursive();
+    }
+
+    if let Some(target) = target {
+        if let Ok(mut transform) = pieces.get_mut(entity) {
+            transform.translation.z = PIECE_Z_INDEX;
+        }
+        square_clicks.send(SquareClicked(target));
+    } else if let Ok(transform) = pieces.get(entity) {
+        snap_back(&mut commands, entity, transform);
+    }
+
+    *selection = Selection::PieceSelected { entity, moves };
+}
+
+fn snap_back(commands: &mut Commands, entity: Entity, transform: &Transform) {
+    commands.entity(entity).insert(SnapBack {
+        from: transform.translation.truncate(),
+        timer: Timer::from_seconds(SNAP_BACK_SECONDS, TimerMode::Once),
+    });
+}
+
+fn snap_back_pieces(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut pieces: Query<(Entity, &BoardPosition, &mut Transform, &mut SnapBack)>,
+) {
+    for (entity, position, mut transform, mut snap_back) in pieces.iter_mut() {
+        let delta = if settings.reduced_motion {
+            snap_back.timer.duration()
+        } else {
+            time.delta()
+        };
+        snap_back.timer.tick(delta);
+
+        let to = Vec2::new(
+            (position.x * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+            (position.y * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+        );
+        let progress = snap_back.timer.percent();
+        transform.translation = snap_back.from.lerp(to, progress).extend(DRAG_Z_INDEX);
+
+        if snap_back.timer.finished() {
+            transform.translation.z = PIECE_Z_INDEX;
+            commands.entity(entity).remove::<SnapBack>();
+        }
+    }
+}
+
+fn handle_keyboard_cursor(
+    actions: Res<Actions>,
+    mut cursor: Query<(&mut BoardPosition, &mut Visibility), With<KeyboardCursor>>,
+    pieces: Query<(Entity, &BoardPosition, &Player), (With<Piece>, Without<KeyboardCursor>)>,
+    possible_moves: Res<PossibleMoves>,
+    current_turn: Res<CurrentTurn>,
+    mut selection: ResMut<Selection>,
+    mut square_clicks: EventWriter<SquareClicked>,
+) {
+    let Ok((mut cursor_position, mut cursor_visibility)) = cursor.get_single_mut() else {
+        return;
+    };
+
+    let (dx, dy) = if actions.just_pressed(Action::CursorLeft) {
+        (-1, 0)
+    } else if actions.just_pressed(Action::CursorRight) {
+        (1, 0)
+    } else if actions.just_pressed(Action::CursorUp) {
+        (0, 1)
+    } else if actions.just_pressed(Action::CursorDown) {
+        (0, -1)
+    } else {
+        (0, 0)
+    };
+
+    if (dx, dy) != (0, 0) {
+        cursor_position.x = (cursor_position.x + dx).clamp(0, BOARD_SIZE - 1);
+        cursor_position.y = (cursor_position.y + dy).clamp(0, BOARD_SIZE - 1);
+        *cursor_visibility = Visibility::Visible;
+    }
+
+    let backwards = actions.just_pressed(Action::PreviousPiece);
+
+    if actions.just_pressed(Action::NextPiece) || backwards {
+        // Reading order, from the top left of the board
+        let mut movable_pieces: Vec<(Entity, BoardPosition, Vec<Square>)> = pieces
+            .iter()
+            .filter(|(_, _, player)| **player == current_turn.0)
+            .map(|(entity, position, _)| {
+                let moves = possible_moves.get(position.square()).to_vec();
+                (entity, *position, moves)
+            })
+            .filter(|(_, _, moves)| !moves.is_empty())
+            .collect();
+        movable_pieces.sort_by_key(|(_, position, _)| (-position.y, position.x));
+
+        let current = movable_pieces
+            .iter()
+            .position(|(_, position, _)| position == &*cursor_position);
+
+        let next = match (current, backwards) {
+            (Some(i), false) => Some((i + 1) % movable_pieces.len()),
+            (Some(i), true) => Some((i + movable_pieces.len() - 1) % movable_pieces.len()),
+            (None, false) => movable_pieces
+                .iter()
+                .position(|(_, position, _)| {
+                    (-position.y, position.x) > (-cursor_position.y, cursor_position.x)
+                })
+                .or((!movable_pieces.is_empty()).then_some(0)),
+            (None, true) => movable_pieces
+                .iter()
+                .rposition(|(_, position, _)| {
+                    (-position.y, position.x) < (-cursor_position.y, cursor_position.x)
+                })
+                .or(movable_pieces.len().checked_sub(1)),
+        };
+
+        if let Some((entity, position, moves)) = next.map(|i| movable_pieces.swap_remove(i)) {
+            *cursor_position = position;
+            *cursor_visibility = Visibility::Visible;
+            *selection = Selection::PieceSelected { entity, moves };
+        }
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        // Enter plays a staged move wherever the cursor is
+        if let Selection::MoveStaged { mv, .. } = &*selection {
+            square_clicks.send(SquareClicked(mv.to));
+        } else {
+            *cursor_visibility = Visibility::Visible;
+            square_clicks.send(SquareClicked(cursor_position.square()));
+        }
+    }
+
+    if actions.just_pressed(Action::ClearSelection) {
+        *selection = Selection::Idle;
+    }
+}
+
+fn handle_square_clicks(
+    mut square_clicks: EventReader<SquareClicked>,
+    pieces: Query<(Entity, &Piece, &BoardPosition, &Player)>,
+    board: Res<Board>,
+    possible_moves: Res<PossibleMoves>,
+    current_turn: Res<CurrentTurn>,
+    settings: Res<Settings>,
+    forked: Option<Res<ForkedGame>>,
+    mut selection: ResMut<Selection>,
+    mut move_events: EventWriter<MoveEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for SquareClicked(target) in square_clicks.iter() {
+        let _span = debug_span!("square_clicked", square = %target).entered();
+
+        // The engine's pieces are its own to move
+        if get_engine_player(&settings, forked.as_deref()) == Some(current_turn.0) {
+            continue;
+        }
+
+        // Anywhere else drops the pawn's move and picks afresh
+        if let Selection::AwaitingPromotion { mv, .. } = &*selection {
+            let choice = get_promotion_squares(mv.to)
+                .iter()
+                .position(|square| square == target);
+            if let Some(choice) = choice {
+                let mv = Move {
+                    promotion: Some(PROMOTION_PIECES[choice]),
+                    ..*mv
+                };
+                debug!("promoting the pawn on {}", mv.from);
+                move_events.send(MoveEvent(mv));
+                continue;
+            }
+        }
+
+        // Anywhere else drops the staged move and picks afresh
+        if let Selection::MoveStaged { mv, .. } = &*selection {
+            if mv.to == *target {
+                debug!("confirmed the move from {}", mv.from);
+                move_events.send(MoveEvent(*mv));
+                continue;
+            }
+        }
+
+        if let Selection::PieceSelected { entity, moves } | Selection::Dragging { entity, moves } =
+            &*selection
+        {
+            if let Ok((_, piece_type, selected_position, player)) = pieces.get(*entity) {
+                if moves.contains(target) {
+                    let mv = Move {
+                        from: selected_position.square(),
+                        to: *target,
+                        promotion: None,
+                    };
+
+                    // Picking what the pawn becomes confirms the move too
+                    if is_promotion(*piece_type, *target) {
+                        debug!("awaiting the promotion of the pawn on {}", mv.from);
+                        *selection = Selection::AwaitingPromotion {
+                            entity: *entity,
+                            mv,
+                        };
+                        toasts.send(Toast::new("toast-pick-promotion"));
+                    } else if settings.confirm_moves {
+                        debug!("staged the move from {}", mv.from);
+                        *selection = Selection::MoveStaged {
+                            entity: *entity,
+                            mv,
+                        };
+                        toasts.send(Toast::new("toast-confirm-move"));
+                    } else {
+                        debug!("moving the piece on {}", mv.from);
+                        move_events.send(MoveEvent(mv));
+                    }
+                    continue;
+                }
+
+                // The piece could go there, were it not for the king
+                if get_possible_moves(piece_type, selected_position, player, &board)
+                    .contains(target)
+                {
+                    toasts.send(Toast::new("toast-king-in-check"));
+                    continue;
+                }
+            }
+        }
+
+        *selection = pieces
+            .iter()
+            .find(|(_, _, position, player)| {
+                **player == current_turn.0 && position.square() == *target
+            })
+            .map_or(Selection::Idle, |(entity, _, position, _)| {
+                Selection::PieceSelected {
+                    entity,
+                    moves: possible_moves.get(position.square()).to_vec(),
+                }
+            });
+        debug!("selected {:?}", selection.moves());
+    }
+}
+
+fn show_staged_move(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    game_assets: Option<Res<GameAssets>>,
+    pieces: Query<(&Piece, &Player)>,
+    ghosts: Query<Entity, With<StagedMoveGhost>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Selection::MoveStaged { entity, mv } = &*selection else {
+        return;
+    };
+    let (Some(game_assets), Ok(board_root)) = (game_assets, board_root.get_single()) else {
+        return;
+    };
+    let Ok((piece_type, player)) = pieces.get(*entity) else {
+        return;
+    };
+
+    let ghost = commands
+        .spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                    index: get_atlas_index(&game_assets, *piece_type, *player),
+                    color: Color::WHITE.with_a(0.5),
+                    ..default()
+                },
+                texture_atlas: game_assets.piece_atlas.clone(),
+                transform: Transform::from_xyz(
+                    (mv.to.0 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                    (mv.to.1 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                    GHOST_Z_INDEX,
+                ),
+                ..default()
+            },
+            StagedMoveGhost,
+        ))
+        .id();
+    commands.entity(board_root).add_child(ghost);
+}
+
+fn show_promotion_choices(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    game_assets: Option<Res<GameAssets>>,
+    pieces: Query<&Player>,
+    choices: Query<Entity, With<PromotionChoice>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    for entity in choices.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Selection::AwaitingPromotion { entity, mv } = &*selection else {
+        return;
+    };
+    let (Some(game_assets), Ok(board_root)) = (game_assets, board_root.get_single()) else {
+        return;
+    };
+    let Ok(player) = pieces.get(*entity) else {
+        return;
+    };
+
+    for (piece_type, square) in PROMOTION_PIECES
+        .into_iter()
+        .zip(get_promotion_squares(mv.to))
+    {
+        // Over whatever stands on the square, so the choices read clearly
+        let choice = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: PROMOTION_BACKDROP_COLOR,
+                        custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(
+                        (square.0 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                        (square.1 * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+                        PROMOTION_Z_INDEX,
+                    ),
+                    ..default()
+                },
+                PromotionChoice,
+            ))
+            .with_children(|parent| {
+                parent.spawn(SpriteSheetBundle {
+                    sprite: TextureAtlasSprite {
+                        custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                        index: get_atlas_index(&game_assets, piece_type, *player),
+                        ..default()
+                    },
+                    texture_atlas: game_assets.piece_atlas.clone(),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                    ..default()
+                });
+            })
+            .id();
+        commands.entity(board_root).add_child(choice);
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use fluent::{concurrent::FluentBundle, FluentArgs, FluentResource};
+
+use crate::{
+    input::{Action, Actions},
+    settings::Settings,
+};
+
+const DEFAULT_LANGUAGE: &str = "en-US";
+// Built in, so the browser build has them too
+const LOCALES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(cycle_language)
+            .add_system(apply_language.after(cycle_language));
+    }
+}
+
+#[derive(Resource)]
+pub struct Localizer {
+    pub language: &'static str,
+    pub bundle: FluentBundle<FluentResource>,
+    // Messages missing from a translation are shown in English
+    pub fallback: FluentBundle<FluentResource>,
+}
+
+impl Localizer {
+    pub fn new(requested: Option<&str>) -> Self {
+        let language = get_language(requested);
+
+        Self {
+            language,
+            bundle: get_locale_bundle(language),
+            fallback: get_locale_bundle(DEFAULT_LANGUAGE),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, &FluentArgs::new())
+    }
+
+    pub fn format(&self, id: &str, args: &FluentArgs) -> String {
+        for bundle in [&self.bundle, &self.fallback] {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = Vec::new();
+                let text = bundle.format_pattern(pattern, Some(args), &mut errors);
+
+                if !errors.is_empty() {
+                    warn!("could not format message {id}: {errors:?}");
+                }
+
+                return text.into_owned();
+            }
+        }
+
+        warn!("missing message {id}");
+        id.to_string()
+    }
+}
+
+fn cycle_language(
+    actions: Res<Actions>,
+    mut settings: ResMut<Settings>,
+    localizer: Res<Localizer>,
+) {
+    if !actions.just_pressed(Action::CycleLanguage) {
+        return;
+    }
+
+    let current = LOCALES
+        .iter()
+        .position(|(language, _)| *language == localizer.language)
+        .unwrap_or_default();
+    settings.language = Some(LOCALES[(current + 1) % LOCALES.len()].0.to_string());
+}
+
+fn apply_language(settings: Res<Settings>, mut localizer: ResMut<Localizer>) {
+    if !settings.is_changed() || get_language(settings.language.as_deref()) == localizer.language {
+        return;
+    }
+
+    *localizer = Localizer::new(settings.language.as_deref());
+}
+
+fn get_language(requested: Option<&str>) -> &'static str {
+    let requested = requested
+        .map(str::to_string)
+        .or_else(sys_locale::get_locale)
+        .unwrap_or_default();
+
+    // Match on the language alone, so de-AT still gets German
+    let primary = |language: &str| {
+        language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+
+    LOCALES
+        .iter()
+        .map(|(language, _)| *language)
+        .find(|language| primary(language) == primary(&requested))
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+fn get_locale_bundle(language: &'static str) -> FluentBundle<FluentResource> {
+    let (_, source) = LOCALES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .expect("language comes from LOCALES");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![language.parse().unwrap_or_default()]);
+    // Isolation marks end up verbatim in sprites and screen reader output
+    bundle.set_use_isolating(false);
+
+    let resource =
+        FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+            warn!("errors in the {language} translation: {errors:?}");
+            resource
+        });
+    if let Err(errors) = bundle.add_resource(resource) {
+        warn!("errors in the {language} translation: {errors:?}");
+    }
+
+    bundle
+}
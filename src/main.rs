@@ -1,855 +1,336 @@
-use bevy::{prelude::*, utils::HashMap, window::PrimaryWindow};
-
-const PIECE_SIZE: i32 = 60;
-const BOARD_SIZE: i32 = 8;
-const TILE_Z_INDEX: f32 = 0.0;
-const PIECE_Z_INDEX: f32 = 1.0;
-const GUIDE_Z_INDEX: f32 = 2.0;
-
-#[derive(Component, PartialEq, Eq, Hash)]
-enum Piece {
-    King,
-    Queen,
-    Knight,
-    Pawn,
-    Bishop,
-    Rook,
+// Bevy systems take their data as parameters, so long signatures are normal
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod accessibility;
+mod adjourn;
+mod analysis;
+mod analysis_window;
+mod arrows;
+mod board;
+mod board_3d;
+mod camera;
+mod comment_box;
+mod diagnostics;
+mod explore;
+mod export;
+mod fair_play;
+mod hand_and_brain;
+mod headless;
+mod heatmap;
+mod hud;
+mod input;
+mod locale;
+mod mate;
+mod maze;
+mod mini_board;
+mod move_panel;
+mod notifications;
+mod opponent;
+mod outline;
+mod pieces;
+mod positions;
+mod preview;
+mod puzzles;
+mod repertoire;
+mod rules;
+mod save;
+mod settings;
+#[cfg(feature = "speech")]
+mod speech;
+mod sprt;
+mod stats;
+mod study;
+mod takeback;
+#[cfg(test)]
+mod tests;
+mod toast;
+mod ui;
+mod ui_theme;
+mod variations;
+mod view;
+
+use bevy::{prelude::*, window::ExitCondition};
+use chess_core::BOARD_SIZE;
+
+use crate::{
+    accessibility::ScreenReaderPlugin,
+    adjourn::AdjournPlugin,
+    analysis::AnalysisPlugin,
+    analysis_window::AnalysisWindowPlugin,
+    arrows::CandidateArrowsPlugin,
+    board::{BoardPlugin, PIECE_SIZE},
+    board_3d::Board3dPlugin,
+    camera::{CameraPlugin, GameCamera},
+    comment_box::CommentBoxPlugin,
+    diagnostics::DiagnosticsOverlayPlugin,
+    explore::ExplorationPlugin,
+    export::ExportPlugin,
+    fair_play::{get_fair_play_path_from_args, print_fair_play_report},
+    hand_and_brain::HandAndBrainPlugin,
+    headless::{run_headless_match, HeadlessMatch},
+    heatmap::HeatmapPlugin,
+    hud::HudPlugin,
+    input::InputPlugin,
+    locale::LocalizationPlugin,
+    mate::MateSearchPlugin,
+    maze::MazePlugin,
+    mini_board::MiniBoardPlugin,
+    move_panel::MovePanelPlugin,
+    notifications::NotificationsPlugin,
+    opponent::EngineOpponentPlugin,
+    outline::PieceOutlinePlugin,
+    pieces::PiecesPlugin,
+    positions::{print_random_positions, RandomPositions},
+    preview::LinePreviewPlugin,
+    puzzles::PuzzlesPlugin,
+    repertoire::RepertoirePlugin,
+    rules::RulesPlugin,
+    save::{get_replay_path_from_args, GameSnapshot, ReplayPlayback, SavePlugin},
+    settings::SettingsPlugin,
+    stats::{export_stats, get_stats_path_from_args},
+    study::{StudyPlugin, StudySource},
+    takeback::TakebackPlugin,
+    toast::ToastPlugin,
+    ui::UiPlugin,
+    ui_theme::UiThemePlugin,
+    variations::VariationsPlugin,
+    view::BoardViewPlugin,
+};
+
+// Game logic that counts time runs on FixedUpdate at this rate, so it
+// behaves the same whatever the frame rate
+const GAME_TICK_SECONDS: f32 = 1.0 / 60.0;
+
+// The board is all greens, so keying on green would punch holes in it
+const CHROMA_KEY_COLOR: Color = Color::FUCHSIA;
+
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    // Waiting for the piece images and font
+    #[default]
+    Loading,
+    Playing,
 }
 
-#[derive(Component)]
-struct Tile;
-
-#[derive(Component)]
-struct Guide;
-
-#[derive(Component, PartialEq, Eq)]
-struct BoardPosition {
-    x: i32,
-    y: i32,
+// The steps a move goes through within a frame
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    // Turning clicks and keys into picked squares
+    Input,
+    // Checking picked squares against the rules and sending moves
+    Rules,
+    Apply,
+    // Bringing sprites, highlights and announcements up to date
+    Render,
 }
 
-impl BoardPosition {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+// The game state, tick rate and order of the sets, shared with the tests
+struct GameSetsPlugin;
+
+impl Plugin for GameSetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .insert_resource(FixedTime::new_from_secs(GAME_TICK_SECONDS))
+            .configure_sets(
+                (
+                    GameSet::Input.run_if(in_state(GameState::Playing)),
+                    GameSet::Rules.run_if(in_state(GameState::Playing)),
+                    GameSet::Apply.run_if(in_state(GameState::Playing)),
+                    GameSet::Render,
+                )
+                    .chain(),
+            );
     }
 }
 
-#[derive(Component, PartialEq, Eq)]
-enum Player {
-    White,
-    Black,
-}
-
-#[derive(Resource)]
-struct GameAssets {
-    piece_atlas: Handle<TextureAtlas>,
-    pieces: HashMap<Piece, usize>,
-}
-
-#[derive(Resource)]
-struct BoardPopulationDone(bool);
-
-#[derive(Resource)]
-struct CurrentTurn(Player);
-
-#[derive(Resource)]
-struct SelectedPiece(Option<Entity>);
-
-fn main() {
-    App::new()
-        .insert_resource(BoardPopulationDone(false))
-        .insert_resource(CurrentTurn(Player::White))
-        .insert_resource(SelectedPiece(None))
-        .add_plugins(
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    resolution: (
-                        (PIECE_SIZE * BOARD_SIZE) as f32,
-                        (PIECE_SIZE * BOARD_SIZE) as f32,
-                    )
-                        .into(),
-                    title: "Chess".to_string(),
-                    resizable: true,
-                    ..default()
-                }),
-                ..default()
-            }),
-        )
-        .add_startup_system(load_assets)
-        .add_startup_system(spawn_camera)
-        .add_startup_system(generate_board)
-        .add_system(populate_board)
-        .add_system(update_pieces_positions)
-        .add_system(handle_piece_movement)
-        .add_system(handle_piece_selection)
-        .add_system(display_possible_piece_movements)
-        .run();
-}
-
-fn load_assets(
-    mut commands: Commands,
-    assets: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-) {
-    let piece_atlas = TextureAtlas::from_grid(
-        assets.load("pieces.png"),
-        Vec2::splat(PIECE_SIZE as f32),
-        6,
-        2,
-        None,
-        None,
-    );
-
-    commands.insert_resource(GameAssets {
-        piece_atlas: texture_atlases.add(piece_atlas),
-        pieces: HashMap::from([
-            (Piece::King, 0),
-            (Piece::Queen, 1),
-            (Piece::Knight, 2),
-            (Piece::Pawn, 3),
-            (Piece::Bishop, 4),
-            (Piece::Rook, 5),
-        ]),
-    });
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+enum OverlayMode {
+    Off,
+    Transparent,
+    ChromaKey,
 }
 
-fn spawn_camera(mut commands: Commands, window: Query<&Window, With<PrimaryWindow>>) {
-    let window = window.get_single().unwrap();
-
-    commands.spawn(Camera2dBundle {
-        transform: Transform::from_xyz(window.width() / 2.0, window.height() / 2.0, 999.0),
-        ..default()
-    });
-}
+impl OverlayMode {
+    fn from_args() -> Self {
+        let mut mode = OverlayMode::Off;
 
-fn generate_board(mut commands: Commands) {
-    let board = commands
-        .spawn((TransformBundle::default(), VisibilityBundle::default()))
-        .id();
-
-    let guide_board = commands
-        .spawn((TransformBundle::default(), VisibilityBundle::default()))
-        .id();
-
-    for x in 0..BOARD_SIZE {
-        for y in 0..BOARD_SIZE {
-            let tile = commands
-                .spawn((
-                    SpriteBundle {
-                        sprite: Sprite {
-                            color: get_tile_color(x, y),
-                            custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
-                            ..default()
-                        },
-                        transform: Transform::from_xyz(0.0, 0.0, TILE_Z_INDEX),
-                        ..default()
-                    },
-                    BoardPosition::new(x, y),
-                    Tile,
-                ))
-                .id();
-
-            let guide = commands
-                .spawn((
-                    SpriteBundle {
-                        sprite: Sprite {
-                            color: Color::GRAY,
-                            custom_size: Some(Vec2::splat(10.0)),
-                            ..default()
-                        },
-                        visibility: Visibility::Hidden,
-                        transform: Transform::from_xyz(0.0, 0.0, GUIDE_Z_INDEX),
-                        ..default()
-                    },
-                    BoardPosition::new(x, y),
-                    Guide,
-                ))
-                .id();
-
-            commands.entity(board).add_child(tile);
-            commands.entity(guide_board).add_child(guide);
+        for arg in std::env::args().skip(1) {
+            match arg.as_str() {
+                "--overlay" => mode = OverlayMode::Transparent,
+                "--chroma-key" => mode = OverlayMode::ChromaKey,
+                _ => {}
+            }
         }
-    }
-}
 
-fn populate_board(
-    mut commands: Commands,
-    mut population_done: ResMut<BoardPopulationDone>,
-    game_assets: Res<GameAssets>,
-) {
-    if !population_done.0 {
-        spawn_white_pieces(&game_assets, &mut commands);
-        spawn_black_pieces(&game_assets, &mut commands);
-        population_done.0 = true;
+        mode
     }
 }
 
-fn update_pieces_positions(mut pieces: Query<(&mut Transform, &BoardPosition)>) {
-    for (mut transform, position) in pieces.iter_mut() {
-        transform.translation.x = (position.x * PIECE_SIZE + (PIECE_SIZE / 2)) as f32;
-        transform.translation.y = (position.y * PIECE_SIZE + (PIECE_SIZE / 2)) as f32;
+fn main() {
+    if let Some(headless_match) = HeadlessMatch::from_args() {
+        run_headless_match(headless_match);
+        return;
     }
-}
-
-fn handle_piece_selection(
-    buttons: Res<Input<MouseButton>>,
-    window: Query<&Window, With<PrimaryWindow>>,
-    camera: Query<(&Camera, &GlobalTransform)>,
-    pieces: Query<(Entity, &BoardPosition, &Player), With<Piece>>,
-    mut tiles: Query<(&BoardPosition, &mut Sprite), With<Tile>>,
-    current_player: Res<CurrentTurn>,
-    mut selected_piece: ResMut<SelectedPiece>,
-) {
-    let window = window.get_single().unwrap();
-    let (camera, camera_transform) = camera.get_single().unwrap();
-
-    let mut selected_piece_board_position = None;
-
-    if buttons.just_pressed(MouseButton::Left) {
-        if let Some(world_position) = window
-            .cursor_position()
-            .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-            .map(|ray| ray.origin.truncate())
-        {
-            for (entity, position, player) in pieces.iter() {
-                if player == &current_player.0
-                    && position.x == to_board_posistion(world_position.x)
-                    && position.y == to_board_posistion(world_position.y)
-                {
-                    selected_piece.0 = Some(entity);
-                    selected_piece_board_position = Some(position);
-                    break;
-                } else {
-                    selected_piece.0 = None;
-                }
-            }
 
-            for (tile_pos, mut tile_sprite) in tiles.iter_mut() {
-                if let Some(selected_piece_board_position) = selected_piece_board_position {
-                    if tile_pos.x == selected_piece_board_position.x
-                        && tile_pos.y == selected_piece_board_position.y
-                    {
-                        tile_sprite.color = Color::YELLOW;
-                    } else {
-                        tile_sprite.color = get_tile_color(tile_pos.x, tile_pos.y);
-                    }
-                } else {
-                    tile_sprite.color = get_tile_color(tile_pos.x, tile_pos.y);
-                }
-            }
-        }
+    if let Some(random_positions) = RandomPositions::from_args() {
+        print_random_positions(random_positions);
+        return;
     }
-}
 
-fn display_possible_piece_movements(
-    selected_piece: Res<SelectedPiece>,
-    pieces: Query<(&BoardPosition, &Player, &Piece)>,
-    mut guides: Query<(&BoardPosition, &mut Visibility), With<Guide>>,
-) {
-    if let Some(selected_piece_ent) = selected_piece.0 {
-        let mut white_pieces_positions = Vec::new();
-        let mut black_pieces_positions = Vec::new();
-
-        for (piece_board_position, piece_player, _) in pieces.iter() {
-            match piece_player {
-                &Player::White => {
-                    white_pieces_positions.push(piece_board_position);
-                }
-                &Player::Black => {
-                    black_pieces_positions.push(piece_board_position);
-                }
-            }
-        }
+    if let Some(path) = get_fair_play_path_from_args() {
+        print_fair_play_report(path);
+        return;
+    }
 
-        let (selected_piece_position, selected_piece_player, selected_piece_type) =
-            pieces.get(selected_piece_ent).unwrap();
+    if let Some(path) = get_stats_path_from_args() {
+        export_stats(path);
+        return;
+    }
 
-        let possible_moves = get_possible_moves(
-            selected_piece_type,
-            selected_piece_position,
-            selected_piece_player,
-            white_pieces_positions,
-            black_pieces_positions,
-        );
+    let overlay_mode = OverlayMode::from_args();
+    let resume = std::env::args().any(|arg| arg == "--resume");
+    let replay = get_replay_path_from_args().map(|path| {
+        GameSnapshot::read(&path).unwrap_or_else(|err| {
+            eprintln!("could not read replay {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    });
 
-        for (guide_position, mut guide_visibility) in guides.iter_mut() {
-            *guide_visibility = Visibility::Hidden;
+    let mut app = App::new();
 
-            for possible_move in possible_moves.iter() {
-                if possible_move.0 == guide_position.x && possible_move.1 == guide_position.y {
-                    *guide_visibility = Visibility::Visible;
-                }
-            }
-        }
-    } else {
-        for (_, mut guide_visibility) in guides.iter_mut() {
-            *guide_visibility = Visibility::Hidden;
-        }
+    if let Some(snapshot) = replay {
+        app.insert_resource(ReplayPlayback {
+            moves: snapshot.history,
+            times: snapshot.move_times,
+            next: 0,
+        });
     }
-}
-
-fn handle_piece_movement(
-    buttons: Res<Input<MouseButton>>,
-    window: Query<&Window, With<PrimaryWindow>>,
-    camera: Query<(&Camera, &GlobalTransform)>,
-    pieces: Query<(&BoardPosition, &Player, &Piece)>,
-    mut selected_piece: ResMut<SelectedPiece>,
-) {
-    if let Some(selected_piece_ent) = selected_piece.0 {
-        let (selected_piece_position, selected_piece_player, selected_piece_type) =
-            pieces.get(selected_piece_ent).unwrap();
-
-        let mut white_pieces_positions = Vec::new();
-        let mut black_pieces_positions = Vec::new();
-
-        for (piece_board_position, piece_player, _) in pieces.iter() {
-            match piece_player {
-                &Player::White => {
-                    white_pieces_positions.push(piece_board_position);
-                }
-                &Player::Black => {
-                    black_pieces_positions.push(piece_board_position);
-                }
-            }
-        }
 
-        let possible_moves = get_possible_moves(
-            selected_piece_type,
-            selected_piece_position,
-            selected_piece_player,
-            white_pieces_positions,
-            black_pieces_positions,
-        );
-
-        let window = window.get_single().unwrap();
-        let (camera, camera_transform) = camera.get_single().unwrap();
-
-        if buttons.just_pressed(MouseButton::Left) {
-            if let Some(world_position) = window
-                .cursor_position()
-                .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-                .map(|ray| ray.origin.truncate())
-            {
-                for (piece_position, piece_player, _) in pieces.iter() {
-                    if piece_player == selected_piece_player
-                        && piece_position.x == to_board_posistion(world_position.x)
-                        && piece_position.y == to_board_posistion(world_position.y)
-                    {
-                        return;
-                    }
-
-                    if !possible_moves.contains(&(
-                        to_board_posistion(world_position.x),
-                        to_board_posistion(world_position.y),
-                    )) {
-                        return;
-                    }
-
-                    for possible_move in possible_moves.iter() {
-                        if possible_move.0 == to_board_posistion(world_position.x)
-                            && possible_move.1 == to_board_posistion(world_position.y)
-                        {
-                            println!("moved");
-                            // TODO: somehow mutate these values
-                            // selected_piece_position.x = possible_move.0;
-                            // selected_piece_position.y = possible_move.1;
-                            selected_piece.0 = None;
-                        }
-                    }
-                }
-            }
-        }
+    // Moves and clicks are logged in spans, which RUST_LOG can turn up,
+    // e.g. RUST_LOG=chess=debug,chess_core=trace
+    app.insert_resource(overlay_mode)
+        .insert_resource(get_clear_color(overlay_mode))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(get_primary_window(overlay_mode)),
+                    // Not kept open by the analysis window alone
+                    exit_condition: ExitCondition::OnPrimaryClosed,
+                    ..default()
+                })
+                .set(AssetPlugin {
+                    watch_for_changes: cfg!(feature = "hot-reload"),
+                    ..default()
+                }),
+        )
+        .add_plugin(GameSetsPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(LocalizationPlugin)
+        .add_plugin(BoardPlugin)
+        .add_plugin(PiecesPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(RulesPlugin)
+        .add_plugin(SavePlugin { resume })
+        .add_plugin(AdjournPlugin { resume })
+        .add_plugin(UiPlugin)
+        .add_plugin(UiThemePlugin)
+        .add_plugin(ToastPlugin)
+        .add_plugin(ExportPlugin)
+        .add_plugin(ScreenReaderPlugin)
+        .add_plugin(DiagnosticsOverlayPlugin)
+        .add_plugin(MateSearchPlugin)
+        .add_plugin(HeatmapPlugin)
+        .add_plugin(AnalysisPlugin)
+        .add_plugin(AnalysisWindowPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(LinePreviewPlugin)
+        .add_plugin(CandidateArrowsPlugin)
+        .add_plugin(TakebackPlugin)
+        .add_plugin(HandAndBrainPlugin)
+        .add_plugin(PuzzlesPlugin)
+        .add_plugin(RepertoirePlugin)
+        .add_plugin(MazePlugin)
+        .add_plugin(EngineOpponentPlugin)
+        .add_plugin(NotificationsPlugin)
+        .add_plugin(ExplorationPlugin)
+        .add_plugin(VariationsPlugin)
+        .add_plugin(MovePanelPlugin)
+        .add_plugin(MiniBoardPlugin)
+        .add_plugin(CommentBoxPlugin)
+        .add_system(hide_ui_in_overlay);
+
+    // The 3D board brings a camera of its own
+    if std::env::args().any(|arg| arg == "--3d") {
+        app.add_plugin(Board3dPlugin);
+    } else {
+        app.add_plugin(CameraPlugin)
+            .add_plugin(BoardViewPlugin)
+            .add_plugin(PieceOutlinePlugin);
     }
-}
 
-fn get_tile_color(x: i32, y: i32) -> Color {
-    if (x % 2 == 1 && y % 2 != 1) || (x % 2 != 1 && y % 2 == 1) {
-        Color::LIME_GREEN
-    } else {
-        Color::GREEN
+    if let Some(source) = StudySource::from_args() {
+        app.add_plugin(StudyPlugin { source });
     }
-}
 
-fn is_inside_board(x: i32, y: i32) -> bool {
-    x >= 0 && x <= 7 && y >= 0 && y <= 7
-}
+    #[cfg(feature = "speech")]
+    app.add_plugin(speech::SpeechPlugin);
 
-fn to_board_posistion(pos: f32) -> i32 {
-    (pos.round() / PIECE_SIZE as f32).floor() as i32
+    app.run();
 }
 
-fn get_possible_moves(
-    piece_type: &Piece,
-    piece_position: &BoardPosition,
-    piece_player: &Player,
-    white_pieces_positions: Vec<&BoardPosition>,
-    black_pieces_positions: Vec<&BoardPosition>,
-) -> Vec<(i32, i32)> {
-    let mut possible_moves = Vec::new();
-
-    match piece_type {
-        Piece::King => {
-            for i in 0..8 {
-                let ex_pos = match i {
-                    0 => (1, 0),
-                    1 => (1, 1),
-                    2 => (0, 1),
-                    3 => (-1, 1),
-                    4 => (-1, 0),
-                    5 => (-1, -1),
-                    6 => (0, -1),
-                    7 => (1, -1),
-                    _ => unreachable!(),
-                };
-
-                let (allies_positions, _) = get_allies_and_enemies(
-                    piece_player,
-                    &white_pieces_positions,
-                    &black_pieces_positions,
-                );
-
-                let target = (piece_position.x + ex_pos.0, piece_position.y + ex_pos.1);
-
-                if is_inside_board(target.0, target.1)
-                    && !allies_positions.contains(&&BoardPosition::new(target.0, target.1))
-                {
-                    possible_moves.push(target);
-                }
-            }
-        }
-        Piece::Queen => {
-            for i in 0..8 {
-                let ex_pos = match i {
-                    0 => (0, 1),
-                    1 => (0, -1),
-                    2 => (1, 0),
-                    3 => (-1, 0),
-                    4 => (1, 1),
-                    5 => (1, -1),
-                    6 => (-1, 1),
-                    7 => (-1, -1),
-                    _ => unreachable!(),
-                };
-
-                let mut path = true;
-                let mut chain = 1;
-
-                let (allies_positions, enemies_positions) = get_allies_and_enemies(
-                    piece_player,
-                    &white_pieces_positions,
-                    &black_pieces_positions,
-                );
-
-                while path {
-                    let target = (
-                        piece_position.x + ex_pos.0 * chain,
-                        piece_position.y + ex_pos.1 * chain,
-                    );
-
-                    if !allies_positions.contains(&&BoardPosition::new(target.0, target.1))
-                        && is_inside_board(target.0, target.1)
-                    {
-                        possible_moves.push(target);
-
-                        if enemies_positions.contains(&&BoardPosition::new(target.0, target.1)) {
-                            path = false;
-                        }
-
-                        chain += 1;
-                    } else {
-                        path = false;
-                    }
-                }
-            }
-        }
-        Piece::Knight => {
-            let targets = [
-                (1, 2),
-                (-1, 2),
-                (2, 1),
-                (2, -1),
-                (1, -2),
-                (-1, -2),
-                (-2, 1),
-                (-2, -1),
-            ];
-
-            let (allies_positions, _) = get_allies_and_enemies(
-                piece_player,
-                &white_pieces_positions,
-                &black_pieces_positions,
-            );
-
-            for i in 0..8 {
-                let target = (
-                    piece_position.x + targets[i].0,
-                    piece_position.y + targets[i].1,
-                );
-
-                if is_inside_board(target.1, target.1)
-                    && !allies_positions.contains(&&BoardPosition::new(target.0, target.1))
-                {
-                    possible_moves.push(target);
-                }
-            }
-        }
-        Piece::Pawn => {
-            let (allies_positions, enemies_positions) = get_allies_and_enemies(
-                piece_player,
-                &white_pieces_positions,
-                &black_pieces_positions,
-            );
-            let y_modifier = match piece_player {
-                &Player::White => 1,
-                &Player::Black => -1,
-            };
-            let starting_y = match piece_player {
-                &Player::White => 1,
-                &Player::Black => 6,
-            };
-
-            if !allies_positions.contains(&&BoardPosition::new(
-                piece_position.x,
-                piece_position.y + 1 * y_modifier,
-            )) && !enemies_positions.contains(&&BoardPosition::new(
-                piece_position.x,
-                piece_position.y + 1 * y_modifier,
-            )) && piece_position.y < 7
-                && piece_position.y > 0
-            {
-                possible_moves.push((piece_position.x, piece_position.y + 1 * y_modifier));
-            }
-
-            if !allies_positions.contains(&&BoardPosition::new(
-                piece_position.x,
-                piece_position.y + 2 * y_modifier,
-            )) && !enemies_positions.contains(&&BoardPosition::new(
-                piece_position.x,
-                piece_position.y + 2 * y_modifier,
-            )) && piece_position.y == starting_y
-            {
-                possible_moves.push((piece_position.x, piece_position.y + 2 * y_modifier));
-            }
-
-            if enemies_positions.contains(&&BoardPosition::new(
-                piece_position.x + 1,
-                piece_position.y + 1 * y_modifier,
-            )) {
-                possible_moves.push((piece_position.x + 1, piece_position.y + 1 * y_modifier));
-            }
+fn get_primary_window(overlay_mode: OverlayMode) -> Window {
+    let mut window = Window {
+        resolution: (
+            (PIECE_SIZE * BOARD_SIZE) as f32,
+            (PIECE_SIZE * BOARD_SIZE) as f32,
+        )
+            .into(),
+        title: "Chess".to_string(),
+        resizable: true,
+        // Follow the size of the page element the canvas lives in
+        fit_canvas_to_parent: true,
+        ..default()
+    };
 
-            if enemies_positions.contains(&&BoardPosition::new(
-                piece_position.x - 1,
-                piece_position.y + 1 * y_modifier,
-            )) {
-                possible_moves.push((piece_position.x - 1, piece_position.y + 1 * y_modifier));
-            }
-        }
-        Piece::Bishop => {
-            for i in 0..4 {
-                let ex_pos = match i {
-                    0 => (1, 1),
-                    1 => (1, -1),
-                    2 => (-1, 1),
-                    3 => (-1, -1),
-                    _ => unreachable!(),
-                };
-
-                let mut path = true;
-                let mut chain = 1;
-
-                let (allies_positions, enemies_positions) = get_allies_and_enemies(
-                    piece_player,
-                    &white_pieces_positions,
-                    &black_pieces_positions,
-                );
-
-                while path {
-                    let target = (
-                        piece_position.x + ex_pos.0 * chain,
-                        piece_position.y + ex_pos.1 * chain,
-                    );
-
-                    if !allies_positions.contains(&&BoardPosition::new(target.0, target.1))
-                        && is_inside_board(target.0, target.1)
-                    {
-                        possible_moves.push(target);
-
-                        if enemies_positions.contains(&&BoardPosition::new(target.0, target.1)) {
-                            path = false;
-                        }
-
-                        chain += 1;
-                    } else {
-                        path = false;
-                    }
-                }
-            }
-        }
-        Piece::Rook => {
-            for i in 0..4 {
-                let ex_pos = match i {
-                    0 => (0, 1),
-                    1 => (0, -1),
-                    2 => (1, 0),
-                    3 => (-1, 0),
-                    _ => unreachable!(),
-                };
-
-                let mut path = true;
-                let mut chain = 1;
-
-                let (allies_positions, enemies_positions) = get_allies_and_enemies(
-                    piece_player,
-                    &white_pieces_positions,
-                    &black_pieces_positions,
-                );
-
-                while path {
-                    let target = (
-                        piece_position.x + ex_pos.0 * chain,
-                        piece_position.y + ex_pos.1 * chain,
-                    );
-
-                    if !allies_positions.contains(&&BoardPosition::new(target.0, target.1))
-                        && is_inside_board(target.0, target.1)
-                    {
-                        possible_moves.push(target);
-
-                        if enemies_positions.contains(&&BoardPosition::new(target.0, target.1)) {
-                            path = false;
-                        }
-
-                        chain += 1;
-                    } else {
-                        path = false;
-                    }
-                }
-            }
-        }
+    // Capture software wants a fixed, borderless frame around the board
+    if overlay_mode != OverlayMode::Off {
+        window.resizable = false;
+        window.decorations = false;
     }
 
-    possible_moves
-}
-
-fn get_allies_and_enemies<'a>(
-    piece_player: &Player,
-    white_pieces_positions: &'a Vec<&'a BoardPosition>,
-    black_pieces_positions: &'a Vec<&'a BoardPosition>,
-) -> (&'a Vec<&'a BoardPosition>, &'a Vec<&'a BoardPosition>) {
-    let allies_positions;
-    let enemies_positions;
-
-    match piece_player {
-        &Player::White => {
-            allies_positions = white_pieces_positions;
-            enemies_positions = black_pieces_positions;
-        }
-        &Player::Black => {
-            allies_positions = black_pieces_positions;
-            enemies_positions = white_pieces_positions;
+    if overlay_mode == OverlayMode::Transparent {
+        window.transparent = true;
+        #[cfg(target_os = "macos")]
+        {
+            window.composite_alpha_mode = bevy::window::CompositeAlphaMode::PostMultiplied;
         }
     }
 
-    (allies_positions, enemies_positions)
+    window
 }
 
-fn spawn_piece(
-    piece_type: Piece,
-    player: Player,
-    x: i32,
-    y: i32,
-    texture_atlas: Handle<TextureAtlas>,
-    index: usize,
-    commands: &mut Commands,
+// Only the board goes out to the stream, so the game's camera draws none
+// of the UI: the HUD, toasts, move panel and banners
+fn hide_ui_in_overlay(
+    mut commands: Commands,
+    overlay_mode: Res<OverlayMode>,
+    cameras: Query<Entity, Added<GameCamera>>,
 ) {
-    commands.spawn((
-        SpriteSheetBundle {
-            sprite: TextureAtlasSprite {
-                custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
-                index,
-                ..default()
-            },
-            texture_atlas,
-            transform: Transform::from_xyz(0.0, 0.0, PIECE_Z_INDEX),
-            ..default()
-        },
-        piece_type,
-        player,
-        BoardPosition::new(x, y),
-    ));
-}
+    if *overlay_mode == OverlayMode::Off {
+        return;
+    }
 
-fn spawn_white_pieces(game_assets: &GameAssets, commands: &mut Commands) {
-    spawn_piece(
-        Piece::King,
-        Player::White,
-        4,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::King],
-        commands,
-    );
-    spawn_piece(
-        Piece::Queen,
-        Player::White,
-        3,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Queen],
-        commands,
-    );
-    spawn_piece(
-        Piece::Knight,
-        Player::White,
-        1,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Knight],
-        commands,
-    );
-    spawn_piece(
-        Piece::Knight,
-        Player::White,
-        6,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Knight],
-        commands,
-    );
-    spawn_piece(
-        Piece::Bishop,
-        Player::White,
-        2,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Bishop],
-        commands,
-    );
-    spawn_piece(
-        Piece::Bishop,
-        Player::White,
-        5,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Bishop],
-        commands,
-    );
-    spawn_piece(
-        Piece::Rook,
-        Player::White,
-        0,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Rook],
-        commands,
-    );
-    spawn_piece(
-        Piece::Rook,
-        Player::White,
-        7,
-        0,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Rook],
-        commands,
-    );
-
-    for i in 0..BOARD_SIZE {
-        spawn_piece(
-            Piece::Pawn,
-            Player::White,
-            i,
-            1,
-            game_assets.piece_atlas.clone(),
-            game_assets.pieces[&Piece::Pawn],
-            commands,
-        );
+    for entity in cameras.iter() {
+        commands
+            .entity(entity)
+            .insert(UiCameraConfig { show_ui: false });
     }
 }
 
-fn spawn_black_pieces(game_assets: &GameAssets, commands: &mut Commands) {
-    spawn_piece(
-        Piece::King,
-        Player::Black,
-        3,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::King] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Queen,
-        Player::Black,
-        4,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Queen] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Knight,
-        Player::Black,
-        1,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Knight] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Knight,
-        Player::Black,
-        6,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Knight] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Bishop,
-        Player::Black,
-        2,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Bishop] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Bishop,
-        Player::Black,
-        5,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Bishop] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Rook,
-        Player::Black,
-        0,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Rook] + 6,
-        commands,
-    );
-    spawn_piece(
-        Piece::Rook,
-        Player::Black,
-        7,
-        7,
-        game_assets.piece_atlas.clone(),
-        game_assets.pieces[&Piece::Rook] + 6,
-        commands,
-    );
-
-    for i in 0..BOARD_SIZE {
-        spawn_piece(
-            Piece::Pawn,
-            Player::Black,
-            i,
-            6,
-            game_assets.piece_atlas.clone(),
-            game_assets.pieces[&Piece::Pawn] + 6,
-            commands,
-        );
+fn get_clear_color(overlay_mode: OverlayMode) -> ClearColor {
+    match overlay_mode {
+        OverlayMode::Off => ClearColor::default(),
+        OverlayMode::Transparent => ClearColor(Color::NONE),
+        OverlayMode::ChromaKey => ClearColor(CHROMA_KEY_COLOR),
     }
 }
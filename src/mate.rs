@@ -0,0 +1,152 @@
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_core::{find_mate, get_san, Board, MateTree};
+use fluent::fluent_args;
+use futures_lite::future;
+
+use crate::{
+    input::{Action, Actions},
+    locale::Localizer,
+    pieces::GameAssets,
+    rules::{CurrentTurn, MoveHistory},
+    settings::Settings,
+    toast::Toast,
+    ui_theme::{ThemedBackground, ThemedText, UiTheme},
+    GameSet,
+};
+
+pub struct MateSearchPlugin;
+
+impl Plugin for MateSearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(start_mate_search)
+            .add_system(finish_mate_searches)
+            .add_system(
+                close_mate_tree
+                    .run_if(resource_changed::<MoveHistory>())
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+// The search runs off the main thread, since deep ones take a while
+#[derive(Component)]
+struct MateSearch {
+    task: Task<Option<MateTree>>,
+    board: Board,
+    // The position is stale once more moves have been played
+    moves_played: usize,
+    max_moves: u32,
+}
+
+#[derive(Component)]
+struct MateTreePanel;
+
+fn start_mate_search(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    current_turn: Res<CurrentTurn>,
+    history: Res<MoveHistory>,
+    searches: Query<(), With<MateSearch>>,
+    panels: Query<Entity, With<MateTreePanel>>,
+) {
+    if !actions.just_pressed(Action::FindMate) || !searches.is_empty() {
+        return;
+    }
+
+    // Pressing it again with the tree open closes it
+    if !panels.is_empty() {
+        for entity in panels.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let board = *board;
+    let player = current_turn.0;
+    let max_moves = settings.mate_search_moves;
+    let task =
+        AsyncComputeTaskPool::get().spawn(async move { find_mate(&board, player, max_moves) });
+
+    commands.spawn(MateSearch {
+        task,
+        board,
+        moves_played: history.moves.len(),
+        max_moves,
+    });
+}
+
+fn finish_mate_searches(
+    mut commands: Commands,
+    mut searches: Query<(Entity, &mut MateSearch)>,
+    history: Res<MoveHistory>,
+    localizer: Res<Localizer>,
+    game_assets: Res<GameAssets>,
+    theme: Res<UiTheme>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (entity, mut search) in searches.iter_mut() {
+        let Some(tree) = future::block_on(future::poll_once(&mut search.task)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+
+        if search.moves_played != history.moves.len() {
+            continue;
+        }
+
+        let Some(tree) = tree else {
+            toasts.send(Toast::new("toast-no-mate").with_arg("moves", search.max_moves));
+            continue;
+        };
+
+        let moves = tree.moves_to_mate();
+        info!("mate in {moves}:\n{}", tree.to_text(&search.board));
+        toasts.send(
+            Toast::new("toast-mate-found")
+                .with_arg("moves", moves)
+                .with_arg("move", get_san(&search.board, &tree.mv)),
+        );
+
+        let title = localizer.format("mate-tree-title", &fluent_args!["moves" => moves]);
+        let style = TextStyle {
+            font: game_assets.font.clone(),
+            font_size: 14.0,
+            color: theme.text,
+        };
+
+        commands.spawn((
+            TextBundle::from_sections([
+                TextSection::new(format!("{title}\n"), style.clone()),
+                TextSection::new(tree.to_text(&search.board), style),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(8.0),
+                    // Below the HUD
+                    top: Val::Px(48.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            })
+            .with_background_color(theme.panel),
+            ThemedBackground(|theme| theme.panel),
+            ThemedText(|theme| theme.text),
+            MateTreePanel,
+        ));
+    }
+}
+
+// The tree only holds for the position it was found in
+fn close_mate_tree(mut commands: Commands, panels: Query<Entity, With<MateTreePanel>>) {
+    for entity in panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,318 @@
+use bevy::{a11y::AccessibilityNode, prelude::*};
+use chess_core::{get_random_maze, Board, BoardPosition, Maze, Piece, Player, Square, MAZE_PIECES};
+use fluent::fluent_args;
+
+use crate::{
+    board::{BoardRoot, MAZE_TARGET_Z_INDEX, PIECE_SIZE},
+    explore::Exploration,
+    input::{Action, Actions, Selection},
+    locale::Localizer,
+    pieces::{spawn_pieces, GameAssets},
+    puzzles::PuzzleAttempt,
+    rules::{CurrentTurn, GameOver, GameTime, MoveEvent, MoveHistory, ReplaceHistoryEvent},
+    settings::Settings,
+    toast::Toast,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+const MAZE_TARGETS: usize = 5;
+const MAZE_OBSTACLES: usize = 10;
+
+// A training game for beginners: one piece has to be taken onto every
+// target square, around pawns in the way, as quickly as can be. Moves go
+// through the usual picking and the move generator, but land here rather
+// than in the game, which is kept aside meanwhile as for puzzles. Each
+// new maze goes on to the next kind of piece
+pub struct MazePlugin;
+
+impl Plugin for MazePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(switch_mazes.in_set(GameSet::Input))
+            .add_system(
+                apply_maze_moves
+                    .run_if(resource_exists::<MazeRun>())
+                    .in_set(GameSet::Apply),
+            )
+            .add_systems(
+                (
+                    update_maze_targets.run_if(
+                        resource_exists_and_changed::<MazeRun>()
+                            .or_else(resource_removed::<MazeRun>())
+                            .or_else(resource_changed::<Settings>()),
+                    ),
+                    spawn_maze_banner
+                        .run_if(resource_added::<MazeRun>().or_else(resource_removed::<MazeRun>())),
+                    update_maze_banner.run_if(resource_exists::<MazeRun>()),
+                )
+                    .chain()
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+// Present while a maze is played
+#[derive(Resource)]
+pub struct MazeRun {
+    // The game as it was before the maze
+    pub game: MoveHistory,
+    pub maze: Maze,
+    // Into MAZE_PIECES
+    pub level: usize,
+    pub player: Player,
+    // Where the piece stands now
+    pub square: Square,
+    pub targets_left: Vec<Square>,
+    pub moves: usize,
+    pub par: usize,
+    // Game time when the maze was laid out, and how long it took once done
+    pub started: f64,
+    pub time: Option<f64>,
+}
+
+#[derive(Component)]
+struct MazeTarget;
+
+#[derive(Component)]
+struct MazeBanner;
+
+// Lays out the next maze, starting from a game, or goes back to the game
+fn switch_mazes(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    settings: Res<Settings>,
+    game_time: Res<GameTime>,
+    run: Option<Res<MazeRun>>,
+    exploration: Option<Res<Exploration>>,
+    attempt: Option<Res<PuzzleAttempt>>,
+    mut board: ResMut<Board>,
+    mut current_turn: ResMut<CurrentTurn>,
+    mut selection: ResMut<Selection>,
+    game_assets: Option<Res<GameAssets>>,
+    pieces: Query<Entity, With<Piece>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if actions.just_pressed(Action::LeaveMaze) {
+        if let Some(run) = run {
+            info!("back to the game");
+            replace_events.send(ReplaceHistoryEvent(run.game.clone()));
+            commands.remove_resource::<MazeRun>();
+        }
+        return;
+    }
+
+    if !actions.just_pressed(Action::NextMaze) || exploration.is_some() || attempt.is_some() {
+        return;
+    }
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    let (level, game) = match &run {
+        Some(run) => ((run.level + 1) % MAZE_PIECES.len(), run.game.clone()),
+        None => (0, history.clone()),
+    };
+    let piece = MAZE_PIECES[level];
+    let Some((maze, par)) =
+        get_random_maze(piece, MAZE_TARGETS, MAZE_OBSTACLES, &mut rand::thread_rng())
+            .and_then(|maze| Some((maze.clone(), maze.get_par()?)))
+    else {
+        warn!("no maze for the {} turned up", piece.name());
+        toasts.send(Toast::new("toast-maze-failed"));
+        return;
+    };
+    info!("a {} maze, solved in {par} moves at best", piece.name());
+
+    // The piece is the player's, even when the engine plays White
+    let player = settings
+        .engine_opponent
+        .map_or(Player::White, |engine| engine.opponent());
+
+    *board = maze.get_board(player);
+    current_turn.0 = player;
+    *selection = Selection::Idle;
+
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(game_assets) = game_assets {
+        spawn_pieces(&mut commands, &game_assets, board_root, board.pieces());
+    } else {
+        for piece in board.pieces() {
+            let piece = commands.spawn(piece).id();
+            commands.entity(board_root).add_child(piece);
+        }
+    }
+
+    // Moves aren't taken once the game is over. The result comes back with
+    // the game
+    commands.remove_resource::<GameOver>();
+    commands.insert_resource(MazeRun {
+        game,
+        square: maze.start,
+        targets_left: maze.targets.clone(),
+        maze,
+        level,
+        player,
+        moves: 0,
+        par,
+        started: game_time.0,
+        time: None,
+    });
+}
+
+// Moves of anything but the maze's piece, such as one the engine was
+// still thinking about for the game, are dropped. They are drained rather
+// than read, so the game doesn't take them once the maze is left
+fn apply_maze_moves(
+    mut run: ResMut<MazeRun>,
+    mut move_events: ResMut<Events<MoveEvent>>,
+    mut pieces: Query<&mut BoardPosition, With<Piece>>,
+    mut board: ResMut<Board>,
+    mut current_turn: ResMut<CurrentTurn>,
+    mut selection: ResMut<Selection>,
+    game_time: Res<GameTime>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for MoveEvent(mv) in move_events.drain() {
+        if mv.from != run.square || run.time.is_some() {
+            continue;
+        }
+
+        let Some(mut position) = pieces
+            .iter_mut()
+            .find(|position| position.square() == mv.from)
+        else {
+            continue;
+        };
+        position.x = mv.to.0;
+        position.y = mv.to.1;
+
+        board.apply_move(&mv);
+        run.square = mv.to;
+        run.moves += 1;
+        run.targets_left.retain(|target| *target != mv.to);
+        // The player moves again
+        current_turn.0 = run.player;
+        *selection = Selection::Idle;
+
+        if run.targets_left.is_empty() {
+            let time = game_time.0 - run.started;
+            info!("maze done in {} moves and {time:.1}s", run.moves);
+            run.time = Some(time);
+            toasts.send(
+                Toast::new("toast-maze-done")
+                    .with_arg("moves", run.moves)
+                    .with_arg("par", run.par)
+                    .with_arg("seconds", format!("{time:.1}")),
+            );
+        }
+    }
+}
+
+fn update_maze_targets(
+    mut commands: Commands,
+    run: Option<Res<MazeRun>>,
+    settings: Res<Settings>,
+    targets: Query<Entity, With<MazeTarget>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    for entity in targets.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(run), Ok(board_root)) = (run, board_root.get_single()) else {
+        return;
+    };
+
+    // Diamonds, to tell them from the dots of the possible moves
+    for square in &run.targets_left {
+        let target = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: settings.palette().check,
+                        custom_size: Some(Vec2::splat(PIECE_SIZE as f32 / 3.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(
+                        (square.0 * PIECE_SIZE + (PIECE_SIZE / 2)) as f32,
+                        (square.1 * PIECE_SIZE + (PIECE_SIZE / 2)) as f32,
+                        MAZE_TARGET_Z_INDEX,
+                    )
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                    ..default()
+                },
+                MazeTarget,
+            ))
+            .id();
+        commands.entity(board_root).add_child(target);
+    }
+}
+
+// Kept up while the maze is, as the time counts up every frame. Screen
+// readers are only told of found targets, not every tick of it
+fn spawn_maze_banner(
+    mut commands: Commands,
+    run: Option<Res<MazeRun>>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    banners: Query<Entity, With<MazeBanner>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(_), Some(game_assets), Ok(status_bar)) = (run, game_assets, status_bars.get_single())
+    else {
+        return;
+    };
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        String::new(),
+        BannerKind::Mode,
+        &game_assets,
+        &theme,
+        MazeBanner,
+    );
+}
+
+fn update_maze_banner(
+    run: Res<MazeRun>,
+    game_time: Res<GameTime>,
+    localizer: Res<Localizer>,
+    mut banners: Query<(&mut Text, &mut AccessibilityNode), With<MazeBanner>>,
+) {
+    let seconds = run.time.unwrap_or(game_time.0 - run.started);
+    let text = localizer.format(
+        if run.time.is_some() {
+            "maze-done-banner"
+        } else {
+            "maze-banner"
+        },
+        &fluent_args![
+            "piece" => run.maze.piece.name(),
+            "found" => run.maze.targets.len() - run.targets_left.len(),
+            "targets" => run.maze.targets.len(),
+            "moves" => run.moves,
+            "par" => run.par,
+            "seconds" => format!("{seconds:.1}")
+        ],
+    );
+
+    for (mut banner_text, mut node) in banners.iter_mut() {
+        if run.is_changed() {
+            node.set_name(text.clone());
+        }
+        if banner_text.sections[0].value != text {
+            banner_text.sections[0].value = text.clone();
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+};
+use chess_core::{get_board_after_moves, BOARD_SIZE};
+
+use crate::{
+    analysis_window::spawn_board_sprites,
+    board::PIECE_SIZE,
+    move_panel::{HoveredMove, PANEL_WIDTH},
+    pieces::GameAssets,
+    rules::MoveHistory,
+    settings::Settings,
+    ui_theme::{ThemedBackground, UiTheme},
+    GameSet, GameState,
+};
+
+// Seen only by the mini board's camera, like the analysis window's layer
+const MINI_BOARD_LAYER: u8 = 2;
+// Of the image drawn into, which is shown a little smaller beside the panel
+const MINI_BOARD_TEXTURE_SIZE: u32 = 256;
+const MINI_BOARD_SIZE: f32 = 160.0;
+
+// The position after the move under the mouse in the move panel, drawn by
+// a camera of its own into an image next to the panel, so a line can be
+// looked through without leaving the position on the board
+pub struct MiniBoardPlugin;
+
+impl Plugin for MiniBoardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_mini_board.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(
+                update_mini_board
+                    .run_if(
+                        resource_changed::<HoveredMove>().or_else(resource_changed::<Settings>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+#[derive(Component)]
+struct MiniBoardCamera;
+
+// The image of the board in the UI
+#[derive(Component)]
+struct MiniBoardView;
+
+#[derive(Component, Clone)]
+struct MiniBoardSprite;
+
+fn spawn_mini_board(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    theme: Res<UiTheme>,
+) {
+    let size = Extent3d {
+        width: MINI_BOARD_TEXTURE_SIZE,
+        height: MINI_BOARD_TEXTURE_SIZE,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image.clone()),
+                // Before the main camera, which shows the image
+                order: -1,
+                // Only drawn while a move is hovered
+                is_active: false,
+                ..default()
+            },
+            transform: Transform::from_xyz(board_size / 2.0, board_size / 2.0, 999.0),
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::AutoMin {
+                    min_width: board_size,
+                    min_height: board_size,
+                },
+                ..default()
+            },
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        RenderLayers::layer(MINI_BOARD_LAYER),
+        MiniBoardCamera,
+    ));
+
+    // Framed by a panel, as an image's own background color tints it
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(PANEL_WIDTH + 16.0),
+                        top: Val::Px(8.0),
+                        ..default()
+                    },
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: theme.panel.into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ThemedBackground(|theme| theme.panel),
+            MiniBoardView,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    size: Size::all(Val::Px(MINI_BOARD_SIZE)),
+                    ..default()
+                },
+                image: image.into(),
+                ..default()
+            });
+        });
+}
+
+fn update_mini_board(
+    mut commands: Commands,
+    hovered_move: Res<HoveredMove>,
+    history: Res<MoveHistory>,
+    settings: Res<Settings>,
+    game_assets: Option<Res<GameAssets>>,
+    sprites: Query<Entity, With<MiniBoardSprite>>,
+    mut cameras: Query<&mut Camera, With<MiniBoardCamera>>,
+    mut views: Query<&mut Visibility, With<MiniBoardView>>,
+) {
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let shown = hovered_move.0.is_some() && game_assets.is_some();
+    for mut camera in cameras.iter_mut() {
+        camera.is_active = shown;
+    }
+    for mut visibility in views.iter_mut() {
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let (Some(node), Some(game_assets)) = (hovered_move.0, game_assets) else {
+        return;
+    };
+
+    let (moves, _) = history.tree.get_line(Some(node));
+    let last_move = moves.last().copied();
+    let palette = settings.palette();
+    let marked: Vec<_> = last_move
+        .into_iter()
+        .flat_map(|mv| [(mv.from, palette.last_move), (mv.to, palette.last_move)])
+        .collect();
+
+    spawn_board_sprites(
+        &mut commands,
+        &get_board_after_moves(&moves),
+        &marked,
+        &settings,
+        &game_assets,
+        (RenderLayers::layer(MINI_BOARD_LAYER), MiniBoardSprite),
+    );
+}
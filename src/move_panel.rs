@@ -0,0 +1,368 @@
+use bevy::{prelude::*, utils::HashSet};
+use chess_core::{get_board_after_moves, get_nag_symbol, get_san, Board};
+
+use crate::{
+    analysis::ViewedPly,
+    input::{Action, Actions},
+    pieces::GameAssets,
+    rules::{MoveHistory, ReplaceHistoryEvent},
+    ui_theme::UiTheme,
+    variations::{MoveTree, NodeId, TreeNode},
+    GameSet,
+};
+
+pub const PANEL_WIDTH: f32 = 200.0;
+const VARIATION_INDENT: f32 = 12.0;
+
+// The moves of the game as a PGN viewer lays them out, the main line with
+// its variations indented below the moves they replace, and comments in
+// between. Any move can be clicked to go to it, and variations folded away
+pub struct MovePanelPlugin;
+
+impl Plugin for MovePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MovePanel>()
+            .init_resource::<HoveredMove>()
+            .add_system(toggle_move_panel)
+            .add_system(click_move_panel.in_set(GameSet::Input))
+            .add_system(hover_move_panel.in_set(GameSet::Render))
+            .add_system(
+                draw_move_panel
+                    .run_if(
+                        resource_changed::<MovePanel>()
+                            .or_else(resource_changed::<MoveHistory>())
+                            .or_else(resource_exists_and_changed::<ViewedPly>())
+                            .or_else(resource_changed::<UiTheme>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct MovePanel {
+    shown: bool,
+    // The first moves of the variations folded away
+    collapsed: HashSet<NodeId>,
+}
+
+// The move under the mouse, if any
+#[derive(Resource, Default)]
+pub struct HoveredMove(pub Option<NodeId>);
+
+#[derive(Component)]
+struct MovePanelRoot;
+
+#[derive(Component)]
+enum PanelButton {
+    Move(NodeId),
+    Toggle(NodeId),
+}
+
+enum PanelItem {
+    Move { node: NodeId, label: String },
+    Toggle { node: NodeId, collapsed: bool },
+    Comment(String),
+}
+
+// One line of the panel, indented by how deep its variation is
+struct PanelRow {
+    depth: usize,
+    items: Vec<PanelItem>,
+}
+
+fn toggle_move_panel(actions: Res<Actions>, mut panel: ResMut<MovePanel>) {
+    if actions.just_pressed(Action::ToggleMovePanel) {
+        panel.shown = !panel.shown;
+    }
+}
+
+fn click_move_panel(
+    buttons: Query<(&Interaction, &PanelButton), Changed<Interaction>>,
+    history: Res<MoveHistory>,
+    mut panel: ResMut<MovePanel>,
+    viewed_ply: Option<ResMut<ViewedPly>>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+) {
+    let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Clicked)
+    else {
+        return;
+    };
+
+    let node = match button {
+        PanelButton::Toggle(node) => {
+            if !panel.collapsed.remove(node) {
+                panel.collapsed.insert(*node);
+            }
+            return;
+        }
+        PanelButton::Move(node) => *node,
+    };
+
+    let (moves, times) = history.tree.get_line(Some(node));
+
+    // After the game, moves of the game itself are only looked at
+    if let Some(mut viewed_ply) = viewed_ply {
+        if history.moves.starts_with(&moves) {
+            viewed_ply.0 = moves.len();
+            return;
+        }
+    }
+
+    replace_events.send(ReplaceHistoryEvent(MoveHistory {
+        moves,
+        times,
+        tree: history.tree.clone(),
+    }));
+}
+
+fn hover_move_panel(
+    buttons: Query<(&Interaction, &PanelButton)>,
+    mut hovered_move: ResMut<HoveredMove>,
+) {
+    let node = buttons
+        .iter()
+        .find_map(|(interaction, button)| match (interaction, button) {
+            (Interaction::Hovered | Interaction::Clicked, PanelButton::Move(node)) => Some(*node),
+            _ => None,
+        });
+
+    if hovered_move.0 != node {
+        hovered_move.0 = node;
+    }
+}
+
+fn draw_move_panel(
+    mut commands: Commands,
+    panel: Res<MovePanel>,
+    history: Res<MoveHistory>,
+    viewed_ply: Option<Res<ViewedPly>>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    roots: Query<Entity, With<MovePanelRoot>>,
+) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(game_assets) = game_assets.filter(|_| panel.shown) else {
+        return;
+    };
+
+    let shown_moves = match viewed_ply {
+        Some(viewed_ply) => &history.moves[..viewed_ply.0],
+        None => &history.moves,
+    };
+    let current = history.tree.find(shown_moves);
+
+    let mut rows = vec![PanelRow {
+        depth: 0,
+        items: history
+            .tree
+            .comment
+            .iter()
+            .map(|comment| PanelItem::Comment(comment.clone()))
+            .collect(),
+    }];
+    if let Some(&first) = history.tree.children(None).first() {
+        add_line(
+            &history.tree,
+            &panel.collapsed,
+            first,
+            get_board_after_moves(&[]),
+            0,
+            0,
+            &mut rows,
+        );
+    }
+
+    let style = TextStyle {
+        font: game_assets.font.clone(),
+        font_size: 14.0,
+        color: theme.text,
+    };
+    let comment_style = TextStyle {
+        color: theme.comment_text,
+        ..style.clone()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(8.0),
+                        top: Val::Px(8.0),
+                        ..default()
+                    },
+                    size: Size::width(Val::Px(PANEL_WIDTH)),
+                    max_size: Size::height(Val::Percent(80.0)),
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::Hidden,
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: theme.panel.into(),
+                ..default()
+            },
+            MovePanelRoot,
+        ))
+        .with_children(|parent| {
+            for row in rows.iter().filter(|row| !row.items.is_empty()) {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_wrap: FlexWrap::Wrap,
+                            margin: UiRect::left(Val::Px(row.depth as f32 * VARIATION_INDENT)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row_node| {
+                        for item in &row.items {
+                            let (text, button, highlighted) =
+                                match item {
+                                    PanelItem::Comment(comment) => {
+                                        // Wrapped to what is left of the panel
+                                        let width =
+                                            PANEL_WIDTH - 8.0 - row.depth as f32 * VARIATION_INDENT;
+                                        row_node.spawn(
+                                            TextBundle::from_section(
+                                                comment.clone(),
+                                                comment_style.clone(),
+                                            )
+                                            .with_style(Style {
+                                                max_size: Size::width(Val::Px(width)),
+                                                margin: UiRect::right(Val::Px(4.0)),
+                                                ..default()
+                                            }),
+                                        );
+                                        continue;
+                                    }
+                                    PanelItem::Move { node, label } => (
+                                        label.clone(),
+                                        PanelButton::Move(*node),
+                                        current == Some(*node),
+                                    ),
+                                    PanelItem::Toggle { node, collapsed } => (
+                                        if *collapsed { "[+]" } else { "[-]" }.to_string(),
+                                        PanelButton::Toggle(*node),
+                                        false,
+                                    ),
+                                };
+
+                            row_node
+                                .spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            margin: UiRect::right(Val::Px(4.0)),
+                                            ..default()
+                                        },
+                                        background_color: if highlighted {
+                                            theme.highlight.into()
+                                        } else {
+                                            Color::NONE.into()
+                                        },
+                                        ..default()
+                                    },
+                                    button,
+                                ))
+                                .with_children(|button| {
+                                    button.spawn(TextBundle::from_section(text, style.clone()));
+                                });
+                        }
+                    });
+            }
+        });
+}
+
+// Lays out a line from its first move, with the variations of each of its
+// moves in rows of their own below it
+fn add_line(
+    tree: &MoveTree,
+    collapsed: &HashSet<NodeId>,
+    first: NodeId,
+    mut board: Board,
+    mut ply: usize,
+    depth: usize,
+    rows: &mut Vec<PanelRow>,
+) {
+    let mut node = Some(first);
+    let mut starts_row = true;
+
+    while let Some(id) = node {
+        let tree_node = tree.node(id);
+        let mv = tree_node.mv;
+        let label = get_move_label(&board, tree_node, ply, starts_row);
+        let items = &mut rows.last_mut().unwrap().items;
+        items.push(PanelItem::Move { node: id, label });
+        starts_row = false;
+
+        // Black's move after a comment is numbered again, as in PGN
+        if let Some(comment) = &tree_node.comment {
+            items.push(PanelItem::Comment(comment.clone()));
+            starts_row = true;
+        }
+
+        // The other moves from the same position, once the main one is in
+        let siblings = tree.siblings(id);
+        if siblings[0] == id && siblings.len() > 1 {
+            for &variation in &siblings[1..] {
+                let is_collapsed = collapsed.contains(&variation);
+                rows.push(PanelRow {
+                    depth: depth + 1,
+                    items: vec![PanelItem::Toggle {
+                        node: variation,
+                        collapsed: is_collapsed,
+                    }],
+                });
+
+                if is_collapsed {
+                    let label = get_move_label(&board, tree.node(variation), ply, true);
+                    let items = &mut rows.last_mut().unwrap().items;
+                    items.push(PanelItem::Move {
+                        node: variation,
+                        label: format!("{label} …"),
+                    });
+                } else {
+                    add_line(tree, collapsed, variation, board, ply, depth + 1, rows);
+                }
+            }
+
+            rows.push(PanelRow {
+                depth,
+                items: Vec::new(),
+            });
+            starts_row = true;
+        }
+
+        board.apply_move(&mv);
+        ply += 1;
+        node = tree.children(Some(id)).first().copied();
+    }
+}
+
+// Numbered like PGN: "1. e4", and Black's moves only where a row starts,
+// as "1... e5". Annotations follow the move, "!" and "?" without a space
+pub fn get_move_label(board: &Board, node: &TreeNode, ply: usize, starts_row: bool) -> String {
+    let mut san = get_san(board, &node.mv);
+    for &nag in &node.nags {
+        if nag > 6 {
+            san.push(' ');
+        }
+        san.push_str(&get_nag_symbol(nag));
+    }
+
+    let number = ply / 2 + 1;
+
+    if ply.is_multiple_of(2) {
+        format!("{number}. {san}")
+    } else if starts_row {
+        format!("{number}... {san}")
+    } else {
+        san
+    }
+}
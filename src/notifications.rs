@@ -0,0 +1,112 @@
+use std::process::Command;
+
+use bevy::{prelude::*, tasks::IoTaskPool, window::PrimaryWindow, winit::WinitWindows};
+use chess_core::{get_board_after_moves, get_san};
+use fluent::fluent_args;
+use winit::window::UserAttentionType;
+
+use crate::{
+    locale::Localizer,
+    rules::{CurrentTurn, MoveHistory},
+    settings::Settings,
+    GameSet,
+};
+
+// Tells a player who went to another window that their opponent moved, by
+// a desktop notification and the window asking for attention in the
+// taskbar, each of which the settings can turn off
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(notify_opponent_moves.in_set(GameSet::Render));
+    }
+}
+
+fn notify_opponent_moves(
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    settings: Res<Settings>,
+    localizer: Res<Localizer>,
+    window: Query<(Entity, &Window), With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+    mut last_length: Local<usize>,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    // Takebacks and loaded games aren't moves, and only add none
+    let length = history.moves.len();
+    let added_move = length > *last_length && !history.is_added();
+    *last_length = length;
+    if !added_move {
+        return;
+    }
+
+    // Only the engine plays against the player so far
+    let mover = current_turn.0.opponent();
+    if settings.engine_opponent != Some(mover) {
+        return;
+    }
+
+    let Ok((entity, window)) = window.get_single() else {
+        return;
+    };
+    if window.focused {
+        return;
+    }
+
+    if settings.flash_taskbar {
+        if let Some(winit_window) = winit_windows.get_window(entity) {
+            winit_window.request_user_attention(Some(UserAttentionType::Informational));
+        }
+    }
+
+    if settings.move_notifications {
+        let (last_move, earlier_moves) = history.moves.split_last().unwrap();
+        let body = localizer.format(
+            "notification-opponent-moved",
+            &fluent_args![
+                "player" => mover.name(),
+                "move" => get_san(&get_board_after_moves(earlier_moves), last_move)
+            ],
+        );
+        show_notification(localizer.get("notification-title"), body);
+    }
+}
+
+// Through the desktop's own tool for it, off the main thread as it may be
+// slow to start. Windows has none to call, so only the taskbar flashes there
+fn show_notification(title: String, body: String) {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!(
+                "display notification {} with title {}",
+                quote_apple_script(&body),
+                quote_apple_script(&title)
+            ),
+        ]);
+        command
+    } else if cfg!(target_os = "windows") {
+        return;
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "Chess", &title, &body]);
+        command
+    };
+
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(err) = command.status() {
+                warn!("could not show a notification: {err}");
+            }
+        })
+        .detach();
+}
+
+fn quote_apple_script(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
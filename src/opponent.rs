@@ -0,0 +1,129 @@
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_core::{get_best_move, Board, EngineConfig, Move, Player};
+use futures_lite::future;
+
+use crate::{
+    analysis::ForkedGame,
+    explore::Exploration,
+    maze::MazeRun,
+    puzzles::PuzzleAttempt,
+    rules::{CurrentTurn, GameOver, MoveEvent, MoveHistory, ReplaceHistoryEvent, TakebackEvent},
+    save::ReplayPlayback,
+    settings::Settings,
+    GameSet,
+};
+
+// Plays the side named by the engine_opponent setting
+pub struct EngineOpponentPlugin;
+
+// The side the engine plays, which in a game played on from a finished
+// one was picked then rather than in the settings
+pub fn get_engine_player(settings: &Settings, forked: Option<&ForkedGame>) -> Option<Player> {
+    forked.map_or(settings.engine_opponent, |forked| Some(forked.engine))
+}
+
+impl Plugin for EngineOpponentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (finish_engine_search, start_engine_search)
+                .chain()
+                .in_set(GameSet::Rules),
+        )
+        // A takeback leaves the engine thinking about a position that's gone
+        .add_system(cancel_stale_engine_searches.in_set(GameSet::Render));
+    }
+}
+
+// Off the main thread, so deeper searches don't stall the frame
+#[derive(Component)]
+struct EngineSearch {
+    task: Task<Option<Move>>,
+    // The line searched from, as another line of the same length may have
+    // taken its place since
+    line: Vec<Move>,
+}
+
+fn start_engine_search(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    forked: Option<Res<ForkedGame>>,
+    board: Res<Board>,
+    current_turn: Res<CurrentTurn>,
+    history: Res<MoveHistory>,
+    game_over: Option<Res<GameOver>>,
+    replay: Option<Res<ReplayPlayback>>,
+    exploration: Option<Res<Exploration>>,
+    puzzle: Option<Res<PuzzleAttempt>>,
+    maze: Option<Res<MazeRun>>,
+    searches: Query<(), With<EngineSearch>>,
+) {
+    if get_engine_player(&settings, forked.as_deref()) != Some(current_turn.0)
+        || game_over.is_some()
+        || replay.is_some()
+        || exploration.is_some()
+        || puzzle.is_some()
+        || maze.is_some()
+        || !searches.is_empty()
+    {
+        return;
+    }
+
+    let board = *board;
+    let player = current_turn.0;
+    let task = AsyncComputeTaskPool::get()
+        .spawn(async move { get_best_move(&board, player, &EngineConfig::default()) });
+
+    commands.spawn(EngineSearch {
+        task,
+        line: history.moves.clone(),
+    });
+}
+
+fn finish_engine_search(
+    mut commands: Commands,
+    mut searches: Query<(Entity, &mut EngineSearch)>,
+    history: Res<MoveHistory>,
+    mut move_events: EventWriter<MoveEvent>,
+) {
+    for (entity, mut search) in searches.iter_mut() {
+        let Some(mv) = future::block_on(future::poll_once(&mut search.task)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+
+        if search.line != history.moves {
+            continue;
+        }
+
+        if let Some(mv) = mv {
+            debug!("the engine plays from {}", mv.from);
+            move_events.send(MoveEvent(mv));
+        }
+    }
+}
+
+// Dropping the task stops the search. Any takeback or replaced history
+// drops them all, whatever line ends up on the board
+fn cancel_stale_engine_searches(
+    mut commands: Commands,
+    searches: Query<(Entity, &EngineSearch)>,
+    history: Res<MoveHistory>,
+    mut takeback_events: EventReader<TakebackEvent>,
+    mut replace_events: EventReader<ReplaceHistoryEvent>,
+) {
+    let replaced = takeback_events.iter().count() + replace_events.iter().count() > 0;
+    if !replaced && !history.is_changed() {
+        return;
+    }
+
+    for (entity, search) in searches.iter() {
+        if replaced || search.line != history.moves {
+            debug!("dropping the engine's search of a position no longer on the board");
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::{
+    input::{HoveredPiece, Selection},
+    settings::Settings,
+    GameSet,
+};
+
+const OUTLINE_SHADER_PATH: &str = "shaders/piece_outline.wgsl";
+// How far the outline reaches past the piece, as a part of its size
+const OUTLINE_WIDTH: f32 = 0.08;
+const HOVER_OUTLINE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.6);
+
+// A glowing outline around the selected piece and the one under the mouse,
+// traced from the piece's own image, so it reads on any board colors
+pub struct PieceOutlinePlugin;
+
+impl Plugin for PieceOutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<OutlineMaterial>::default())
+            .add_startup_system(create_outline_mesh)
+            .add_systems(
+                (
+                    update_outlines.run_if(
+                        resource_changed::<Selection>()
+                            .or_else(resource_changed::<HoveredPiece>())
+                            .or_else(resource_changed::<Settings>()),
+                    ),
+                    fit_outlines,
+                )
+                    .chain()
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "667f23b2-ace4-4cb7-a10a-3f31cdeacd51"]
+struct OutlineMaterial {
+    // Linear, as the shader works in
+    #[uniform(0)]
+    color: Vec4,
+    // The piece's cell of the atlas in uv, from its top left to bottom right
+    #[uniform(0)]
+    rect: Vec4,
+    #[uniform(0)]
+    width: f32,
+    #[texture(1)]
+    #[sampler(2)]
+    texture: Handle<Image>,
+}
+
+impl Material2d for OutlineMaterial {
+    fn fragment_shader() -> ShaderRef {
+        OUTLINE_SHADER_PATH.into()
+    }
+}
+
+#[derive(Resource)]
+struct OutlineMesh(Handle<Mesh>);
+
+// Behind the piece it belongs to, as a child that moves along with it
+#[derive(Component)]
+struct PieceOutline;
+
+fn create_outline_mesh(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let mesh = meshes.add(shape::Quad::new(Vec2::ONE).into());
+    commands.insert_resource(OutlineMesh(mesh));
+}
+
+fn update_outlines(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    hovered_piece: Res<HoveredPiece>,
+    settings: Res<Settings>,
+    mesh: Res<OutlineMesh>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut materials: ResMut<Assets<OutlineMaterial>>,
+    pieces: Query<(&TextureAtlasSprite, &Handle<TextureAtlas>)>,
+    outlines: Query<Entity, With<PieceOutline>>,
+) {
+    for entity in outlines.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let selected = selection.piece();
+    let hovered = hovered_piece.0.filter(|&piece| Some(piece) != selected);
+    let outlined = [
+        (selected, settings.palette().selected_tile.with_a(1.0)),
+        (hovered, HOVER_OUTLINE_COLOR),
+    ];
+
+    for (piece, color) in outlined {
+        let Some(piece) = piece else {
+            continue;
+        };
+        let Ok((sprite, atlas)) = pieces.get(piece) else {
+            continue;
+        };
+        let Some(atlas) = texture_atlases.get(atlas) else {
+            continue;
+        };
+        let Some(cell) = atlas.textures.get(sprite.index) else {
+            continue;
+        };
+
+        let material = materials.add(OutlineMaterial {
+            color: Vec4::from(color.as_linear_rgba_f32()),
+            rect: Vec4::new(
+                cell.min.x / atlas.size.x,
+                cell.min.y / atlas.size.y,
+                cell.max.x / atlas.size.x,
+                cell.max.y / atlas.size.y,
+            ),
+            width: OUTLINE_WIDTH,
+            texture: atlas.texture.clone(),
+        });
+
+        let outline = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(mesh.0.clone()),
+                    material,
+                    ..default()
+                },
+                PieceOutline,
+            ))
+            .id();
+        commands.entity(piece).add_child(outline);
+    }
+}
+
+// Sized to the piece's sprite, which stands taller in the isometric view
+fn fit_outlines(
+    pieces: Query<&TextureAtlasSprite>,
+    mut outlines: Query<(&Parent, &mut Transform), With<PieceOutline>>,
+) {
+    for (parent, mut transform) in outlines.iter_mut() {
+        let Ok(sprite) = pieces.get(parent.get()) else {
+            continue;
+        };
+        let sprite_size = sprite.custom_size.unwrap_or(Vec2::ONE);
+        let size = sprite_size * (1.0 + 2.0 * OUTLINE_WIDTH);
+        let offset = -sprite.anchor.as_vec() * sprite_size;
+
+        // Just behind the piece
+        let fitted = Transform::from_translation(offset.extend(-0.01)).with_scale(size.extend(1.0));
+        if *transform != fitted {
+            *transform = fitted;
+        }
+    }
+}
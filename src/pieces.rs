@@ -0,0 +1,236 @@
+use std::path::Path;
+
+use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use chess_core::{get_starting_pieces, Board, BoardPosition, Piece, Player};
+
+use crate::{
+    board::{BoardRoot, PIECE_SIZE, PIECE_Z_INDEX},
+    settings::Settings,
+    GameState,
+};
+
+const FONT_PATH: &str = "fonts/DejaVuSans.ttf";
+
+pub struct PiecesPlugin;
+
+impl Plugin for PiecesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Piece>()
+            .register_type::<Player>()
+            .register_type::<BoardPosition>()
+            .insert_resource(BoardSetup(get_starting_pieces()))
+            .add_startup_system(load_assets)
+            .add_system(wait_for_assets.run_if(in_state(GameState::Loading)))
+            .add_system(populate_board.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(change_piece_atlas.run_if(resource_changed::<Settings>()))
+            .add_system(fit_piece_atlas_to_image.after(change_piece_atlas));
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct GameAssets {
+    pub piece_atlas: Handle<TextureAtlas>,
+    pub pieces: HashMap<Piece, usize>,
+    pub font: Handle<Font>,
+}
+
+#[derive(Resource)]
+pub struct BoardSetup(pub Vec<(Piece, Player, BoardPosition)>);
+
+fn load_assets(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    settings: Res<Settings>,
+) {
+    let piece_atlas = TextureAtlas::from_grid(
+        assets.load(settings.piece_atlas.as_str()),
+        Vec2::splat(PIECE_SIZE as f32),
+        6,
+        2,
+        None,
+        None,
+    );
+
+    commands.insert_resource(GameAssets {
+        piece_atlas: texture_atlases.add(piece_atlas),
+        pieces: HashMap::from([
+            (Piece::King, 0),
+            (Piece::Queen, 1),
+            (Piece::Knight, 2),
+            (Piece::Pawn, 3),
+            (Piece::Bishop, 4),
+            (Piece::Rook, 5),
+        ]),
+        font: assets.load(FONT_PATH),
+    });
+}
+
+// Swaps in another image when the setting changes. The atlas keeps its
+// handle, so pieces already on the board switch over too
+fn change_piece_atlas(
+    assets: Res<AssetServer>,
+    settings: Res<Settings>,
+    images: Res<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    game_assets: Option<Res<GameAssets>>,
+) {
+    let Some(game_assets) = game_assets else {
+        return;
+    };
+    let Some(atlas) = texture_atlases.get(&game_assets.piece_atlas) else {
+        return;
+    };
+
+    let current_path = assets.get_handle_path(&atlas.texture);
+    if current_path.is_some_and(|path| path.path() == Path::new(&settings.piece_atlas)) {
+        return;
+    }
+
+    // An image still loading gets sized by fit_piece_atlas_to_image
+    let texture: Handle<Image> = assets.load(settings.piece_atlas.as_str());
+    let tile_size = images
+        .get(&texture)
+        .map_or(Vec2::splat(PIECE_SIZE as f32), |image| {
+            image.size() / Vec2::new(6.0, 2.0)
+        });
+
+    let atlas = TextureAtlas::from_grid(texture, tile_size, 6, 2, None, None);
+    texture_atlases.set_untracked(&game_assets.piece_atlas, atlas);
+}
+
+// The atlas can be drawn at any resolution, e.g. twice the board's for
+// HiDPI screens, so size its grid from the image rather than PIECE_SIZE
+fn fit_piece_atlas_to_image(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    game_assets: Res<GameAssets>,
+) {
+    let Some(atlas) = texture_atlases.get(&game_assets.piece_atlas) else {
+        return;
+    };
+
+    let texture = atlas.texture.clone();
+    let loaded = image_events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => *handle == texture,
+        AssetEvent::Removed { .. } => false,
+    });
+
+    let Some(image) = images.get(&texture).filter(|_| loaded) else {
+        return;
+    };
+
+    let tile_size = image.size() / Vec2::new(6.0, 2.0);
+
+    if atlas.textures.first().map(|rect| rect.size()) != Some(tile_size) {
+        let atlas = TextureAtlas::from_grid(texture, tile_size, 6, 2, None, None);
+        texture_atlases.set_untracked(&game_assets.piece_atlas, atlas);
+    }
+}
+
+fn wait_for_assets(
+    assets: Res<AssetServer>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    game_assets: Res<GameAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(atlas) = texture_atlases.get(&game_assets.piece_atlas) else {
+        return;
+    };
+
+    match assets.get_group_load_state([atlas.texture.id(), game_assets.font.id()]) {
+        LoadState::Loaded => next_state.set(GameState::Playing),
+        // Play on without them rather than hang on the loading state
+        LoadState::Failed => {
+            warn!("could not load the piece images or font");
+            next_state.set(GameState::Playing);
+        }
+        _ => {}
+    }
+}
+
+// Replaces whatever is on the board, so entering Playing again restarts
+fn populate_board(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    board_setup: Res<BoardSetup>,
+    mut board: ResMut<Board>,
+    pieces: Query<Entity, With<Piece>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    let Ok(board_root) = board_root.get_single() else {
+        warn!("no board to put the pieces on");
+        return;
+    };
+
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    spawn_pieces(
+        &mut commands,
+        &game_assets,
+        board_root,
+        board_setup.0.iter().copied(),
+    );
+
+    *board = Board::from_pieces(&board_setup.0);
+}
+
+pub fn spawn_pieces(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    board_root: Entity,
+    pieces: impl Iterator<Item = (Piece, Player, BoardPosition)>,
+) {
+    for (piece_type, player, position) in pieces {
+        let index = get_atlas_index(game_assets, piece_type, player);
+        let piece = spawn_piece(
+            piece_type,
+            player,
+            position.x,
+            position.y,
+            game_assets.piece_atlas.clone(),
+            index,
+            commands,
+        );
+        commands.entity(board_root).add_child(piece);
+    }
+}
+
+// White's pieces are on the top row of the atlas, Black's below
+pub fn get_atlas_index(game_assets: &GameAssets, piece_type: Piece, player: Player) -> usize {
+    match player {
+        Player::White => game_assets.pieces[&piece_type],
+        Player::Black => game_assets.pieces[&piece_type] + 6,
+    }
+}
+
+fn spawn_piece(
+    piece_type: Piece,
+    player: Player,
+    x: i32,
+    y: i32,
+    texture_atlas: Handle<TextureAtlas>,
+    index: usize,
+    commands: &mut Commands,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                    index,
+                    ..default()
+                },
+                texture_atlas,
+                transform: Transform::from_xyz(0.0, 0.0, PIECE_Z_INDEX),
+                ..default()
+            },
+            piece_type,
+            player,
+            BoardPosition::new(x, y),
+        ))
+        .id()
+}
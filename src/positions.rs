@@ -0,0 +1,86 @@
+// Prints random legal positions as FEN, one per line, for training,
+// endgame practice and seeding fuzz corpora. Started with
+// --random-position, optionally with the material as in
+// --random-position=QRrp (the kings are always there)
+
+use chess_core::{get_position_fen, get_random_position, parse_material, Piece, Player};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub struct RandomPositions {
+    pub material: Vec<(Piece, Player)>,
+    pub count: u32,
+    pub seed: Option<u64>,
+}
+
+impl RandomPositions {
+    // None unless --random-position is given. --count=N sets how many and
+    // --seed=N makes them the same every run
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let material = args.iter().find_map(|arg| {
+            if arg == "--random-position" {
+                Some("QRBNPPPqrbnppp")
+            } else {
+                arg.strip_prefix("--random-position=")
+            }
+        })?;
+
+        let Some(material) = parse_material(material) else {
+            eprintln!("{material:?} is not material like QRrp, without the kings");
+            std::process::exit(1);
+        };
+
+        let mut random_positions = Self {
+            material,
+            count: 1,
+            seed: None,
+        };
+
+        for arg in &args {
+            let Some((name, value)) = arg.split_once('=') else {
+                continue;
+            };
+
+            let parsed = match name {
+                "--count" => value
+                    .parse()
+                    .map(|value| random_positions.count = value)
+                    .is_ok(),
+                "--seed" => value
+                    .parse()
+                    .map(|value| random_positions.seed = Some(value))
+                    .is_ok(),
+                _ => true,
+            };
+
+            if !parsed {
+                eprintln!("{name} needs a whole number, not {value:?}");
+                std::process::exit(1);
+            }
+        }
+
+        Some(random_positions)
+    }
+}
+
+pub fn print_random_positions(random_positions: RandomPositions) {
+    let mut rng = match random_positions.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    for _ in 0..random_positions.count {
+        let player = if rng.gen() {
+            Player::White
+        } else {
+            Player::Black
+        };
+
+        let Some(board) = get_random_position(&random_positions.material, player, &mut rng) else {
+            eprintln!("could not find a legal position with that material");
+            std::process::exit(1);
+        };
+
+        println!("{}", get_position_fen(&board, player));
+    }
+}
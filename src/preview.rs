@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use chess_core::{get_principal_variation, Board, EngineConfig, Move, Piece, Player};
+
+use crate::{
+    analysis::ViewedPly,
+    board::{BoardRoot, GHOST_Z_INDEX, PIECE_SIZE},
+    input::{Action, Actions},
+    pieces::{get_atlas_index, GameAssets},
+    settings::Settings,
+    GameSet,
+};
+
+// Plies of the engine's line that are played out
+const PREVIEW_PLIES: usize = 6;
+// Each ghost slides to its square over this long, then the next one starts
+const STEP_SECONDS: f32 = 0.8;
+const GHOST_ALPHA: f32 = 0.5;
+
+// Plays the engine's best line from the position shown after the game as
+// see-through ghost pieces over the board, over and over until turned off
+pub struct LinePreviewPlugin;
+
+impl Plugin for LinePreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            toggle_line_preview
+                .run_if(resource_exists::<ViewedPly>())
+                .in_set(GameSet::Input),
+        )
+        .add_system(end_stale_line_preview.run_if(resource_exists::<LinePreview>()))
+        .add_system(
+            animate_line_preview
+                .run_if(resource_exists::<LinePreview>())
+                .in_set(GameSet::Render),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct LinePreview {
+    // The ply the line starts from
+    ply: usize,
+    // Each move of the line, with the piece that makes it
+    steps: Vec<(Piece, Player, Move)>,
+    step: usize,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct Ghost {
+    step: usize,
+    from: Vec2,
+    to: Vec2,
+}
+
+fn toggle_line_preview(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    board: Res<Board>,
+    viewed_ply: Res<ViewedPly>,
+    preview: Option<Res<LinePreview>>,
+    ghosts: Query<Entity, With<Ghost>>,
+) {
+    if !actions.just_pressed(Action::PreviewLine) {
+        return;
+    }
+
+    if preview.is_some() {
+        stop_line_preview(commands, ghosts);
+        return;
+    }
+
+    let player = if viewed_ply.0.is_multiple_of(2) {
+        Player::White
+    } else {
+        Player::Black
+    };
+
+    let mut board = *board;
+    let steps = get_principal_variation(&board, player, &EngineConfig::default(), PREVIEW_PLIES)
+        .into_iter()
+        .filter_map(|mv| {
+            let (piece_type, owner) = board.get(mv.from)?;
+            board.apply_move(&mv);
+            Some((piece_type, owner, mv))
+        })
+        .collect();
+
+    commands.insert_resource(LinePreview {
+        ply: viewed_ply.0,
+        steps,
+        step: 0,
+        timer: Timer::from_seconds(STEP_SECONDS, TimerMode::Repeating),
+    });
+}
+
+fn stop_line_preview(mut commands: Commands, ghosts: Query<Entity, With<Ghost>>) {
+    commands.remove_resource::<LinePreview>();
+
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Another position was picked, or the game was taken back
+fn end_stale_line_preview(
+    commands: Commands,
+    preview: Res<LinePreview>,
+    viewed_ply: Option<Res<ViewedPly>>,
+    ghosts: Query<Entity, With<Ghost>>,
+) {
+    if viewed_ply.is_some_and(|viewed_ply| viewed_ply.0 == preview.ply) {
+        return;
+    }
+
+    stop_line_preview(commands, ghosts);
+}
+
+fn animate_line_preview(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    game_assets: Res<GameAssets>,
+    mut preview: ResMut<LinePreview>,
+    mut ghosts: Query<(Entity, &Ghost, &mut Transform)>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    preview.timer.tick(time.delta());
+    // Still a step apart, but each ghost appears on its square
+    let progress = if settings.reduced_motion {
+        1.0
+    } else {
+        preview.timer.percent()
+    };
+
+    for (_, ghost, mut transform) in ghosts.iter_mut() {
+        let position = if ghost.step == preview.step {
+            ghost.from.lerp(ghost.to, progress)
+        } else {
+            ghost.to
+        };
+        transform.translation = position.extend(GHOST_Z_INDEX);
+    }
+
+    if preview.timer.just_finished() {
+        preview.step += 1;
+
+        // A step's pause after the last move, then from the start again
+        if preview.step > preview.steps.len() {
+            preview.step = 0;
+            for (entity, _, _) in ghosts.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    // Despawned ghosts linger until the end of the frame, so a new round's
+    // first ghost waits a frame for the last round's to go
+    let step = preview.step;
+    let Some(&(piece_type, player, mv)) = preview.steps.get(step) else {
+        return;
+    };
+    if ghosts.iter().any(|(_, ghost, _)| ghost.step == step) {
+        return;
+    }
+
+    let square_center = |x: i32, y: i32| {
+        Vec2::new(
+            (x * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+            (y * PIECE_SIZE + PIECE_SIZE / 2) as f32,
+        )
+    };
+    let from = square_center(mv.from.0, mv.from.1);
+
+    let ghost = commands
+        .spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(Vec2::splat(PIECE_SIZE as f32)),
+                    index: get_atlas_index(&game_assets, piece_type, player),
+                    color: Color::WHITE.with_a(GHOST_ALPHA),
+                    ..default()
+                },
+                texture_atlas: game_assets.piece_atlas.clone(),
+                transform: Transform::from_translation(from.extend(GHOST_Z_INDEX)),
+                ..default()
+            },
+            Ghost {
+                step,
+                from,
+                to: square_center(mv.to.0, mv.to.1),
+            },
+        ))
+        .id();
+    commands.entity(board_root).add_child(ghost);
+}
@@ -0,0 +1,297 @@
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_core::{get_puzzle, is_blunder, EngineConfig, Puzzle};
+use fluent::fluent_args;
+use futures_lite::future;
+
+use crate::{
+    analysis::{EvalScores, ViewedPly},
+    explore::Exploration,
+    input::{Action, Actions},
+    locale::Localizer,
+    maze::MazeRun,
+    pieces::GameAssets,
+    rules::{MoveEvent, MoveHistory, ReplaceHistoryEvent, TakebackEvent},
+    settings::{read_ron_file, write_ron_file},
+    toast::Toast,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+const PUZZLES_FILE_NAME: &str = "puzzles.ron";
+
+// A tactics set made of the player's own mistakes. After a game, the
+// position a blunder left can be kept as a puzzle, with the engine's
+// refutation as its solution. In puzzle mode the player finds the
+// solver's moves and the replies are played for them, while the game is
+// kept aside as when exploring
+pub struct PuzzlesPlugin;
+
+impl Plugin for PuzzlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            save_puzzle
+                .run_if(resource_exists::<ViewedPly>())
+                .in_set(GameSet::Input),
+        )
+        .add_system(finish_puzzle_searches)
+        .add_system(switch_puzzles.in_set(GameSet::Input))
+        .add_system(
+            check_puzzle_moves
+                .run_if(resource_exists::<PuzzleAttempt>())
+                .run_if(resource_changed::<MoveHistory>())
+                .in_set(GameSet::Apply),
+        )
+        .add_system(
+            update_puzzle_banner
+                .run_if(
+                    resource_exists_and_changed::<PuzzleAttempt>()
+                        .or_else(resource_removed::<PuzzleAttempt>())
+                        .or_else(resource_changed::<Localizer>())
+                        .or_else(resource_changed::<UiTheme>()),
+                )
+                .in_set(GameSet::Render),
+        );
+    }
+}
+
+// Present in puzzle mode
+#[derive(Resource)]
+pub struct PuzzleAttempt {
+    // The game as it was before the puzzles
+    pub game: MoveHistory,
+    pub puzzle: Puzzle,
+    // Of the saved puzzles, counted from 0
+    pub index: usize,
+    pub count: usize,
+    pub solved: bool,
+}
+
+// The refutation of a blunder, worked out off the main thread
+#[derive(Component)]
+struct PuzzleSearch(Task<Option<Puzzle>>);
+
+#[derive(Component)]
+struct PuzzleBanner;
+
+fn save_puzzle(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    viewed_ply: Res<ViewedPly>,
+    history: Res<MoveHistory>,
+    scores: Option<Res<EvalScores>>,
+    searches: Query<(), With<PuzzleSearch>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !actions.just_pressed(Action::SavePuzzle) || !searches.is_empty() {
+        return;
+    }
+
+    // Blunders are told from the evaluation graph
+    let Some(scores) = scores else {
+        toasts.send(Toast::new("toast-puzzle-not-ready"));
+        return;
+    };
+    if !is_blunder(&scores.0, viewed_ply.0) {
+        toasts.send(Toast::new("toast-not-a-blunder"));
+        return;
+    }
+
+    let moves = history.moves[..viewed_ply.0].to_vec();
+    let task = AsyncComputeTaskPool::get()
+        .spawn(async move { get_puzzle(&moves, &EngineConfig::default()) });
+    commands.spawn(PuzzleSearch(task));
+}
+
+fn finish_puzzle_searches(
+    mut commands: Commands,
+    mut searches: Query<(Entity, &mut PuzzleSearch)>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (entity, mut search) in searches.iter_mut() {
+        let Some(puzzle) = future::block_on(future::poll_once(&mut search.0)) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+
+        let result = puzzle
+            .ok_or("the game is over there".to_string())
+            .and_then(add_puzzle);
+        match result {
+            Ok(count) => {
+                info!("saved puzzle {count}");
+                toasts.send(Toast::new("toast-puzzle-saved").with_arg("count", count));
+            }
+            Err(err) => {
+                warn!("could not save the puzzle: {err}");
+                toasts.send(Toast::new("toast-puzzle-failed"));
+            }
+        }
+    }
+}
+
+// Returns how many puzzles there are now. The same position isn't kept
+// twice
+fn add_puzzle(puzzle: Puzzle) -> Result<usize, String> {
+    let path = get_puzzles_path().ok_or("there is no data directory")?;
+    let mut puzzles = read_puzzles()?;
+
+    if !puzzles.iter().any(|saved| saved.moves == puzzle.moves) {
+        puzzles.push(puzzle);
+        write_ron_file(&path, &puzzles)?;
+    }
+
+    Ok(puzzles.len())
+}
+
+// None saved yet is no error
+fn read_puzzles() -> Result<Vec<Puzzle>, String> {
+    let path = get_puzzles_path().ok_or("there is no data directory")?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    read_ron_file(&path)
+}
+
+fn get_puzzles_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("chess").join(PUZZLES_FILE_NAME))
+}
+
+// Goes on to the next puzzle, starting puzzle mode from a game, or back to
+// the game
+fn switch_puzzles(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    attempt: Option<Res<PuzzleAttempt>>,
+    exploration: Option<Res<Exploration>>,
+    maze: Option<Res<MazeRun>>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if actions.just_pressed(Action::LeavePuzzles) {
+        if let Some(attempt) = attempt {
+            info!("back to the game");
+            replace_events.send(ReplaceHistoryEvent(attempt.game.clone()));
+            commands.remove_resource::<PuzzleAttempt>();
+        }
+        return;
+    }
+
+    if !actions.just_pressed(Action::NextPuzzle) || exploration.is_some() || maze.is_some() {
+        return;
+    }
+
+    let puzzles = match read_puzzles() {
+        Ok(puzzles) => puzzles,
+        Err(err) => {
+            warn!("could not read the puzzles: {err}");
+            toasts.send(Toast::new("toast-puzzles-failed"));
+            return;
+        }
+    };
+    if puzzles.is_empty() {
+        toasts.send(Toast::new("toast-no-puzzles"));
+        return;
+    }
+
+    let (index, game) = match attempt {
+        Some(attempt) => ((attempt.index + 1) % puzzles.len(), attempt.game.clone()),
+        None => (0, history.clone()),
+    };
+    let puzzle = puzzles[index].clone();
+    info!("puzzle {} of {}", index + 1, puzzles.len());
+
+    replace_events.send(ReplaceHistoryEvent(MoveHistory {
+        moves: puzzle.moves.clone(),
+        times: vec![0.0; puzzle.moves.len()],
+        ..default()
+    }));
+    commands.insert_resource(PuzzleAttempt {
+        game,
+        puzzle,
+        index,
+        count: puzzles.len(),
+        solved: false,
+    });
+}
+
+// A move off the solution is taken back, and one on it is answered with
+// the next move of the solution
+fn check_puzzle_moves(
+    mut attempt: ResMut<PuzzleAttempt>,
+    history: Res<MoveHistory>,
+    mut move_events: EventWriter<MoveEvent>,
+    mut takeback_events: EventWriter<TakebackEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if attempt.solved {
+        return;
+    }
+    let Some(played) = history.moves.strip_prefix(attempt.puzzle.moves.as_slice()) else {
+        return;
+    };
+    if played.is_empty() {
+        return;
+    }
+
+    let solution = &attempt.puzzle.solution;
+    if !solution.starts_with(played) {
+        toasts.send(Toast::new("toast-puzzle-wrong-move"));
+        takeback_events.send(TakebackEvent(1));
+    } else if played.len() == solution.len() {
+        toasts.send(Toast::new("toast-puzzle-solved"));
+        attempt.solved = true;
+    } else if !played.len().is_multiple_of(2) {
+        move_events.send(MoveEvent(solution[played.len()]));
+    }
+}
+
+fn update_puzzle_banner(
+    mut commands: Commands,
+    attempt: Option<Res<PuzzleAttempt>>,
+    localizer: Res<Localizer>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    banners: Query<Entity, With<PuzzleBanner>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(attempt), Some(game_assets), Ok(status_bar)) =
+        (attempt, game_assets, status_bars.get_single())
+    else {
+        return;
+    };
+
+    let text = localizer.format(
+        if attempt.solved {
+            "puzzle-solved-banner"
+        } else {
+            "puzzle-banner"
+        },
+        &fluent_args![
+            "number" => attempt.index + 1,
+            "count" => attempt.count,
+            "player" => attempt.puzzle.player().name()
+        ],
+    );
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Mode,
+        &game_assets,
+        &theme,
+        PuzzleBanner,
+    );
+}
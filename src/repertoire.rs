@@ -0,0 +1,241 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    a11y::{
+        accesskit::{NodeBuilder, Role},
+        AccessibilityNode,
+    },
+    prelude::*,
+};
+use chess_core::{get_board_after_moves, get_san, read_pgn, Move, Player, Repertoire};
+use fluent::fluent_args;
+
+use crate::{
+    analysis::ViewedPly,
+    explore::Exploration,
+    locale::Localizer,
+    pieces::GameAssets,
+    puzzles::PuzzleAttempt,
+    rules::MoveHistory,
+    settings::Settings,
+    toast::Toast,
+    ui_theme::{ThemedBackground, UiTheme},
+    GameSet,
+};
+
+const REPERTOIRE_FILE_NAME: &str = "repertoire.pgn";
+
+// Opening lines the player means to stick to, imported from a PGN file
+// with --repertoire=<file.pgn>, which is kept so later games use it too.
+// Leaving them during a game only shows a small note under the status
+// area, so as not to get in the way. In the analysis after the game, the
+// note says which move left them and what the book had instead
+pub struct RepertoirePlugin;
+
+impl Plugin for RepertoirePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_repertoire)
+            .add_system(
+                check_repertoire
+                    .run_if(resource_exists::<OpeningRepertoire>())
+                    .run_if(resource_changed::<MoveHistory>())
+                    .in_set(GameSet::Apply),
+            )
+            .add_system(
+                update_repertoire_note
+                    .run_if(
+                        resource_exists_and_changed::<RepertoireDeviation>()
+                            .or_else(resource_removed::<RepertoireDeviation>())
+                            .or_else(resource_added::<ViewedPly>())
+                            .or_else(resource_removed::<ViewedPly>())
+                            .or_else(resource_changed::<Localizer>())
+                            .or_else(resource_changed::<UiTheme>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+// Present once a repertoire has been imported
+#[derive(Resource)]
+pub struct OpeningRepertoire(pub Repertoire);
+
+// Present while the game has left the repertoire through a move of the
+// player's. The engine's moves aren't theirs to know
+#[derive(Resource)]
+pub struct RepertoireDeviation {
+    pub ply: usize,
+    // What the repertoire has there instead
+    pub book: Vec<Move>,
+}
+
+#[derive(Component)]
+struct RepertoireNote;
+
+fn load_repertoire(mut commands: Commands, mut toasts: EventWriter<Toast>) {
+    let import = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--repertoire=").map(PathBuf::from));
+
+    let result = match import {
+        Some(path) => import_repertoire(&path).map_err(|err| {
+            warn!("could not import the repertoire {}: {err}", path.display());
+            toasts.send(Toast::new("toast-repertoire-failed"));
+        }),
+        // None imported yet is no error
+        None => match get_repertoire_path().filter(|path| path.exists()) {
+            Some(path) => fs::read_to_string(&path).map_err(|err| {
+                warn!("could not read the repertoire {}: {err}", path.display());
+                toasts.send(Toast::new("toast-repertoire-failed"));
+            }),
+            None => return,
+        },
+    };
+    let Ok(pgn) = result else {
+        return;
+    };
+
+    let games = read_pgn(&pgn);
+    let repertoire = Repertoire::from_pgn(&games);
+    if repertoire.is_empty() {
+        warn!("the repertoire has no moves");
+        return;
+    }
+
+    info!("read a repertoire of {} lines", games.len());
+    commands.insert_resource(OpeningRepertoire(repertoire));
+}
+
+// Returns the PGN, after keeping a copy of it where later games find it
+fn import_repertoire(path: &Path) -> Result<String, String> {
+    let pgn = fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+    let kept_path = get_repertoire_path().ok_or("there is no data directory")?;
+    kept_path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&kept_path, &pgn))
+        .map_err(|err| err.to_string())?;
+    info!("kept the repertoire at {}", kept_path.display());
+
+    Ok(pgn)
+}
+
+fn get_repertoire_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("chess").join(REPERTOIRE_FILE_NAME))
+}
+
+// Explored lines and puzzles aren't the game, which is checked again on
+// the way back to it
+fn check_repertoire(
+    mut commands: Commands,
+    repertoire: Res<OpeningRepertoire>,
+    history: Res<MoveHistory>,
+    settings: Res<Settings>,
+    deviation: Option<Res<RepertoireDeviation>>,
+    exploration: Option<Res<Exploration>>,
+    attempt: Option<Res<PuzzleAttempt>>,
+) {
+    if exploration.is_some() || attempt.is_some() {
+        return;
+    }
+
+    let players: Vec<Player> = [Player::White, Player::Black]
+        .into_iter()
+        .filter(|player| settings.engine_opponent != Some(*player))
+        .collect();
+
+    match repertoire.0.find_deviation(&history.moves, &players) {
+        Some(ply) => {
+            // Only the first way out counts, and it stays until taken back
+            if deviation.is_some_and(|deviation| deviation.ply == ply) {
+                return;
+            }
+            info!("left the repertoire at ply {ply}");
+            commands.insert_resource(RepertoireDeviation {
+                ply,
+                book: repertoire.0.get_book_moves(&history.moves[..ply]).to_vec(),
+            });
+        }
+        None => {
+            if deviation.is_some() {
+                commands.remove_resource::<RepertoireDeviation>();
+            }
+        }
+    }
+}
+
+fn update_repertoire_note(
+    mut commands: Commands,
+    deviation: Option<Res<RepertoireDeviation>>,
+    viewed_ply: Option<Res<ViewedPly>>,
+    history: Res<MoveHistory>,
+    localizer: Res<Localizer>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    notes: Query<Entity, With<RepertoireNote>>,
+) {
+    for entity in notes.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(deviation), Some(game_assets)) = (deviation, game_assets) else {
+        return;
+    };
+
+    let text = if viewed_ply.is_some() {
+        let board = get_board_after_moves(&history.moves[..deviation.ply]);
+        let number = deviation.ply / 2 + 1;
+        let san = get_san(&board, &history.moves[deviation.ply]);
+        let played = if deviation.ply.is_multiple_of(2) {
+            format!("{number}. {san}")
+        } else {
+            format!("{number}... {san}")
+        };
+        let book = deviation
+            .book
+            .iter()
+            .map(|mv| get_san(&board, mv))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        localizer.format(
+            "repertoire-left-book",
+            &fluent_args!["move" => played, "book" => book],
+        )
+    } else {
+        localizer.get("repertoire-left")
+    };
+
+    let mut status_node = NodeBuilder::new(Role::Status);
+    status_node.set_name(text.clone());
+
+    // Under the status area, in its size
+    commands.spawn((
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font: game_assets.font.clone(),
+                font_size: 12.0,
+                color: theme.text,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(8.0),
+                top: Val::Px(80.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        })
+        .with_background_color(theme.panel),
+        ThemedBackground(|theme| theme.panel),
+        AccessibilityNode::from(status_node),
+        RepertoireNote,
+    ));
+}
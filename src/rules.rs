@@ -0,0 +1,329 @@
+use bevy::{
+    diagnostic::Diagnostics,
+    prelude::*,
+    utils::{HashMap, Instant},
+};
+use chess_core::{
+    get_board_after_moves, get_game_result, get_legal_moves, is_promotion, Board, BoardPosition,
+    GameResult, Move, Piece, Player, Square, PROMOTION_PIECES,
+};
+
+use crate::{
+    adjourn::SealingMove,
+    board::BoardRoot,
+    diagnostics::POSSIBLE_MOVES_TIME,
+    hand_and_brain::BrainCall,
+    input::Selection,
+    maze::MazeRun,
+    pieces::{get_atlas_index, spawn_pieces, GameAssets},
+    variations::{MoveTree, NodeId},
+    GameSet, GameState,
+};
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CurrentTurn(Player::White))
+            .insert_resource(MoveHistory::default())
+            .init_resource::<Board>()
+            .init_resource::<PossibleMoves>()
+            .insert_resource(GameTime::default())
+            .add_event::<MoveEvent>()
+            .add_event::<TakebackEvent>()
+            .add_event::<ReplaceHistoryEvent>()
+            .add_system(
+                advance_game_time
+                    .in_schedule(CoreSchedule::FixedUpdate)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                (
+                    // A maze takes the moves itself, and puts the game back
+                    // when left. A sealed move is kept off the board
+                    apply_takebacks.run_if(not(resource_exists::<MazeRun>())),
+                    apply_moves
+                        .run_if(not(resource_exists::<MazeRun>()))
+                        .run_if(not(resource_exists::<SealingMove>())),
+                    update_possible_moves.run_if(
+                        resource_changed::<Board>()
+                            .or_else(resource_exists_and_changed::<BrainCall>())
+                            .or_else(resource_removed::<BrainCall>())
+                            .or_else(resource_exists_and_changed::<MazeRun>()),
+                    ),
+                    detect_game_over.run_if(resource_changed::<MoveHistory>()),
+                )
+                    .chain()
+                    .in_set(GameSet::Apply),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct CurrentTurn(pub Player);
+
+pub struct MoveEvent(pub Move);
+
+// Undoes this many of the last moves
+pub struct TakebackEvent(pub usize);
+
+// Puts back a game kept aside, such as the one left to explore another line
+pub struct ReplaceHistoryEvent(pub MoveHistory);
+
+// The line on the board, with every other line played in the game
+#[derive(Resource, Default, Clone)]
+pub struct MoveHistory {
+    pub moves: Vec<Move>,
+    // Game time of each move, in seconds since the game started
+    pub times: Vec<f64>,
+    pub tree: MoveTree,
+}
+
+impl MoveHistory {
+    // Where the next move branches from. A history set without its tree,
+    // as by loading a save, gets one from its moves
+    fn get_current_node(&mut self) -> Option<NodeId> {
+        let node = self.tree.find(&self.moves);
+        if node.is_none() && !self.moves.is_empty() {
+            self.tree = MoveTree::from_line(&self.moves, &self.times);
+            return self.tree.find(&self.moves);
+        }
+
+        node
+    }
+}
+
+// Present once the game has ended, after which no more moves are taken
+#[derive(Resource)]
+pub struct GameOver(pub GameResult);
+
+// Seconds of play, counted in fixed ticks rather than frames
+#[derive(Resource, Default)]
+pub struct GameTime(pub f64);
+
+// The legal moves of every piece on the board, by the square it stands on
+#[derive(Resource, Default)]
+pub struct PossibleMoves(HashMap<Square, Vec<Square>>);
+
+impl PossibleMoves {
+    pub fn get(&self, square: Square) -> &[Square] {
+        self.0.get(&square).map_or(&[], Vec::as_slice)
+    }
+}
+
+fn advance_game_time(mut game_time: ResMut<GameTime>, fixed_time: Res<FixedTime>) {
+    game_time.0 += fixed_time.period.as_secs_f64();
+}
+
+fn apply_moves(
+    mut commands: Commands,
+    mut move_events: EventReader<MoveEvent>,
+    mut takeback_events: EventReader<TakebackEvent>,
+    mut replace_events: EventReader<ReplaceHistoryEvent>,
+    mut pieces: Query<(
+        Entity,
+        &mut BoardPosition,
+        &mut Piece,
+        &Player,
+        Option<&mut TextureAtlasSprite>,
+    )>,
+    mut selection: ResMut<Selection>,
+    mut current_turn: ResMut<CurrentTurn>,
+    mut history: ResMut<MoveHistory>,
+    mut board: ResMut<Board>,
+    game_time: Res<GameTime>,
+    game_assets: Option<Res<GameAssets>>,
+) {
+    // Picked on a board that apply_takebacks has just replaced
+    if takeback_events.iter().count() + replace_events.iter().count() > 0 {
+        for MoveEvent(mv) in move_events.iter() {
+            debug!("dropping the move from {}, made before a takeback", mv.from);
+        }
+        return;
+    }
+
+    // Despawns are deferred, so skip pieces captured earlier this frame
+    let mut captured_pieces = Vec::new();
+
+    for MoveEvent(mv) in move_events.iter() {
+        let _span = info_span!("apply_move", from = %mv.from, to = %mv.to).entered();
+
+        // Moves come from saves, studies and the engine as well as the
+        // player, so one that doesn't fit the board is dropped before it
+        // can put the board and the history at odds
+        if !is_legal_move(&board, current_turn.0, mv) {
+            warn!("dropping the illegal move from {} to {}", mv.from, mv.to);
+            continue;
+        }
+
+        let mut moving_piece = None;
+
+        for (entity, position, ..) in pieces.iter() {
+            if captured_pieces.contains(&entity) {
+                continue;
+            }
+
+            if position.square() == mv.to {
+                debug!("capturing the piece on {}", mv.to);
+                captured_pieces.push(entity);
+                commands.entity(entity).despawn_recursive();
+            } else if position.square() == mv.from {
+                moving_piece = Some(entity);
+            }
+        }
+
+        let Some(moving_piece) = moving_piece else {
+            warn!("no piece to move from {:?}", mv.from);
+            continue;
+        };
+
+        let (_, mut position, mut piece_type, player, sprite) =
+            pieces.get_mut(moving_piece).unwrap();
+        position.x = mv.to.0;
+        position.y = mv.to.1;
+
+        if let Some(promotion) = mv.promotion {
+            debug!("promoting to a {}", promotion.name());
+            *piece_type = promotion;
+            if let (Some(mut sprite), Some(game_assets)) = (sprite, &game_assets) {
+                sprite.index = get_atlas_index(game_assets, promotion, *player);
+            }
+        }
+
+        board.apply_move(mv);
+        let parent = history.get_current_node();
+        history.tree.add_move(parent, *mv, game_time.0);
+        history.moves.push(*mv);
+        history.times.push(game_time.0);
+        current_turn.0 = current_turn.0.opponent();
+        *selection = Selection::Idle;
+        debug!("{} to move", current_turn.0.name());
+    }
+}
+
+// A promotion may be left out, as in games saved before pawns were promoted
+fn is_legal_move(board: &Board, player: Player, mv: &Move) -> bool {
+    let Some((piece_type, owner)) = board.get(mv.from) else {
+        return false;
+    };
+    let position = BoardPosition::new(mv.from.0, mv.from.1);
+
+    owner == player
+        && get_legal_moves(&piece_type, &position, &owner, board).contains(&mv.to)
+        && mv.promotion.is_none_or(|promotion| {
+            is_promotion(piece_type, mv.to) && PROMOTION_PIECES.contains(&promotion)
+        })
+}
+
+// The pieces are laid out afresh, as captured ones may come back
+fn apply_takebacks(
+    mut commands: Commands,
+    mut takeback_events: EventReader<TakebackEvent>,
+    mut replace_events: EventReader<ReplaceHistoryEvent>,
+    mut selection: ResMut<Selection>,
+    mut current_turn: ResMut<CurrentTurn>,
+    mut history: ResMut<MoveHistory>,
+    mut board: ResMut<Board>,
+    game_assets: Option<Res<GameAssets>>,
+    pieces: Query<Entity, With<Piece>>,
+    board_root: Query<Entity, With<BoardRoot>>,
+) {
+    let replacement = replace_events
+        .iter()
+        .last()
+        .map(|ReplaceHistoryEvent(history)| history.clone());
+    let plies: usize = takeback_events
+        .iter()
+        .map(|TakebackEvent(plies)| plies)
+        .sum();
+    if replacement.is_none() && plies == 0 {
+        return;
+    }
+
+    let Ok(board_root) = board_root.get_single() else {
+        return;
+    };
+
+    if let Some(replacement) = replacement {
+        info!("going back to a game of {} moves", replacement.moves.len());
+        *history = replacement;
+    } else {
+        let kept = history.moves.len().saturating_sub(plies);
+        info!("taking back {} moves", history.moves.len() - kept);
+        history.moves.truncate(kept);
+        history.times.truncate(kept);
+    }
+
+    *board = get_board_after_moves(&history.moves);
+    current_turn.0 = if history.moves.len().is_multiple_of(2) {
+        Player::White
+    } else {
+        Player::Black
+    };
+    *selection = Selection::Idle;
+
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // Headless games have no sprites to draw the pieces with
+    if let Some(game_assets) = game_assets {
+        spawn_pieces(&mut commands, &game_assets, board_root, board.pieces());
+    } else {
+        for piece in board.pieces() {
+            let piece = commands.spawn(piece).id();
+            commands.entity(board_root).add_child(piece);
+        }
+    }
+}
+
+// Also clears a result when the history is replaced, as by resuming
+fn detect_game_over(
+    mut commands: Commands,
+    history: Res<MoveHistory>,
+    game_over: Option<Res<GameOver>>,
+) {
+    match get_game_result(&history.moves) {
+        Some(result) if game_over.is_none() => {
+            info!("game over by {}: {}", result.name(), result.score());
+            commands.insert_resource(GameOver(result));
+        }
+        None if game_over.is_some() => commands.remove_resource::<GameOver>(),
+        _ => {}
+    }
+}
+
+// In hand and brain, only pieces of the kind the brain called can move,
+// and none before the call. In a maze only its piece moves, the pawns in
+// the way being obstacles, and nothing once it's done
+fn update_possible_moves(
+    board: Res<Board>,
+    history: Res<MoveHistory>,
+    brain_call: Option<Res<BrainCall>>,
+    maze: Option<Res<MazeRun>>,
+    mut possible_moves: ResMut<PossibleMoves>,
+    diagnostics: Option<ResMut<Diagnostics>>,
+) {
+    let _span = debug_span!("update_possible_moves").entered();
+    let start = Instant::now();
+
+    let called = brain_call.map(|brain_call| brain_call.get(history.moves.len()));
+    possible_moves.0 = board
+        .pieces()
+        .filter(|(piece_type, _, _)| called.is_none_or(|called| called == Some(*piece_type)))
+        .filter(|(_, _, position)| {
+            maze.as_ref()
+                .is_none_or(|maze| maze.time.is_none() && maze.square == position.square())
+        })
+        .map(|(piece_type, player, position)| {
+            let moves = get_legal_moves(&piece_type, &position, &player, &board);
+            (position.square(), moves)
+        })
+        .collect();
+
+    if let Some(mut diagnostics) = diagnostics {
+        diagnostics.add_measurement(POSSIBLE_MOVES_TIME, || {
+            start.elapsed().as_secs_f64() * 1000.0
+        });
+    }
+}
@@ -0,0 +1,382 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use chess_core::{get_board_after_moves, BoardPosition, Move, Piece, Player};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::ForkedGame,
+    explore::Exploration,
+    input::{Action, Actions},
+    pieces::BoardSetup,
+    puzzles::PuzzleAttempt,
+    rules::{CurrentTurn, GameTime, MoveEvent, MoveHistory, ReplaceHistoryEvent},
+    settings::{read_ron_file, write_ron_file, Settings},
+    toast::Toast,
+    variations::MoveTree,
+    GameSet,
+};
+
+const AUTOSAVE_FILE_NAME: &str = "autosave.ron";
+const SNAPSHOT_VERSION: u32 = 3;
+// Seconds of play between autosaves, so the clock, which runs on between
+// moves, is kept too
+const AUTOSAVE_INTERVAL: f64 = 10.0;
+
+pub struct SavePlugin {
+    // Continue the autosaved game instead of starting a new one
+    pub resume: bool,
+}
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        let resume = self.resume;
+
+        app.add_startup_system(
+            load_autosave
+                .run_if(move || resume)
+                .run_if(not(resource_exists::<ReplayPlayback>())),
+        )
+        .add_system(
+            resume_last_game
+                .run_if(not(resource_exists::<ReplayPlayback>()))
+                .run_if(not(resource_exists::<Exploration>()))
+                .run_if(not(resource_exists::<PuzzleAttempt>()))
+                .run_if(not(resource_exists::<ForkedGame>()))
+                .in_set(GameSet::Input),
+        )
+        .add_system(
+            play_replay
+                .run_if(resource_exists::<ReplayPlayback>())
+                .in_set(GameSet::Rules),
+        )
+        // Captures must be applied before the board is snapshotted
+        .add_system(
+            write_autosave
+                .run_if(not(resource_exists::<ReplayPlayback>()))
+                .run_if(not(resource_exists::<Exploration>()))
+                .run_if(not(resource_exists::<PuzzleAttempt>()))
+                .run_if(not(resource_exists::<ForkedGame>()))
+                .in_base_set(CoreSet::PostUpdate),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub moves: Vec<Move>,
+    pub times: Vec<f64>,
+    pub next: usize,
+}
+
+// The one schema for anything that stores or sends a game. Fields added
+// in later versions must be #[serde(default)] so older files still load,
+// and fields a newer version added are skipped when reading its files.
+#[derive(Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub version: u32,
+    pub turn: Player,
+    pub pieces: Vec<(Piece, Player, BoardPosition)>,
+    pub history: Vec<Move>,
+    #[serde(default)]
+    pub move_times: Vec<f64>,
+    // The game clock, which runs on past the last move. Format 2 and
+    // before only had the move times
+    #[serde(default)]
+    pub game_time: Option<f64>,
+    // As they were when the game was saved, the variant among them
+    #[serde(default)]
+    pub settings: Option<Settings>,
+}
+
+impl GameSnapshot {
+    pub fn capture<'a>(
+        pieces: impl Iterator<Item = (&'a Piece, &'a Player, &'a BoardPosition)>,
+        current_turn: &CurrentTurn,
+        history: &MoveHistory,
+        game_time: &GameTime,
+        settings: &Settings,
+    ) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            turn: current_turn.0,
+            pieces: pieces
+                .map(|(piece_type, player, position)| (*piece_type, *player, *position))
+                .collect(),
+            history: history.moves.clone(),
+            move_times: history.times.clone(),
+            game_time: Some(game_time.0),
+            settings: Some(settings.clone()),
+        }
+    }
+
+    // For a game with no pieces on hand, as once it is over
+    pub fn from_history(history: &MoveHistory, settings: &Settings) -> Self {
+        let turn = if history.moves.len().is_multiple_of(2) {
+            Player::White
+        } else {
+            Player::Black
+        };
+
+        Self {
+            version: SNAPSHOT_VERSION,
+            turn,
+            pieces: get_board_after_moves(&history.moves).pieces().collect(),
+            history: history.moves.clone(),
+            move_times: history.times.clone(),
+            game_time: None,
+            settings: Some(settings.clone()),
+        }
+    }
+
+    // Sets the game up from the snapshot, before the board is populated.
+    // Of the settings, only those the game is played by come back, and
+    // the rest stay as the player has them now
+    pub fn restore(
+        self,
+        history: &mut MoveHistory,
+        current_turn: &mut CurrentTurn,
+        board_setup: &mut BoardSetup,
+        game_time: &mut GameTime,
+        settings: &mut Settings,
+    ) {
+        self.restore_clock_and_settings(game_time, settings);
+        current_turn.0 = self.turn;
+        board_setup.0 = self.pieces;
+        history.moves = self.history;
+        history.times = self.move_times;
+    }
+
+    // For a game brought back while another is on the board, whose pieces
+    // are then laid out from the history
+    fn restore_clock_and_settings(&self, game_time: &mut GameTime, settings: &mut Settings) {
+        game_time.0 = self
+            .game_time
+            .or(self.move_times.last().copied())
+            .unwrap_or_default();
+
+        if let Some(saved) = &self.settings {
+            settings.engine_opponent = saved.engine_opponent;
+            settings.armageddon = saved.armageddon;
+            settings.hand_and_brain = saved.hand_and_brain;
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let mut snapshot: Self = read_ron_file(path)?;
+
+        // What this version knows of it is still worth playing
+        if snapshot.version > SNAPSHOT_VERSION {
+            warn!(
+                "{} was saved by a newer version of the game (format {}, this one knows up to {})",
+                path.display(),
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+        }
+
+        // Format 1 had no move times, so space those moves a second apart
+        if snapshot.move_times.len() != snapshot.history.len() {
+            snapshot.move_times = (1..=snapshot.history.len()).map(|i| i as f64).collect();
+        }
+
+        Ok(snapshot)
+    }
+}
+
+fn load_autosave(
+    mut history: ResMut<MoveHistory>,
+    mut current_turn: ResMut<CurrentTurn>,
+    mut board_setup: ResMut<BoardSetup>,
+    mut game_time: ResMut<GameTime>,
+    mut settings: ResMut<Settings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(path) = get_autosave_path() else {
+        return;
+    };
+
+    if !path.exists() {
+        warn!("no saved game to resume at {}", path.display());
+        return;
+    }
+
+    match GameSnapshot::read(&path) {
+        Ok(snapshot) => snapshot.restore(
+            &mut history,
+            &mut current_turn,
+            &mut board_setup,
+            &mut game_time,
+            &mut settings,
+        ),
+        Err(err) => {
+            warn!("could not read saved game {}: {err}", path.display());
+            toasts.send(Toast::new("toast-resume-failed"));
+        }
+    }
+}
+
+// Once a move is played the autosave is the game on the board, so there is
+// only a last game to go back to before then
+fn resume_last_game(
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    mut game_time: ResMut<GameTime>,
+    mut settings: ResMut<Settings>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !actions.just_pressed(Action::ResumeLastGame) || !history.moves.is_empty() {
+        return;
+    }
+
+    let Some(path) = get_autosave_path().filter(|path| path.exists()) else {
+        toasts.send(Toast::new("toast-no-last-game"));
+        return;
+    };
+
+    match GameSnapshot::read(&path) {
+        Ok(snapshot) => {
+            info!("resuming a game of {} moves", snapshot.history.len());
+            snapshot.restore_clock_and_settings(&mut game_time, &mut settings);
+            replace_events.send(ReplaceHistoryEvent(MoveHistory {
+                tree: MoveTree::from_line(&snapshot.history, &snapshot.move_times),
+                moves: snapshot.history,
+                times: snapshot.move_times,
+            }));
+        }
+        Err(err) => {
+            warn!("could not read saved game {}: {err}", path.display());
+            toasts.send(Toast::new("toast-resume-failed"));
+        }
+    }
+}
+
+// On every move and change of settings, every so often for the clock, and
+// on the way out
+fn write_autosave(
+    history: Res<MoveHistory>,
+    current_turn: Res<CurrentTurn>,
+    game_time: Res<GameTime>,
+    settings: Res<Settings>,
+    pieces: Query<(&Piece, &Player, &BoardPosition)>,
+    mut app_exit_events: EventReader<AppExit>,
+    // The game time as of the last autosave
+    mut saved_at: Local<f64>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let exiting = app_exit_events.iter().next().is_some();
+    let due = history.is_changed()
+        || settings.is_changed()
+        || exiting
+        || game_time.0 - *saved_at >= AUTOSAVE_INTERVAL;
+
+    // An untouched new game must not clobber the previous save
+    if !due || history.moves.is_empty() {
+        return;
+    }
+    *saved_at = game_time.0;
+
+    let Some(path) = get_autosave_path() else {
+        return;
+    };
+
+    let snapshot = GameSnapshot::capture(
+        pieces.iter(),
+        &current_turn,
+        &history,
+        &game_time,
+        &settings,
+    );
+
+    if let Err(err) = write_ron_file(&path, &snapshot) {
+        warn!("could not autosave to {}: {err}", path.display());
+        toasts.send(Toast::new("toast-autosave-failed"));
+    }
+}
+
+// Keeps a game apart from the autosave, which the next game overwrites.
+// Returns where it went, which --replay= plays back
+pub fn archive_game(history: &MoveHistory, settings: &Settings) -> Result<PathBuf, String> {
+    // Before the clock, which panics on the web, where there is no data dir
+    let dir = get_archive_dir().ok_or("there is no data directory")?;
+    archive_game_in(&dir, history, settings)
+}
+
+pub fn archive_game_in(
+    dir: &Path,
+    history: &MoveHistory,
+    settings: &Settings,
+) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = claim_archive_path(dir, timestamp)?;
+
+    write_ron_file(&path, &GameSnapshot::from_history(history, settings))?;
+    Ok(path)
+}
+
+// Games archived within the same second get a counter, and the file is
+// created here so another game can't take the name before it is written
+fn claim_archive_path(dir: &Path, timestamp: u64) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+
+    let mut count = 1;
+    loop {
+        let name = if count == 1 {
+            format!("game-{timestamp}.ron")
+        } else {
+            format!("game-{timestamp}-{count}.ron")
+        };
+        let path = dir.join(name);
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok(path),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => count += 1,
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
+
+// Where finished games are kept, named by the time they were archived
+pub fn get_archive_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("chess").join("games"))
+}
+
+pub fn get_replay_path_from_args() -> Option<PathBuf> {
+    std::env::args().skip(1).find_map(|arg| {
+        if arg == "--replay" {
+            get_autosave_path()
+        } else {
+            arg.strip_prefix("--replay=").map(PathBuf::from)
+        }
+    })
+}
+
+fn play_replay(
+    mut replay: ResMut<ReplayPlayback>,
+    game_time: Res<GameTime>,
+    mut move_events: EventWriter<MoveEvent>,
+) {
+    while replay.next < replay.moves.len() {
+        if replay.times[replay.next] > game_time.0 {
+            break;
+        }
+
+        let mv = replay.moves[replay.next];
+        debug!("replaying move {}: {}-{}", replay.next + 1, mv.from, mv.to);
+        move_events.send(MoveEvent(mv));
+        replay.next += 1;
+    }
+}
+
+fn get_autosave_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("chess").join(AUTOSAVE_FILE_NAME))
+}
@@ -0,0 +1,352 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::prelude::*;
+use chess_core::Player;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    input::{get_default_key_bindings, Action, Actions, Binding},
+    locale::Localizer,
+    toast::Toast,
+    ui_theme::UiThemeMode,
+};
+
+const SETTINGS_FILE_NAME: &str = "settings.ron";
+// How often to look for edits made to the file while the game runs
+const SETTINGS_CHECK_SECONDS: f32 = 1.0;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_settings.in_base_set(StartupSet::PreStartup))
+            .add_system(reload_settings)
+            .add_system(write_settings.after(reload_settings))
+            .add_system(cycle_theme);
+    }
+}
+
+// The settings as last read from or written to the file
+#[derive(Resource)]
+struct SettingsFile {
+    settings: Settings,
+    modified: Option<SystemTime>,
+    check_timer: Timer,
+}
+
+impl SettingsFile {
+    fn new(settings: Settings, path: &Path) -> Self {
+        Self {
+            settings,
+            modified: get_modified_time(path),
+            check_timer: Timer::from_seconds(SETTINGS_CHECK_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    // The colors from the settings file
+    Custom,
+    Deuteranopia,
+    Protanopia,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn next(&self) -> Self {
+        match self {
+            Theme::Custom => Theme::Deuteranopia,
+            Theme::Deuteranopia => Theme::Protanopia,
+            Theme::Protanopia => Theme::HighContrast,
+            Theme::HighContrast => Theme::Custom,
+        }
+    }
+}
+
+pub struct Palette {
+    pub light_tile: Color,
+    pub dark_tile: Color,
+    pub selected_tile: Color,
+    pub guide: Color,
+    pub cursor: Color,
+    pub last_move: Color,
+    pub check: Color,
+}
+
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+    // Also mark highlights with shapes, for when colors are hard to tell apart
+    pub indicator_shapes: bool,
+    pub light_tile_color: Color,
+    pub dark_tile_color: Color,
+    pub selected_tile_color: Color,
+    pub guide_color: Color,
+    pub cursor_color: Color,
+    pub last_move_color: Color,
+    pub check_color: Color,
+    pub piece_atlas: String,
+    pub screenshot_size: u32,
+    pub screenshot_coordinates: bool,
+    pub screenshot_last_move: bool,
+    pub animation_size: u32,
+    pub animation_frame_delay_ms: u32,
+    pub qr_code_lichess_url: bool,
+    pub key_bindings: BTreeMap<Action, Vec<Binding>>,
+    // None follows the system language
+    pub language: Option<String>,
+    // On top of the display's own scale factor
+    pub ui_scale: f64,
+    // Only has an effect in builds with the speech feature
+    pub speak_moves: bool,
+    // How many of their own moves the mate search gives the side to move
+    pub mate_search_moves: u32,
+    // Moves wait for a second click or Enter before they are played
+    pub confirm_moves: bool,
+    // The side the engine plays, if any. Its moves can be taken back freely
+    pub engine_opponent: Option<Player>,
+    // The comment on the move being looked at, shown below the board
+    pub comment_box: bool,
+    // The 2D board seen at an angle, with the pieces standing up on it
+    pub isometric_view: bool,
+    // Light or dark menus and panels. None follows the system
+    pub ui_theme: Option<UiThemeMode>,
+    // Tell the player when the engine moves while they're in another window
+    pub move_notifications: bool,
+    pub flash_taskbar: bool,
+    // Games played as armageddon, where Black wins a draw
+    pub armageddon: bool,
+    // Each side is a team of a brain, who names the kind of piece to move,
+    // and a hand, who moves one
+    pub hand_and_brain: bool,
+    // Pieces and the camera jump to where they're going instead of sliding
+    pub reduced_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Custom,
+            indicator_shapes: false,
+            light_tile_color: Color::LIME_GREEN,
+            dark_tile_color: Color::GREEN,
+            selected_tile_color: Color::YELLOW,
+            guide_color: Color::GRAY,
+            cursor_color: Color::rgba(0.0, 0.0, 1.0, 0.4),
+            last_move_color: Color::rgba(1.0, 1.0, 0.0, 0.4),
+            check_color: Color::rgba(1.0, 0.0, 0.0, 0.5),
+            piece_atlas: "pieces.png".to_string(),
+            screenshot_size: 960,
+            screenshot_coordinates: true,
+            screenshot_last_move: true,
+            animation_size: 480,
+            animation_frame_delay_ms: 1000,
+            qr_code_lichess_url: true,
+            key_bindings: get_default_key_bindings(),
+            language: None,
+            ui_scale: 1.0,
+            speak_moves: false,
+            mate_search_moves: 3,
+            confirm_moves: false,
+            engine_opponent: None,
+            comment_box: true,
+            isometric_view: false,
+            ui_theme: None,
+            move_notifications: true,
+            flash_taskbar: true,
+            armageddon: false,
+            hand_and_brain: false,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn palette(&self) -> Palette {
+        match self.theme {
+            Theme::Custom => Palette {
+                light_tile: self.light_tile_color,
+                dark_tile: self.dark_tile_color,
+                selected_tile: self.selected_tile_color,
+                guide: self.guide_color,
+                cursor: self.cursor_color,
+                last_move: self.last_move_color,
+                check: self.check_color,
+            },
+            // Blues against oranges and yellows, which stay apart without
+            // green or red cones
+            Theme::Deuteranopia => Palette {
+                light_tile: Color::rgb(0.87, 0.89, 0.93),
+                dark_tile: Color::rgb(0.35, 0.45, 0.65),
+                selected_tile: Color::rgb(0.90, 0.60, 0.0),
+                guide: Color::rgb(0.15, 0.15, 0.15),
+                cursor: Color::rgba(0.94, 0.89, 0.26, 0.5),
+                last_move: Color::rgba(0.90, 0.60, 0.0, 0.4),
+                check: Color::rgba(0.84, 0.37, 0.0, 0.6),
+            },
+            // Reds look dark without red cones, so none are used
+            Theme::Protanopia => Palette {
+                light_tile: Color::rgb(0.96, 0.93, 0.72),
+                dark_tile: Color::rgb(0.30, 0.45, 0.70),
+                selected_tile: Color::rgb(0.34, 0.71, 0.91),
+                guide: Color::rgb(0.15, 0.15, 0.15),
+                cursor: Color::rgba(0.0, 0.45, 0.70, 0.5),
+                last_move: Color::rgba(0.94, 0.89, 0.26, 0.5),
+                check: Color::rgba(0.0, 0.20, 0.55, 0.6),
+            },
+            Theme::HighContrast => Palette {
+                light_tile: Color::WHITE,
+                dark_tile: Color::rgb(0.55, 0.55, 0.55),
+                selected_tile: Color::YELLOW,
+                guide: Color::FUCHSIA,
+                cursor: Color::rgba(0.0, 0.4, 1.0, 0.5),
+                last_move: Color::rgba(1.0, 0.6, 0.0, 0.5),
+                check: Color::rgba(1.0, 0.0, 0.0, 0.7),
+            },
+        }
+    }
+
+    pub fn use_indicator_shapes(&self) -> bool {
+        self.indicator_shapes || self.theme == Theme::HighContrast
+    }
+}
+
+fn load_settings(mut commands: Commands, mut toasts: EventWriter<Toast>) {
+    let Some(path) = get_settings_path() else {
+        commands.insert_resource(Localizer::new(None));
+        commands.insert_resource(Settings::default());
+        return;
+    };
+
+    let settings = if path.exists() {
+        read_settings(&path).unwrap_or_else(|err| {
+            warn!("could not read settings {}: {err}", path.display());
+            toasts.send(Toast::new("toast-settings-failed"));
+            Settings::default()
+        })
+    } else {
+        // Write the defaults out so there is a file to edit
+        let settings = Settings::default();
+        if let Err(err) = write_ron_file(&path, &settings) {
+            warn!("could not write settings to {}: {err}", path.display());
+        }
+        settings
+    };
+
+    commands.insert_resource(Localizer::new(settings.language.as_deref()));
+    commands.insert_resource(SettingsFile::new(settings.clone(), &path));
+    commands.insert_resource(settings);
+}
+
+fn read_settings(path: &Path) -> Result<Settings, String> {
+    let mut settings: Settings = read_ron_file(path)?;
+
+    // Actions added since the file was written get their default keys
+    for (action, bindings) in get_default_key_bindings() {
+        settings.key_bindings.entry(action).or_insert(bindings);
+    }
+
+    Ok(settings)
+}
+
+// Picks up edits to the file, e.g. to try out theme colors without
+// restarting
+fn reload_settings(
+    time: Res<Time>,
+    settings_file: Option<ResMut<SettingsFile>>,
+    mut settings: ResMut<Settings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(mut settings_file) = settings_file else {
+        return;
+    };
+
+    if !settings_file.check_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Some(path) = get_settings_path() else {
+        return;
+    };
+
+    let modified = get_modified_time(&path);
+    if modified == settings_file.modified {
+        return;
+    }
+    settings_file.modified = modified;
+
+    match read_settings(&path) {
+        Ok(new_settings) => {
+            settings_file.settings = new_settings.clone();
+            if *settings != new_settings {
+                *settings = new_settings;
+            }
+        }
+        // Likely saved halfway through an edit, so keep what we have
+        Err(err) => {
+            warn!("could not reload settings {}: {err}", path.display());
+            toasts.send(Toast::new("toast-settings-failed"));
+        }
+    }
+}
+
+fn write_settings(settings: Res<Settings>, settings_file: Option<ResMut<SettingsFile>>) {
+    let Some(mut settings_file) = settings_file else {
+        return;
+    };
+
+    // Changes read from the file don't need writing back to it
+    if !settings.is_changed() || *settings == settings_file.settings {
+        return;
+    }
+
+    let Some(path) = get_settings_path() else {
+        return;
+    };
+
+    if let Err(err) = write_ron_file(&path, &*settings) {
+        warn!("could not write settings to {}: {err}", path.display());
+    }
+
+    settings_file.settings = settings.clone();
+    settings_file.modified = get_modified_time(&path);
+}
+
+fn cycle_theme(actions: Res<Actions>, mut settings: ResMut<Settings>) {
+    if actions.just_pressed(Action::CycleTheme) {
+        settings.theme = settings.theme.next();
+    }
+}
+
+fn get_settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("chess").join(SETTINGS_FILE_NAME))
+}
+
+fn get_modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub fn read_ron_file<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    ron::from_str(&contents).map_err(|err| err.to_string())
+}
+
+pub fn write_ron_file<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+
+    fs::write(path, contents).map_err(|err| err.to_string())
+}
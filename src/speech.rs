@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use chess_core::{get_board_after_moves, is_king_attacked, Move};
+use fluent::fluent_args;
+
+use crate::{locale::Localizer, rules::MoveHistory, settings::Settings, GameSet};
+
+pub struct SpeechPlugin;
+
+impl Plugin for SpeechPlugin {
+    fn build(&self, app: &mut App) {
+        match tts::Tts::default() {
+            Ok(tts) => {
+                app.insert_non_send_resource(Speaker(tts));
+            }
+            Err(err) => warn!("could not start text to speech: {err}"),
+        }
+
+        app.add_system(
+            speak_moves
+                .run_if(|settings: Res<Settings>| settings.speak_moves)
+                .in_set(GameSet::Render),
+        );
+    }
+}
+
+struct Speaker(tts::Tts);
+
+fn speak_moves(
+    history: Res<MoveHistory>,
+    localizer: Res<Localizer>,
+    speaker: Option<NonSendMut<Speaker>>,
+) {
+    // Loading a saved game is not a move
+    if !history.is_changed() || history.is_added() {
+        return;
+    }
+
+    let (Some(mut speaker), Some((last_move, earlier_moves))) =
+        (speaker, history.moves.split_last())
+    else {
+        return;
+    };
+
+    if let Err(err) = speaker
+        .0
+        .speak(get_spoken_move(earlier_moves, last_move, &localizer), true)
+    {
+        warn!("could not speak the move: {err}");
+    }
+}
+
+fn get_spoken_move(earlier_moves: &[Move], mv: &Move, localizer: &Localizer) -> String {
+    let mut board = get_board_after_moves(earlier_moves);
+
+    let Some((piece_type, player)) = board.get(mv.from) else {
+        return mv.to.to_string();
+    };
+
+    let args = fluent_args!["piece" => piece_type.name(), "to" => mv.to.to_string()];
+    let mut spoken = if board.get(mv.to).is_some() {
+        localizer.format("spoken-capture", &args)
+    } else {
+        localizer.format("spoken-move", &args)
+    };
+
+    board.apply_move(mv);
+    if is_king_attacked(&board, player.opponent()) {
+        spoken = localizer.format("spoken-check", &fluent_args!["move" => spoken]);
+    }
+
+    spoken
+}
@@ -0,0 +1,129 @@
+// Sequential probability ratio test over match results. It stops a match
+// between two engines once the games so far make it clear enough whether
+// the first is elo0 or elo1 stronger than the second
+
+// The normal quantile for a two-sided 95% interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+// Games from the point of view of the first engine
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    // Points per game, from 0 to 1
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    // Spread of the points of a single game around the mean score
+    fn variance(&self) -> f64 {
+        let score = self.score();
+        (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / self.games() as f64
+    }
+
+    // The Elo difference and the half width of its 95% confidence interval.
+    // None until the first engine has both dropped and won points
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let score = self.score();
+        if self.games() == 0 || score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+
+        let margin = CONFIDENCE_Z * (self.variance() / self.games() as f64).sqrt();
+        let low = get_elo((score - margin).max(f64::EPSILON));
+        let high = get_elo((score + margin).min(1.0 - f64::EPSILON));
+
+        Some((get_elo(score), (high - low) / 2.0))
+    }
+}
+
+// The Elo difference that gives the expected score
+pub fn get_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+// The expected score of the stronger side for an Elo difference
+pub fn get_expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    // The hypotheses told apart: the first engine is elo0 or elo1 stronger
+    pub elo0: f64,
+    pub elo1: f64,
+    // Chance of accepting elo1 when elo0 holds, and the other way around
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprtOutcome {
+    AcceptElo0,
+    AcceptElo1,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl Sprt {
+    // Log likelihood ratio of elo1 over elo0, using the normal
+    // approximation of the game results
+    pub fn llr(&self, tally: &Tally) -> f64 {
+        if tally.games() == 0 {
+            return 0.0;
+        }
+
+        let variance = tally.variance();
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        let score0 = get_expected_score(self.elo0);
+        let score1 = get_expected_score(self.elo1);
+
+        tally.games() as f64 * (score1 - score0) * (2.0 * tally.score() - score0 - score1)
+            / (2.0 * variance)
+    }
+
+    // The LLR that accepts elo0 and the one that accepts elo1
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    // None while the games so far don't settle it
+    pub fn get_outcome(&self, tally: &Tally) -> Option<SprtOutcome> {
+        let llr = self.llr(tally);
+        let (lower, upper) = self.bounds();
+
+        if llr <= lower {
+            Some(SprtOutcome::AcceptElo0)
+        } else if llr >= upper {
+            Some(SprtOutcome::AcceptElo1)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// Statistics over the finished games kept in the data directory, written
+// out for charting elsewhere. Started with --export-stats=<file>, as JSON
+// when the file name ends in .json and as CSV otherwise. Every row is from
+// one side's point of view, so a chart can follow either color. The
+// accuracy has the engine look at every move, which takes a while over
+// many games
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chess_core::{
+    get_accuracy, get_board_after_moves, get_fair_play_reports, get_game_result, get_san,
+    EngineConfig, GameResult, Player,
+};
+use serde::Serialize;
+
+use crate::save::{get_archive_dir, GameSnapshot};
+
+// The moves openings are told apart by
+const OPENING_PLIES: usize = 4;
+// The game has no clocks yet, so every game falls under this
+const UNTIMED: &str = "untimed";
+
+#[derive(Serialize)]
+struct StatsRow {
+    // color for all games, opening, time_control, or game for a row per
+    // game in the order they were played
+    group: &'static str,
+    key: String,
+    side: &'static str,
+    games: u32,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    unfinished: u32,
+    // In percent, averaged over the games long enough to judge
+    accuracy: Option<f64>,
+    #[serde(skip)]
+    judged_games: u32,
+}
+
+impl StatsRow {
+    fn new(group: &'static str, key: String, player: Player) -> Self {
+        Self {
+            group,
+            key,
+            side: player.name(),
+            games: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            unfinished: 0,
+            accuracy: None,
+            judged_games: 0,
+        }
+    }
+
+    fn add(&mut self, game: &GameStats, player: Player) {
+        match game.result {
+            Some(result) => match result.winner() {
+                Some(winner) if winner == player => self.wins += 1,
+                Some(_) => self.losses += 1,
+                None => self.draws += 1,
+            },
+            None => self.unfinished += 1,
+        }
+
+        self.games += 1;
+
+        if let Some(accuracy) = game.accuracy[if player == Player::White { 0 } else { 1 }] {
+            let total = self.accuracy.unwrap_or_default() * self.judged_games as f64 + accuracy;
+            self.judged_games += 1;
+            self.accuracy = Some(total / self.judged_games as f64);
+        }
+    }
+}
+
+struct GameStats {
+    // Seconds since 1970, as in the name the game was archived under
+    timestamp: u64,
+    opening: String,
+    result: Option<GameResult>,
+    // White's, then Black's. None with no moves past the opening to judge
+    accuracy: [Option<f64>; 2],
+}
+
+pub fn get_stats_path_from_args() -> Option<PathBuf> {
+    std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--export-stats=").map(PathBuf::from))
+}
+
+pub fn export_stats(path: PathBuf) {
+    let Some(archive_dir) = get_archive_dir() else {
+        eprintln!("there is no data directory");
+        std::process::exit(1);
+    };
+
+    let games = read_games(&archive_dir);
+    let rows = get_rows(&games);
+
+    let contents = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::to_string_pretty(&rows).map_err(|err| err.to_string())
+    } else {
+        Ok(get_csv(&rows))
+    };
+
+    if let Err(err) =
+        contents.and_then(|contents| fs::write(&path, contents).map_err(|err| err.to_string()))
+    {
+        eprintln!("could not write {}: {err}", path.display());
+        std::process::exit(1);
+    }
+
+    eprintln!(
+        "wrote the statistics of {} games to {}",
+        games.len(),
+        path.display()
+    );
+}
+
+// Games that can't be read are left out, and said so
+fn read_games(archive_dir: &Path) -> Vec<GameStats> {
+    let entries = fs::read_dir(archive_dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_else(|err| {
+            eprintln!("could not read {}: {err}", archive_dir.display());
+            Vec::new()
+        });
+
+    let mut games: Vec<GameStats> = entries
+        .iter()
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .filter_map(|path| {
+            let snapshot = GameSnapshot::read(path)
+                .map_err(|err| eprintln!("skipping {}: {err}", path.display()))
+                .ok()?;
+            eprintln!("analysing {}", path.display());
+            Some(get_game_stats(path, &snapshot))
+        })
+        .collect();
+
+    games.sort_by_key(|game| game.timestamp);
+    games
+}
+
+fn get_game_stats(path: &Path, snapshot: &GameSnapshot) -> GameStats {
+    // game-<secs>.ron, or game-<secs>-<count>.ron for later games that second
+    let timestamp = path
+        .file_stem()
+        .and_then(|stem| {
+            let stem = stem.to_str()?.strip_prefix("game-")?;
+            stem.split('-').next()?.parse().ok()
+        })
+        .unwrap_or_default();
+
+    let mut board = get_board_after_moves(&[]);
+    let opening = snapshot
+        .history
+        .iter()
+        .take(OPENING_PLIES)
+        .map(|mv| {
+            let san = get_san(&board, mv);
+            board.apply_move(mv);
+            san
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let reports = get_fair_play_reports(
+        &snapshot.history,
+        &snapshot.move_times,
+        &EngineConfig::default(),
+    );
+
+    GameStats {
+        timestamp,
+        opening,
+        result: get_game_result(&snapshot.history),
+        accuracy: reports
+            .map(|report| (report.judged_moves > 0).then(|| get_accuracy(report.average_loss))),
+    }
+}
+
+fn get_rows(games: &[GameStats]) -> Vec<StatsRow> {
+    let mut rows: Vec<StatsRow> = Vec::new();
+    let mut add = |group, key: String, game: &GameStats, player: Player| {
+        let index = rows
+            .iter()
+            .position(|row| row.group == group && row.key == key && row.side == player.name())
+            .unwrap_or_else(|| {
+                rows.push(StatsRow::new(group, key, player));
+                rows.len() - 1
+            });
+        rows[index].add(game, player);
+    };
+
+    for game in games {
+        for player in [Player::White, Player::Black] {
+            add("color", "all".to_string(), game, player);
+            add("opening", game.opening.clone(), game, player);
+            add("time_control", UNTIMED.to_string(), game, player);
+            add("game", format_timestamp(game.timestamp), game, player);
+        }
+    }
+
+    // Grouped, with the games kept in the order they were played
+    rows.sort_by_key(|row| {
+        ["color", "opening", "time_control", "game"]
+            .iter()
+            .position(|group| *group == row.group)
+    });
+    rows
+}
+
+fn get_csv(rows: &[StatsRow]) -> String {
+    let mut csv = "group,key,side,games,wins,draws,losses,unfinished,accuracy\n".to_string();
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            row.group,
+            quote_csv(&row.key),
+            row.side,
+            row.games,
+            row.wins,
+            row.draws,
+            row.losses,
+            row.unfinished,
+            row.accuracy
+                .map_or(String::new(), |accuracy| format!("{accuracy:.1}")),
+        ));
+    }
+    csv
+}
+
+fn quote_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// As an ISO 8601 time in UTC, which charting tools read as a date
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,178 @@
+// Reads a lichess study, fetched by --study=<id or url> or from a saved
+// copy with --study=<file.pgn>, and lays out one of its chapters, picked
+// with --chapter=N, as a tree of moves to step through
+
+use std::{fs, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use chess_core::{read_pgn, PgnGame};
+use futures_lite::future;
+
+use crate::{
+    rules::{MoveHistory, ReplaceHistoryEvent},
+    toast::Toast,
+    variations::MoveTree,
+};
+
+const LICHESS_STUDY_URL: &str = "https://lichess.org/api/study";
+
+pub struct StudyPlugin {
+    pub source: StudySource,
+}
+
+impl Plugin for StudyPlugin {
+    fn build(&self, app: &mut App) {
+        let source = self.source.clone();
+
+        app.add_startup_system(move |mut commands: Commands| {
+            let source = source.clone();
+            // The fetch blocks, so it waits on the IO pool, not the compute one
+            let task = IoTaskPool::get().spawn(async move { source.read() });
+            commands.spawn(StudyImport(task));
+        })
+        .add_system(finish_study_import);
+    }
+}
+
+#[derive(Clone)]
+pub struct StudySource {
+    // A file, or the study's id, with the chapter's after a slash if given
+    pub location: String,
+    // Counted from 1
+    pub chapter: usize,
+}
+
+impl StudySource {
+    // None unless --study= is given
+    pub fn from_args() -> Option<Self> {
+        let mut source = None;
+        let mut chapter = 1;
+
+        for arg in std::env::args().skip(1) {
+            if let Some(location) = arg.strip_prefix("--study=") {
+                source = Some(location.to_string());
+            } else if let Some(value) = arg.strip_prefix("--chapter=") {
+                let Some(value) = value.parse().ok().filter(|&value| value > 0) else {
+                    eprintln!("--chapter needs a number from 1, not {value:?}");
+                    std::process::exit(1);
+                };
+                chapter = value;
+            }
+        }
+
+        Some(Self {
+            location: source?,
+            chapter,
+        })
+    }
+
+    // The chapter asked for, read from the file or fetched. A fetched study
+    // is kept so it can be read again without a connection
+    fn read(&self) -> Result<PgnGame, String> {
+        let pgn = if PathBuf::from(&self.location).is_file() {
+            fs::read_to_string(&self.location).map_err(|err| err.to_string())?
+        } else {
+            let id = get_study_id(&self.location);
+            let pgn = ureq::get(&format!("{LICHESS_STUDY_URL}/{id}.pgn"))
+                .call()
+                .map_err(|err| err.to_string())?
+                .into_string()
+                .map_err(|err| err.to_string())?;
+
+            if let Some(path) = get_study_path(&id) {
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&path, &pgn));
+                match written {
+                    Ok(()) => info!("kept the study at {}", path.display()),
+                    Err(err) => warn!("could not keep the study at {}: {err}", path.display()),
+                }
+            }
+            pgn
+        };
+
+        let mut chapters = read_pgn(&pgn);
+        if self.chapter > chapters.len() {
+            return Err(format!(
+                "there is no chapter {}, only {}",
+                self.chapter,
+                chapters.len()
+            ));
+        }
+
+        let chapter = chapters.swap_remove(self.chapter - 1);
+        // Only the starting position can be played here
+        if chapter.tag("SetUp") == Some("1") {
+            return Err(format!(
+                "chapter {} starts from a position of its own",
+                self.chapter
+            ));
+        }
+
+        Ok(chapter)
+    }
+}
+
+// The study being looked at, whose moves can be stepped through as during
+// analysis, by step_through_tree
+#[derive(Resource)]
+pub struct Study;
+
+#[derive(Component)]
+struct StudyImport(Task<Result<PgnGame, String>>);
+
+// From "https://lichess.org/study/abcd1234" and the like, keeping a
+// chapter's id after the study's
+fn get_study_id(location: &str) -> String {
+    location
+        .split_once("study/")
+        .map_or(location, |(_, id)| id)
+        .trim_end_matches(".pgn")
+        .trim_matches('/')
+        .to_string()
+}
+
+fn get_study_path(id: &str) -> Option<PathBuf> {
+    let file_name = format!("{}.pgn", id.replace('/', "-"));
+    dirs::data_dir().map(|dir| dir.join("chess").join("studies").join(file_name))
+}
+
+fn finish_study_import(
+    mut commands: Commands,
+    mut imports: Query<(Entity, &mut StudyImport)>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (entity, mut import) in imports.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut import.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+
+        let chapter = match result {
+            Ok(chapter) => chapter,
+            Err(err) => {
+                warn!("could not import the study: {err}");
+                toasts.send(Toast::new("toast-study-failed"));
+                continue;
+            }
+        };
+
+        let name = chapter.tag("Event").unwrap_or_default().to_string();
+        info!("imported {name:?}");
+        toasts.send(Toast::new("toast-study-imported").with_arg("name", &name));
+
+        // From the starting position, with the moves ahead in the tree
+        replace_events.send(ReplaceHistoryEvent(MoveHistory {
+            moves: Vec::new(),
+            times: Vec::new(),
+            tree: MoveTree::from_pgn(&chapter),
+        }));
+        commands.insert_resource(Study);
+    }
+}
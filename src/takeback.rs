@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use chess_core::Player;
+use fluent::fluent_args;
+
+use crate::{
+    analysis::ForkedGame,
+    input::{Action, Actions},
+    locale::Localizer,
+    opponent::get_engine_player,
+    pieces::GameAssets,
+    rules::{CurrentTurn, GameOver, MoveHistory, TakebackEvent},
+    settings::Settings,
+    toast::Toast,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+    GameSet,
+};
+
+// A player asks to take back their last move, and it is only taken back
+// once their opponent agrees. Both sides go through TakebackMessage, so a
+// connection to another machine only has to carry those. The engine
+// always agrees, so against it the last move pair goes back at once
+pub struct TakebackPlugin;
+
+impl Plugin for TakebackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TakebackMessage>()
+            // No takebacks once the game is over
+            .add_systems(
+                (
+                    send_takeback_messages.run_if(not(resource_exists::<GameOver>())),
+                    handle_takeback_messages.run_if(not(resource_exists::<GameOver>())),
+                )
+                    .chain()
+                    .in_set(GameSet::Input),
+            )
+            // Playing on drops the request
+            .add_system(
+                cancel_takeback_request
+                    .run_if(resource_exists::<TakebackRequest>())
+                    .run_if(resource_changed::<MoveHistory>())
+                    .in_set(GameSet::Apply),
+            )
+            .add_system(
+                update_takeback_prompt
+                    .run_if(
+                        resource_added::<TakebackRequest>()
+                            .or_else(resource_removed::<TakebackRequest>())
+                            .or_else(resource_changed::<Localizer>())
+                            .or_else(resource_changed::<UiTheme>()),
+                    )
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+pub enum TakebackMessage {
+    Request { by: Player },
+    Accept,
+    Decline,
+}
+
+// Waiting for the opponent of the player who asked
+#[derive(Resource)]
+pub struct TakebackRequest {
+    pub by: Player,
+}
+
+#[derive(Component)]
+struct TakebackPrompt;
+
+fn send_takeback_messages(
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    forked: Option<Res<ForkedGame>>,
+    current_turn: Res<CurrentTurn>,
+    history: Res<MoveHistory>,
+    request: Option<Res<TakebackRequest>>,
+    mut messages: EventWriter<TakebackMessage>,
+    mut takeback_events: EventWriter<TakebackEvent>,
+) {
+    if let Some(engine) = get_engine_player(&settings, forked.as_deref()) {
+        if actions.just_pressed(Action::RequestTakeback) {
+            // The player's last move, and the engine's reply if it's in
+            let plies = if current_turn.0 == engine { 1 } else { 2 };
+            if plies <= history.moves.len() {
+                takeback_events.send(TakebackEvent(plies));
+            }
+        }
+        return;
+    }
+
+    if request.is_some() {
+        if actions.just_pressed(Action::AcceptTakeback) {
+            messages.send(TakebackMessage::Accept);
+        } else if actions.just_pressed(Action::DeclineTakeback) {
+            messages.send(TakebackMessage::Decline);
+        }
+    } else if actions.just_pressed(Action::RequestTakeback) && !history.moves.is_empty() {
+        // The player who just moved
+        messages.send(TakebackMessage::Request {
+            by: current_turn.0.opponent(),
+        });
+    }
+}
+
+fn handle_takeback_messages(
+    mut commands: Commands,
+    mut messages: EventReader<TakebackMessage>,
+    request: Option<Res<TakebackRequest>>,
+    mut takeback_events: EventWriter<TakebackEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let mut pending = request.is_some();
+
+    for message in messages.iter() {
+        match message {
+            TakebackMessage::Request { by } if !pending => {
+                info!("{} asks for a takeback", by.name());
+                commands.insert_resource(TakebackRequest { by: *by });
+                pending = true;
+            }
+            TakebackMessage::Accept if pending => {
+                takeback_events.send(TakebackEvent(1));
+                toasts.send(Toast::new("toast-takeback-accepted"));
+                commands.remove_resource::<TakebackRequest>();
+                pending = false;
+            }
+            TakebackMessage::Decline if pending => {
+                toasts.send(Toast::new("toast-takeback-declined"));
+                commands.remove_resource::<TakebackRequest>();
+                pending = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn cancel_takeback_request(mut commands: Commands) {
+    commands.remove_resource::<TakebackRequest>();
+}
+
+fn update_takeback_prompt(
+    mut commands: Commands,
+    request: Option<Res<TakebackRequest>>,
+    localizer: Res<Localizer>,
+    game_assets: Option<Res<GameAssets>>,
+    theme: Res<UiTheme>,
+    prompts: Query<Entity, With<TakebackPrompt>>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    for entity in prompts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (Some(request), Some(game_assets), Ok(status_bar)) =
+        (request, game_assets, status_bars.get_single())
+    else {
+        return;
+    };
+
+    let text = localizer.format(
+        "takeback-prompt",
+        &fluent_args!["player" => request.by.name()],
+    );
+
+    spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Prompt,
+        &game_assets,
+        &theme,
+        TakebackPrompt,
+    );
+}
@@ -0,0 +1,956 @@
+// Runs the move pipeline on a headless app, without a window or assets
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    app::AppExit,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+};
+use chess_core::{
+    get_all_legal_moves, get_board_after_moves, get_starting_pieces, read_pgn, Board,
+    BoardPosition, GameResult, Move, Piece, Player, Puzzle, Repertoire, Square,
+};
+
+use crate::{
+    adjourn::{read_adjourned_game, AdjournDirs, AdjournPlugin, SealedMove},
+    board::{get_square_at, BoardRoot},
+    camera::to_viewport_position,
+    explore::{Exploration, ExplorationPlugin},
+    hand_and_brain::HandAndBrainPlugin,
+    input::{get_board_point, InputPlugin, Selection, SnapBack, SquareClicked},
+    locale::Localizer,
+    maze::{MazePlugin, MazeRun},
+    opponent::EngineOpponentPlugin,
+    pieces::BoardSetup,
+    puzzles::{PuzzleAttempt, PuzzlesPlugin},
+    repertoire::{OpeningRepertoire, RepertoireDeviation, RepertoirePlugin},
+    rules::{CurrentTurn, GameOver, GameTime, MoveEvent, MoveHistory, PossibleMoves, RulesPlugin},
+    save::{archive_game_in, GameSnapshot},
+    settings::{write_ron_file, Settings},
+    sprt::{Sprt, SprtOutcome, Tally},
+    takeback::{TakebackPlugin, TakebackRequest},
+    toast::Toast,
+    ui_theme::UiTheme,
+    variations::VariationsPlugin,
+    GameSetsPlugin, GameState,
+};
+
+fn get_test_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(bevy::input::InputPlugin)
+        .add_plugin(GameSetsPlugin)
+        .insert_resource(Settings::default())
+        .add_event::<Toast>()
+        .insert_resource(Localizer::new(Some("en-US")))
+        .init_resource::<UiTheme>()
+        .add_plugin(InputPlugin)
+        .add_plugin(RulesPlugin)
+        .add_plugin(TakebackPlugin)
+        .add_plugin(HandAndBrainPlugin)
+        .add_plugin(PuzzlesPlugin)
+        .add_plugin(MazePlugin)
+        .add_plugin(EngineOpponentPlugin)
+        .add_plugin(ExplorationPlugin)
+        .add_plugin(VariationsPlugin);
+
+    // What the board and pieces plugins would spawn, minus the sprites
+    app.world.spawn((SpatialBundle::default(), BoardRoot));
+    for (piece_type, player, position) in get_starting_pieces() {
+        app.world.spawn((piece_type, player, position));
+    }
+    app.insert_resource(Board::from_pieces(&get_starting_pieces()));
+
+    // Playing starts once the assets are in, which there are none of here
+    app.insert_resource(NextState(Some(GameState::Playing)));
+    app.update();
+
+    app
+}
+
+fn square(name: &str) -> Square {
+    Square::from_algebraic(name).unwrap()
+}
+
+fn click(app: &mut App, name: &str) {
+    app.world.send_event(SquareClicked(square(name)));
+    app.update();
+}
+
+fn play(app: &mut App, from: &str, to: &str) {
+    click(app, from);
+    click(app, to);
+}
+
+fn press_key(app: &mut App, key_code: KeyCode) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+        });
+        app.update();
+    }
+}
+
+// The piece entity standing on a square, as opposed to the board resource
+fn get_piece_at(app: &mut App, name: &str) -> Option<(Piece, Player)> {
+    app.world
+        .query::<(&Piece, &Player, &BoardPosition)>()
+        .iter(&app.world)
+        .find(|(_, _, position)| position.square() == square(name))
+        .map(|(piece_type, player, _)| (*piece_type, *player))
+}
+
+fn count_pieces(app: &mut App) -> usize {
+    app.world.query::<&Piece>().iter(&app.world).count()
+}
+
+fn get_turn(app: &App) -> Player {
+    app.world.resource::<CurrentTurn>().0
+}
+
+// The engine searches on a thread of its own, so its move takes a few
+// frames to land
+fn wait_for_engine_move(app: &mut App, engine: Player) {
+    let timeout = Duration::from_secs(10);
+    let start = Instant::now();
+
+    while get_turn(app) == engine {
+        assert!(
+            start.elapsed() < timeout,
+            "the engine didn't move within {timeout:?}"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+        app.update();
+    }
+}
+
+#[test]
+fn moving_a_piece_updates_the_board_and_the_turn() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+
+    let board = app.world.resource::<Board>();
+    assert_eq!(board.get(square("e2")), None);
+    assert_eq!(board.get(square("e4")), Some((Piece::Pawn, Player::White)));
+    assert_eq!(get_piece_at(&mut app, "e2"), None);
+    assert_eq!(
+        get_piece_at(&mut app, "e4"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::Black);
+    assert_eq!(
+        app.world.resource::<MoveHistory>().moves,
+        vec![Move {
+            from: square("e2"),
+            to: square("e4"),
+            promotion: None,
+        }]
+    );
+    assert!(*app.world.resource::<Selection>() == Selection::Idle);
+}
+
+#[test]
+fn turns_alternate_between_players() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+    // White can't move twice in a row
+    play(&mut app, "d2", "d4");
+    assert_eq!(get_piece_at(&mut app, "d4"), None);
+    assert_eq!(get_turn(&app), Player::Black);
+
+    play(&mut app, "e7", "e5");
+    assert_eq!(
+        get_piece_at(&mut app, "e5"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert_eq!(get_turn(&app), Player::White);
+}
+
+#[test]
+fn capturing_removes_the_captured_piece() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+    play(&mut app, "d7", "d5");
+    play(&mut app, "e4", "d5");
+
+    assert_eq!(count_pieces(&mut app), 31);
+    assert_eq!(
+        get_piece_at(&mut app, "d5"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(
+        app.world.resource::<Board>().get(square("d5")),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::Black);
+}
+
+#[test]
+fn illegal_moves_are_ignored() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e5");
+    play(&mut app, "g1", "g3");
+
+    assert_eq!(
+        get_piece_at(&mut app, "e2"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(
+        get_piece_at(&mut app, "g1"),
+        Some((Piece::Knight, Player::White))
+    );
+    assert_eq!(
+        *app.world.resource::<Board>(),
+        Board::from_pieces(&get_starting_pieces())
+    );
+    assert_eq!(get_turn(&app), Player::White);
+    assert!(app.world.resource::<MoveHistory>().moves.is_empty());
+}
+
+#[test]
+fn illegal_move_events_are_dropped() {
+    let mut app = get_test_app();
+
+    // Black's pawn out of turn, a pawn three squares on, a knight made a queen
+    for (from, to, promotion) in [
+        ("e7", "e5", None),
+        ("e2", "e5", None),
+        ("g1", "f3", Some(Piece::Queen)),
+    ] {
+        app.world.send_event(MoveEvent(Move {
+            from: square(from),
+            to: square(to),
+            promotion,
+        }));
+        app.update();
+    }
+
+    assert!(app.world.resource::<MoveHistory>().moves.is_empty());
+    assert_eq!(*app.world.resource::<Board>(), get_board_after_moves(&[]));
+    assert_eq!(get_piece_at(&mut app, "e5"), None);
+    assert_eq!(get_turn(&app), Player::White);
+}
+
+#[test]
+fn opponent_pieces_cannot_be_selected() {
+    let mut app = get_test_app();
+
+    click(&mut app, "e7");
+    assert!(*app.world.resource::<Selection>() == Selection::Idle);
+
+    click(&mut app, "e5");
+    assert_eq!(
+        get_piece_at(&mut app, "e7"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert_eq!(get_turn(&app), Player::White);
+}
+
+#[test]
+fn keyboard_cursor_makes_moves() {
+    let mut app = get_test_app();
+
+    // The cursor starts on a1, so the next movable piece is the b1 knight
+    press_key(&mut app, KeyCode::Tab);
+    press_key(&mut app, KeyCode::Up);
+    press_key(&mut app, KeyCode::Up);
+    press_key(&mut app, KeyCode::Right);
+    press_key(&mut app, KeyCode::Return);
+
+    assert_eq!(
+        get_piece_at(&mut app, "c3"),
+        Some((Piece::Knight, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::Black);
+}
+
+#[test]
+fn staged_moves_wait_for_confirmation() {
+    let mut app = get_test_app();
+    app.world.resource_mut::<Settings>().confirm_moves = true;
+
+    play(&mut app, "e2", "e4");
+    assert_eq!(get_piece_at(&mut app, "e4"), None);
+    assert_eq!(get_turn(&app), Player::White);
+
+    // Another square drops the staged move
+    click(&mut app, "d2");
+    click(&mut app, "e4");
+    assert_eq!(
+        get_piece_at(&mut app, "d2"),
+        Some((Piece::Pawn, Player::White))
+    );
+
+    play(&mut app, "e2", "e4");
+    press_key(&mut app, KeyCode::Return);
+    assert_eq!(
+        get_piece_at(&mut app, "e4"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::Black);
+}
+
+#[test]
+fn pawns_on_the_last_rank_wait_for_their_promotion() {
+    let mut app = get_test_app();
+    for (from, to) in [
+        ("h2", "h4"),
+        ("g7", "g5"),
+        ("h4", "g5"),
+        ("h7", "h6"),
+        ("g5", "h6"),
+        ("e7", "e6"),
+        ("h6", "h7"),
+        ("e6", "e5"),
+    ] {
+        play(&mut app, from, to);
+    }
+
+    play(&mut app, "h7", "g8");
+    assert_eq!(
+        get_piece_at(&mut app, "h7"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::White);
+
+    // The choices run down the file from g8: queen, rook, bishop, knight
+    click(&mut app, "g6");
+    assert_eq!(get_piece_at(&mut app, "h7"), None);
+    assert_eq!(
+        get_piece_at(&mut app, "g8"),
+        Some((Piece::Bishop, Player::White))
+    );
+    assert_eq!(
+        app.world.resource::<Board>().get(square("g8")),
+        Some((Piece::Bishop, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::Black);
+}
+
+#[test]
+fn takebacks_wait_for_the_opponent() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+    play(&mut app, "d7", "d5");
+    play(&mut app, "e4", "d5");
+
+    // Refused, so the capture stands
+    press_key(&mut app, KeyCode::Back);
+    assert!(app.world.contains_resource::<TakebackRequest>());
+    press_key(&mut app, KeyCode::N);
+    assert!(!app.world.contains_resource::<TakebackRequest>());
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 3);
+
+    press_key(&mut app, KeyCode::Back);
+    press_key(&mut app, KeyCode::Y);
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 2);
+    assert_eq!(get_turn(&app), Player::White);
+    assert_eq!(
+        get_piece_at(&mut app, "d5"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert_eq!(
+        get_piece_at(&mut app, "e4"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(count_pieces(&mut app), 32);
+
+    // The pieces laid out afresh can still be moved
+    play(&mut app, "e4", "e5");
+    assert_eq!(
+        get_piece_at(&mut app, "e5"),
+        Some((Piece::Pawn, Player::White))
+    );
+}
+
+#[test]
+fn a_drag_dropped_along_with_the_selection_slides_back() {
+    let mut app = get_test_app();
+
+    click(&mut app, "e2");
+    let Selection::PieceSelected { entity, moves } = app.world.resource::<Selection>().clone()
+    else {
+        panic!("the pawn wasn't selected");
+    };
+    // Headless pieces have no sprite to carry
+    app.world
+        .entity_mut(entity)
+        .insert(TransformBundle::default());
+    app.insert_resource(Selection::Dragging { entity, moves });
+    app.update();
+
+    press_key(&mut app, KeyCode::Escape);
+    assert!(*app.world.resource::<Selection>() == Selection::Idle);
+    assert!(app.world.get::<SnapBack>(entity).is_some());
+}
+
+#[test]
+fn engine_moves_are_taken_back_in_pairs() {
+    let mut app = get_test_app();
+    app.world.resource_mut::<Settings>().engine_opponent = Some(Player::Black);
+
+    play(&mut app, "e2", "e4");
+    wait_for_engine_move(&mut app, Player::Black);
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 2);
+
+    press_key(&mut app, KeyCode::Back);
+    assert!(app.world.resource::<MoveHistory>().moves.is_empty());
+    assert_eq!(get_turn(&app), Player::White);
+    assert_eq!(
+        get_piece_at(&mut app, "e2"),
+        Some((Piece::Pawn, Player::White))
+    );
+}
+
+#[test]
+fn engine_searches_of_a_replaced_line_are_dropped() {
+    let mut app = get_test_app();
+    for (from, to) in [
+        ("e2", "e4"),
+        ("e7", "e5"),
+        ("d1", "h5"),
+        ("b8", "c6"),
+        ("f1", "c4"),
+        ("g8", "f6"),
+    ] {
+        play(&mut app, from, to);
+    }
+
+    // A sibling of Nf6, the same length, where Qxf7 gives the queen away
+    // instead of mating
+    let mut history = app.world.resource_mut::<MoveHistory>();
+    let parent = history.tree.find(&history.moves[..5]);
+    let sibling = Move {
+        from: square("d8"),
+        to: square("e7"),
+        promotion: None,
+    };
+    history.tree.add_move(parent, sibling, 0.0);
+    let mut line = history.moves[..5].to_vec();
+    line.push(sibling);
+
+    // The engine starts on the Nf6 line in the frame it is swapped out
+    app.world.resource_mut::<Settings>().engine_opponent = Some(Player::White);
+    press_key(&mut app, KeyCode::RBracket);
+    wait_for_engine_move(&mut app, Player::White);
+
+    let moves = &app.world.resource::<MoveHistory>().moves;
+    assert_eq!(moves[..6], line);
+    assert_ne!((moves[6].from, moves[6].to), (square("h5"), square("f7")));
+    assert!(get_all_legal_moves(&get_board_after_moves(&line), Player::White).contains(&moves[6]));
+}
+
+#[test]
+fn explored_moves_are_dropped_on_return() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+    press_key(&mut app, KeyCode::X);
+    assert!(app.world.contains_resource::<Exploration>());
+
+    play(&mut app, "e7", "e5");
+    play(&mut app, "g1", "f3");
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 3);
+
+    press_key(&mut app, KeyCode::X);
+    assert!(!app.world.contains_resource::<Exploration>());
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 1);
+    assert_eq!(get_turn(&app), Player::Black);
+    assert_eq!(get_piece_at(&mut app, "e5"), None);
+    assert_eq!(
+        get_piece_at(&mut app, "e7"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert_eq!(app.world.resource::<Board>().get(square("f3")), None);
+}
+
+#[test]
+fn explored_lines_can_be_stepped_through() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+    press_key(&mut app, KeyCode::X);
+    play(&mut app, "e7", "e5");
+    play(&mut app, "g1", "f3");
+
+    press_key(&mut app, KeyCode::Comma);
+    press_key(&mut app, KeyCode::Comma);
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 1);
+    assert_eq!(get_piece_at(&mut app, "e5"), None);
+
+    press_key(&mut app, KeyCode::End);
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 3);
+    assert_eq!(
+        get_piece_at(&mut app, "f3"),
+        Some((Piece::Knight, Player::White))
+    );
+}
+
+#[test]
+fn takebacks_leave_the_old_line_as_a_variation() {
+    let mut app = get_test_app();
+
+    play(&mut app, "e2", "e4");
+    press_key(&mut app, KeyCode::Back);
+    press_key(&mut app, KeyCode::Y);
+    play(&mut app, "d2", "d4");
+
+    // Over to the main line, 1. e4, and back
+    press_key(&mut app, KeyCode::LBracket);
+    assert_eq!(
+        get_piece_at(&mut app, "e4"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_piece_at(&mut app, "d4"), None);
+    press_key(&mut app, KeyCode::RBracket);
+    assert_eq!(get_piece_at(&mut app, "e4"), None);
+
+    // Deleting 1. d4 goes back to where it branched off
+    press_key(&mut app, KeyCode::Delete);
+    assert!(app.world.resource::<MoveHistory>().moves.is_empty());
+    assert_eq!(
+        app.world
+            .resource::<MoveHistory>()
+            .tree
+            .children(None)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn moves_that_leave_the_king_in_check_are_refused() {
+    let mut app = get_test_app();
+
+    play(&mut app, "d2", "d4");
+    play(&mut app, "e7", "e6");
+    play(&mut app, "e2", "e4");
+    // Check along the diagonal the d-pawn left open
+    play(&mut app, "f8", "b4");
+
+    play(&mut app, "a2", "a3");
+    assert_eq!(
+        get_piece_at(&mut app, "a2"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::White);
+
+    let toasts = app.world.resource::<Events<Toast>>();
+    assert!(toasts
+        .get_reader()
+        .iter(toasts)
+        .any(|toast| toast.message_id == "toast-king-in-check"));
+
+    play(&mut app, "c2", "c3");
+    assert_eq!(get_turn(&app), Player::Black);
+}
+
+#[test]
+fn sprt_settles_lopsided_matches() {
+    let sprt = Sprt::default();
+    let even = Tally {
+        wins: 30,
+        draws: 40,
+        losses: 30,
+    };
+    assert_eq!(even.elo().map(|(elo, _)| elo), Some(0.0));
+    assert_eq!(sprt.get_outcome(&even), None);
+
+    let lopsided = Tally {
+        wins: 300,
+        draws: 100,
+        losses: 100,
+    };
+    let (elo, margin) = lopsided.elo().unwrap();
+    assert!((elo - 147.2).abs() < 0.1, "{elo}");
+    assert!(margin > 0.0 && margin < elo);
+    assert_eq!(sprt.get_outcome(&lopsided), Some(SprtOutcome::AcceptElo1));
+
+    let reversed = Tally {
+        wins: lopsided.losses,
+        draws: lopsided.draws,
+        losses: lopsided.wins,
+    };
+    assert_eq!(sprt.get_outcome(&reversed), Some(SprtOutcome::AcceptElo0));
+}
+
+#[test]
+fn no_moves_are_taken_after_checkmate() {
+    let mut app = get_test_app();
+
+    play(&mut app, "f2", "f3");
+    play(&mut app, "e7", "e5");
+    play(&mut app, "g2", "g4");
+    play(&mut app, "d8", "h4");
+
+    assert_eq!(
+        app.world.resource::<GameOver>().0,
+        GameResult::Checkmate {
+            winner: Player::Black
+        }
+    );
+
+    play(&mut app, "a2", "a3");
+    assert_eq!(
+        get_piece_at(&mut app, "a2"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 4);
+}
+
+#[test]
+fn hand_and_brain_moves_only_the_called_piece() {
+    let mut app = get_test_app();
+    app.world.resource_mut::<Settings>().hand_and_brain = true;
+    // One frame to start it, and one for the possible moves to follow
+    app.update();
+    app.update();
+
+    // Nothing moves before the brain calls a piece
+    play(&mut app, "e2", "e4");
+    assert_eq!(get_turn(&app), Player::White);
+
+    press_key(&mut app, KeyCode::Key2);
+    play(&mut app, "e2", "e4");
+    assert_eq!(get_turn(&app), Player::White);
+    play(&mut app, "g1", "f3");
+    assert_eq!(get_turn(&app), Player::Black);
+
+    // The king has nowhere to go, so it can't be called
+    press_key(&mut app, KeyCode::Key6);
+    let toasts = app.world.resource::<Events<Toast>>();
+    assert!(toasts
+        .get_reader()
+        .iter(toasts)
+        .any(|toast| toast.message_id == "toast-brain-no-moves"));
+
+    press_key(&mut app, KeyCode::Key1);
+    play(&mut app, "e7", "e5");
+    assert_eq!(get_turn(&app), Player::White);
+    assert_eq!(
+        get_piece_at(&mut app, "e5"),
+        Some((Piece::Pawn, Player::Black))
+    );
+}
+
+#[test]
+fn puzzle_moves_are_checked_against_the_solution() {
+    let mut app = get_test_app();
+    app.insert_resource(PuzzleAttempt {
+        game: MoveHistory::default(),
+        puzzle: Puzzle {
+            moves: Vec::new(),
+            solution: ["e2e4", "e7e5", "g1f3"]
+                .map(|name| Move {
+                    from: square(&name[..2]),
+                    to: square(&name[2..]),
+                    promotion: None,
+                })
+                .to_vec(),
+        },
+        index: 0,
+        count: 1,
+        solved: false,
+    });
+
+    play(&mut app, "d2", "d4");
+    app.update();
+    assert!(app.world.resource::<MoveHistory>().moves.is_empty());
+    assert_eq!(
+        get_piece_at(&mut app, "d2"),
+        Some((Piece::Pawn, Player::White))
+    );
+
+    // The reply is played for the solver
+    play(&mut app, "e2", "e4");
+    app.update();
+    assert_eq!(
+        get_piece_at(&mut app, "e5"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert_eq!(get_turn(&app), Player::White);
+
+    play(&mut app, "g1", "f3");
+    app.update();
+    assert!(app.world.resource::<PuzzleAttempt>().solved);
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 3);
+}
+
+#[test]
+fn leaving_the_repertoire_is_noted_with_the_book_move() {
+    let mut app = get_test_app();
+    // Added after startup, so no repertoire is read from the data directory
+    app.add_plugin(RepertoirePlugin);
+    app.insert_resource(OpeningRepertoire(Repertoire::from_pgn(&read_pgn(
+        "1. e4 e5 2. Nf3 Nc6 *",
+    ))));
+
+    play(&mut app, "e2", "e4");
+    play(&mut app, "e7", "e5");
+    app.update();
+    assert!(app.world.get_resource::<RepertoireDeviation>().is_none());
+
+    play(&mut app, "d2", "d4");
+    app.update();
+    let deviation = app.world.resource::<RepertoireDeviation>();
+    assert_eq!(deviation.ply, 2);
+    assert_eq!(
+        deviation.book,
+        vec![Move {
+            from: square("g1"),
+            to: square("f3"),
+            promotion: None,
+        }]
+    );
+}
+
+#[test]
+fn maze_targets_are_collected_and_the_game_comes_back() {
+    let mut app = get_test_app();
+    play(&mut app, "e2", "e4");
+
+    press_key(&mut app, KeyCode::Z);
+    app.update();
+    let run = app.world.resource::<MazeRun>();
+    assert_eq!(run.maze.piece, Piece::Rook);
+    let maze = run.maze.clone();
+    assert_eq!(count_pieces(&mut app), 1 + maze.obstacles.len());
+
+    // Only the maze's piece has anywhere to go
+    for obstacle in &maze.obstacles {
+        assert!(app
+            .world
+            .resource::<PossibleMoves>()
+            .get(*obstacle)
+            .is_empty());
+    }
+
+    // Each target in turn, by the shortest way there, unless it was passed
+    // through on the way to an earlier one
+    let mut from = maze.start;
+    for target in &maze.targets {
+        if !app
+            .world
+            .resource::<MazeRun>()
+            .targets_left
+            .contains(target)
+        {
+            continue;
+        }
+        let distances = maze.get_distances(*target);
+        while from != *target {
+            let to = distances
+                .iter()
+                .filter(|(to, _)| app.world.resource::<PossibleMoves>().get(from).contains(to))
+                .min_by_key(|(_, distance)| *distance)
+                .unwrap()
+                .0;
+            play(&mut app, &from.to_string(), &to.to_string());
+            app.update();
+            assert_eq!(get_turn(&app), Player::White);
+            from = to;
+        }
+    }
+
+    let run = app.world.resource::<MazeRun>();
+    assert!(run.time.is_some());
+    assert!(run.targets_left.is_empty());
+    assert!(app.world.resource::<PossibleMoves>().get(from).is_empty());
+    // Nothing was added to the game
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 1);
+
+    for (key_code, state) in [
+        (KeyCode::LShift, ButtonState::Pressed),
+        (KeyCode::Z, ButtonState::Pressed),
+        (KeyCode::Z, ButtonState::Released),
+        (KeyCode::LShift, ButtonState::Released),
+    ] {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+        });
+        app.update();
+    }
+    app.update();
+
+    assert!(app.world.get_resource::<MazeRun>().is_none());
+    assert_eq!(count_pieces(&mut app), 32);
+    assert_eq!(
+        get_piece_at(&mut app, "e4"),
+        Some((Piece::Pawn, Player::White))
+    );
+    assert_eq!(get_turn(&app), Player::Black);
+}
+
+#[test]
+fn sealed_moves_stay_off_the_board_until_resumed() {
+    let dir = std::env::temp_dir().join(format!("chess-adjourn-{}", std::process::id()));
+    let adjourn_dirs = AdjournDirs {
+        data: Some(dir.join("data")),
+        config: Some(dir.join("config")),
+    };
+
+    let mut app = get_test_app();
+    app.add_plugin(AdjournPlugin { resume: false })
+        .insert_resource(adjourn_dirs.clone());
+    play(&mut app, "e2", "e4");
+
+    press_key(&mut app, KeyCode::S);
+    play(&mut app, "e7", "e5");
+    app.update();
+
+    assert_eq!(
+        get_piece_at(&mut app, "e7"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert_eq!(app.world.resource::<MoveHistory>().moves.len(), 1);
+    assert!(!app.world.resource::<Events<AppExit>>().is_empty());
+
+    let game = read_adjourned_game(&adjourn_dirs).unwrap();
+    assert_eq!(game.snapshot.history.len(), 1);
+    let sealed = Move {
+        from: square("e7"),
+        to: square("e5"),
+        promotion: None,
+    };
+    assert_eq!(game.sealed, sealed);
+
+    // As after resuming with the game set up again
+    app.insert_resource(SealedMove(game.sealed));
+    app.update();
+    app.update();
+    assert_eq!(
+        get_piece_at(&mut app, "e5"),
+        Some((Piece::Pawn, Player::Black))
+    );
+    assert!(read_adjourned_game(&adjourn_dirs).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn cursor_picking_follows_the_viewport_and_the_board() {
+    // Letterboxed to a square in a 600 by 600 window
+    let viewport = Some((Vec2::new(100.0, 50.0), Vec2::new(580.0, 530.0)));
+    assert_eq!(
+        to_viewport_position(Vec2::new(110.0, 80.0), 600.0, viewport),
+        Some(Vec2::new(10.0, 10.0))
+    );
+    assert_eq!(
+        to_viewport_position(Vec2::new(110.0, 60.0), 600.0, viewport),
+        None
+    );
+    assert_eq!(
+        to_viewport_position(Vec2::new(110.0, 60.0), 600.0, None),
+        Some(Vec2::new(110.0, 60.0))
+    );
+
+    let looking_at = |x: f32, y: f32| Ray {
+        origin: Vec3::new(x, y, 999.0),
+        direction: Vec3::NEG_Z,
+    };
+
+    // Turned half a circle around its middle, as seen from Black's side
+    let middle = Vec3::new(240.0, 240.0, 0.0);
+    let rotation = Quat::from_rotation_z(std::f32::consts::PI);
+    let flipped = GlobalTransform::from(
+        Transform::from_translation(middle - rotation * middle).with_rotation(rotation),
+    );
+    let point = get_board_point(looking_at(30.0, 30.0), &flipped).unwrap();
+    assert_eq!(get_square_at(point), square("h8"));
+
+    // Drawn at half size, further right
+    let scaled = GlobalTransform::from(
+        Transform::from_xyz(100.0, 0.0, 0.0).with_scale(Vec3::new(0.5, 0.5, 1.0)),
+    );
+    let point = get_board_point(looking_at(145.0, 75.0), &scaled).unwrap();
+    assert_eq!(get_square_at(point), square("b3"));
+
+    // Near the edge of a square, which rounding used to push over it
+    assert_eq!(get_square_at(Vec2::new(59.7, 0.0)), square("a1"));
+    assert_eq!(get_square_at(Vec2::new(-0.2, 0.0)), Square(-1, 0));
+}
+
+#[test]
+fn snapshots_keep_the_clock_and_variant_and_read_newer_formats() {
+    let dir = std::env::temp_dir().join(format!("chess-snapshot-{}", std::process::id()));
+    let path = dir.join("game.ron");
+
+    let mut app = get_test_app();
+    app.world.resource_mut::<Settings>().armageddon = true;
+    play(&mut app, "e2", "e4");
+    app.world.resource_mut::<GameTime>().0 = 12.5;
+
+    let mut pieces = app.world.query::<(&Piece, &Player, &BoardPosition)>();
+    let snapshot = GameSnapshot::capture(
+        pieces.iter(&app.world),
+        app.world.resource::<CurrentTurn>(),
+        app.world.resource::<MoveHistory>(),
+        app.world.resource::<GameTime>(),
+        app.world.resource::<Settings>(),
+    );
+    write_ron_file(&path, &snapshot).unwrap();
+
+    let mut history = MoveHistory::default();
+    let mut current_turn = CurrentTurn(Player::White);
+    let mut board_setup = BoardSetup(Vec::new());
+    let mut game_time = GameTime::default();
+    let mut settings = Settings::default();
+    GameSnapshot::read(&path).unwrap().restore(
+        &mut history,
+        &mut current_turn,
+        &mut board_setup,
+        &mut game_time,
+        &mut settings,
+    );
+    assert_eq!(history.moves.len(), 1);
+    assert_eq!(current_turn.0, Player::Black);
+    assert_eq!(game_time.0, 12.5);
+    assert!(settings.armageddon);
+
+    // Written by a later version, with a field this one doesn't know
+    std::fs::write(
+        &path,
+        "(version: 99, turn: Black, pieces: [], history: [(from: (4, 1), to: (4, 3))], \
+         clocks: (white: 300.0, black: 300.0))",
+    )
+    .unwrap();
+    let snapshot = GameSnapshot::read(&path).unwrap();
+    assert_eq!(snapshot.history.len(), 1);
+    assert_eq!(snapshot.move_times, vec![1.0]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn games_archived_in_the_same_second_are_all_kept() {
+    let dir = std::env::temp_dir().join(format!("chess-archive-{}", std::process::id()));
+
+    let mut app = get_test_app();
+    play(&mut app, "e2", "e4");
+
+    let history = app.world.resource::<MoveHistory>();
+    let settings = app.world.resource::<Settings>();
+    let paths: Vec<_> = (0..3)
+        .map(|_| archive_game_in(&dir, history, settings).unwrap())
+        .collect();
+
+    assert_ne!(paths[0], paths[1]);
+    assert_ne!(paths[1], paths[2]);
+    assert_ne!(paths[0], paths[2]);
+    for path in &paths {
+        assert_eq!(GameSnapshot::read(path).unwrap().history.len(), 1);
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use fluent::FluentArgs;
+
+use crate::{
+    locale::Localizer,
+    pieces::GameAssets,
+    ui::{spawn_status_banner, BannerKind, StatusBar},
+    ui_theme::UiTheme,
+};
+
+const TOAST_SECONDS: f32 = 3.0;
+// Older toasts are dropped beyond this, rather than being shown long after
+const MAX_QUEUED_TOASTS: usize = 4;
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .init_resource::<ToastQueue>()
+            .add_system(queue_toasts)
+            .add_system(show_toasts.after(queue_toasts));
+    }
+}
+
+// A short message for the player, translated when it is shown
+#[derive(Clone)]
+pub struct Toast {
+    pub message_id: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Toast {
+    pub fn new(message_id: &'static str) -> Self {
+        Self {
+            message_id,
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    fn text(&self, localizer: &Localizer) -> String {
+        let mut args = FluentArgs::new();
+        for (name, value) in &self.args {
+            args.set(*name, value.clone());
+        }
+
+        localizer.format(self.message_id, &args)
+    }
+}
+
+#[derive(Component)]
+struct ToastBanner;
+
+#[derive(Resource, Default)]
+struct ToastQueue {
+    waiting: VecDeque<Toast>,
+    shown: Option<(Entity, Timer)>,
+}
+
+fn queue_toasts(mut toasts: EventReader<Toast>, mut queue: ResMut<ToastQueue>) {
+    for toast in toasts.iter() {
+        if queue.waiting.len() == MAX_QUEUED_TOASTS {
+            queue.waiting.pop_front();
+        }
+        queue.waiting.push_back(toast.clone());
+    }
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut queue: ResMut<ToastQueue>,
+    localizer: Res<Localizer>,
+    game_assets: Res<GameAssets>,
+    theme: Res<UiTheme>,
+    status_bars: Query<Entity, With<StatusBar>>,
+) {
+    if let Some((entity, timer)) = &mut queue.shown {
+        if !timer.tick(time.delta()).finished() {
+            return;
+        }
+
+        commands.entity(*entity).despawn_recursive();
+        queue.shown = None;
+    }
+
+    let Ok(status_bar) = status_bars.get_single() else {
+        return;
+    };
+    let Some(toast) = queue.waiting.pop_front() else {
+        return;
+    };
+
+    let text = toast.text(&localizer);
+
+    let entity = spawn_status_banner(
+        &mut commands,
+        status_bar,
+        text,
+        BannerKind::Toast,
+        &game_assets,
+        &theme,
+        ToastBanner,
+    );
+
+    queue.shown = Some((entity, Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)));
+}
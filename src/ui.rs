@@ -0,0 +1,437 @@
+use bevy::{
+    a11y::{
+        accesskit::{NodeBuilder, Role},
+        AccessibilityNode, Focus,
+    },
+    input::mouse::MouseWheel,
+    prelude::*,
+};
+use fluent::fluent_args;
+
+use crate::{
+    analysis::{ViewedPly, EVAL_GRAPH_HEIGHT, TIME_GRAPH_HEIGHT},
+    input::{get_default_key_bindings, Action, Actions, Binding},
+    locale::Localizer,
+    pieces::GameAssets,
+    settings::Settings,
+    ui_theme::{ThemedBackground, ThemedText, UiTheme},
+};
+
+const UI_SCALE_STEP: f64 = 0.25;
+const MIN_UI_SCALE: f64 = 0.5;
+const MAX_UI_SCALE: f64 = 3.0;
+// Off the bottom of the window, or of the graphs shown after the game
+const STATUS_BAR_MARGIN: f32 = 16.0;
+const BANNER_GAP: f32 = 4.0;
+const COMMENT_WIDTH: f32 = 480.0;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_status_bar)
+            .add_system(
+                place_status_bar
+                    .run_if(resource_added::<ViewedPly>().or_else(resource_removed::<ViewedPly>())),
+            )
+            .add_system(change_ui_scale)
+            .add_system(apply_ui_scale.after(change_ui_scale))
+            .add_system(open_key_remapping)
+            .add_system(handle_key_remapping.run_if(resource_exists::<KeyRemapping>()))
+            .add_system(draw_key_remapping.after(handle_key_remapping))
+            .add_system(focus_key_remapping_row.after(draw_key_remapping));
+    }
+}
+
+#[derive(Resource)]
+pub struct KeyRemapping {
+    pub selected: usize,
+    pub waiting: bool,
+}
+
+// Along the bottom of the window, holding the banners of whatever modes,
+// prompts and toasts are up, one above the other
+#[derive(Component)]
+pub struct StatusBar;
+
+// What a banner in the status bar is for, which sets how it looks and
+// whether screen readers interrupt to read it
+#[derive(Clone, Copy)]
+pub enum BannerKind {
+    // A mode the game is in, until it is left
+    Mode,
+    // Waiting on an answer from a player
+    Prompt,
+    // A short message, gone after a few seconds
+    Toast,
+    // Text to be read at leisure, as a comment on a move
+    Comment,
+    // How the game ended
+    Result,
+}
+
+impl BannerKind {
+    fn font_size(&self) -> f32 {
+        match self {
+            BannerKind::Toast | BannerKind::Comment => 16.0,
+            BannerKind::Mode | BannerKind::Prompt => 20.0,
+            BannerKind::Result => 24.0,
+        }
+    }
+
+    fn max_width(&self) -> Val {
+        match self {
+            BannerKind::Comment => Val::Px(COMMENT_WIDTH),
+            _ => Val::Auto,
+        }
+    }
+
+    fn role(&self) -> Role {
+        match self {
+            BannerKind::Mode | BannerKind::Comment => Role::Status,
+            BannerKind::Prompt | BannerKind::Toast | BannerKind::Result => Role::Alert,
+        }
+    }
+
+    fn background(&self) -> ThemedBackground {
+        match self {
+            BannerKind::Mode => ThemedBackground(|theme| theme.banner),
+            BannerKind::Comment => ThemedBackground(|theme| theme.panel),
+            BannerKind::Prompt | BannerKind::Toast | BannerKind::Result => {
+                ThemedBackground(|theme| theme.dialog)
+            }
+        }
+    }
+
+    fn text_color(&self) -> ThemedText {
+        match self {
+            BannerKind::Mode => ThemedText(|theme| theme.banner_text),
+            _ => ThemedText(|theme| theme.text),
+        }
+    }
+}
+
+#[derive(Component)]
+struct KeyRemappingScreen;
+
+#[derive(Component)]
+struct KeyRemappingRow(usize);
+
+fn spawn_status_bar(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(STATUS_BAR_MARGIN),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(100.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        StatusBar,
+    ));
+}
+
+fn place_status_bar(
+    viewed_ply: Option<Res<ViewedPly>>,
+    mut status_bars: Query<&mut Style, With<StatusBar>>,
+) {
+    let bottom = match viewed_ply {
+        Some(_) => EVAL_GRAPH_HEIGHT + TIME_GRAPH_HEIGHT + STATUS_BAR_MARGIN,
+        None => STATUS_BAR_MARGIN,
+    };
+
+    for mut style in status_bars.iter_mut() {
+        style.position.bottom = Val::Px(bottom);
+    }
+}
+
+// Below those already up. The banner is the marked entity, which is
+// despawned to take it down
+pub fn spawn_status_banner(
+    commands: &mut Commands,
+    status_bar: Entity,
+    text: String,
+    kind: BannerKind,
+    game_assets: &GameAssets,
+    theme: &UiTheme,
+    marker: impl Component,
+) -> Entity {
+    let mut node = NodeBuilder::new(kind.role());
+    node.set_name(text.clone());
+    let (background, text_color) = (kind.background(), kind.text_color());
+
+    let banner = commands
+        .spawn((
+            TextBundle::from_section(
+                text,
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: kind.font_size(),
+                    color: (text_color.0)(theme),
+                },
+            )
+            .with_style(Style {
+                max_size: Size::width(kind.max_width()),
+                margin: UiRect::top(Val::Px(BANNER_GAP)),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            })
+            .with_background_color((background.0)(theme)),
+            background,
+            text_color,
+            AccessibilityNode::from(node),
+            marker,
+        ))
+        .id();
+    commands.entity(status_bar).add_child(banner);
+    banner
+}
+
+fn change_ui_scale(actions: Res<Actions>, mut settings: ResMut<Settings>) {
+    let step = if actions.just_pressed(Action::IncreaseUiScale) {
+        UI_SCALE_STEP
+    } else if actions.just_pressed(Action::DecreaseUiScale) {
+        -UI_SCALE_STEP
+    } else {
+        return;
+    };
+
+    settings.ui_scale = (settings.ui_scale + step).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+}
+
+fn apply_ui_scale(settings: Res<Settings>, mut ui_scale: ResMut<UiScale>) {
+    if settings.is_changed() && ui_scale.scale != settings.ui_scale {
+        ui_scale.scale = settings.ui_scale;
+    }
+}
+
+fn open_key_remapping(mut commands: Commands, actions: Res<Actions>) {
+    if actions.just_pressed(Action::RemapKeys) {
+        commands.insert_resource(KeyRemapping {
+            selected: 0,
+            waiting: false,
+        });
+    }
+}
+
+fn handle_key_remapping(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut remapping: ResMut<KeyRemapping>,
+    mut settings: ResMut<Settings>,
+) {
+    let scroll: f32 = wheel_events.iter().map(|event| event.y).sum();
+
+    // The key that opened the screen is still down this frame
+    if remapping.is_added() {
+        return;
+    }
+
+    let actions: Vec<Action> = settings.key_bindings.keys().copied().collect();
+    let action = actions[remapping.selected];
+
+    if remapping.waiting {
+        if keys.just_pressed(KeyCode::Escape) {
+            remapping.waiting = false;
+            return;
+        }
+
+        let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+        let binding = keys
+            .get_just_pressed()
+            .find(|key| ![KeyCode::LShift, KeyCode::RShift].contains(key))
+            .map(|key| {
+                if shift {
+                    Binding::ShiftKey(*key)
+                } else {
+                    Binding::Key(*key)
+                }
+            })
+            .or_else(|| {
+                buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Mouse(*button))
+            })
+            .or(if scroll > 0.0 {
+                Some(Binding::ScrollUp)
+            } else if scroll < 0.0 {
+                Some(Binding::ScrollDown)
+            } else {
+                None
+            });
+
+        if let Some(binding) = binding {
+            // One input drives one action, so take it away from any other
+            for bindings in settings.key_bindings.values_mut() {
+                bindings.retain(|other| other != &binding);
+            }
+            settings.key_bindings.insert(action, vec![binding]);
+            remapping.waiting = false;
+        }
+
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Up) {
+        remapping.selected = (remapping.selected + actions.len() - 1) % actions.len();
+    } else if keys.just_pressed(KeyCode::Down) {
+        remapping.selected = (remapping.selected + 1) % actions.len();
+    } else if keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        remapping.waiting = true;
+    } else if keys.just_pressed(KeyCode::Back) {
+        if let Some(bindings) = get_default_key_bindings().remove(&action) {
+            settings.key_bindings.insert(action, bindings);
+        }
+    } else if keys.any_just_pressed([KeyCode::Escape, KeyCode::F1]) {
+        commands.remove_resource::<KeyRemapping>();
+    }
+}
+
+fn draw_key_remapping(
+    mut commands: Commands,
+    remapping: Option<Res<KeyRemapping>>,
+    settings: Res<Settings>,
+    localizer: Res<Localizer>,
+    game_assets: Res<GameAssets>,
+    theme: Res<UiTheme>,
+    screens: Query<Entity, With<KeyRemappingScreen>>,
+    mut focus: ResMut<Focus>,
+) {
+    let redraw = match &remapping {
+        Some(remapping) => {
+            remapping.is_changed()
+                || settings.is_changed()
+                || localizer.is_changed()
+                || theme.is_changed()
+        }
+        None => !screens.is_empty(),
+    };
+
+    if !redraw {
+        return;
+    }
+
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(remapping) = remapping else {
+        // Hand focus back to the board instead of a despawned row
+        **focus = None;
+        return;
+    };
+
+    let style = TextStyle {
+        font: game_assets.font.clone(),
+        font_size: 16.0,
+        color: theme.text,
+    };
+
+    let mut rows = Vec::new();
+
+    for (i, (action, bindings)) in settings.key_bindings.iter().enumerate() {
+        let keys = if i == remapping.selected && remapping.waiting {
+            localizer.get("key-bindings-waiting")
+        } else if bindings.is_empty() {
+            localizer.get("key-bindings-unbound")
+        } else {
+            bindings
+                .iter()
+                .map(|binding| binding.label(&localizer))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let color = if i == remapping.selected {
+            settings.palette().selected_tile
+        } else {
+            theme.text
+        };
+
+        rows.push((
+            localizer.format(
+                "key-bindings-row",
+                &fluent_args!["action" => localizer.get(action.message_id()), "keys" => keys],
+            ),
+            TextStyle {
+                color,
+                ..style.clone()
+            },
+        ));
+    }
+
+    let mut dialog_node = NodeBuilder::new(Role::Dialog);
+    dialog_node.set_name(localizer.get("key-bindings-title"));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: theme.dialog.into(),
+                ..default()
+            },
+            AccessibilityNode::from(dialog_node),
+            KeyRemappingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    localizer.get("key-bindings-title"),
+                    TextStyle {
+                        font_size: 22.0,
+                        ..style.clone()
+                    },
+                ),
+                Label,
+            ));
+
+            // Separate texts so screen readers can step through the rows
+            for (i, (text, row_style)) in rows.into_iter().enumerate() {
+                parent.spawn((
+                    TextBundle::from_section(text, row_style),
+                    Label,
+                    KeyRemappingRow(i),
+                ));
+            }
+
+            parent.spawn((
+                TextBundle::from_section(
+                    localizer.get("key-bindings-help"),
+                    TextStyle {
+                        color: theme.faint_text,
+                        ..style
+                    },
+                ),
+                Label,
+            ));
+        });
+}
+
+fn focus_key_remapping_row(
+    remapping: Option<Res<KeyRemapping>>,
+    rows: Query<(Entity, &KeyRemappingRow), Added<KeyRemappingRow>>,
+    mut focus: ResMut<Focus>,
+) {
+    let Some(remapping) = remapping else {
+        return;
+    };
+
+    if let Some((entity, _)) = rows.iter().find(|(_, row)| row.0 == remapping.selected) {
+        **focus = Some(entity);
+    }
+}
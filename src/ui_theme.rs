@@ -0,0 +1,228 @@
+use std::process::Command;
+
+use bevy::{
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+// The colors of the menus and panels around the board, light or dark to
+// match the rest of the desktop unless the settings pick one
+pub struct UiThemePlugin;
+
+impl Plugin for UiThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SystemUiTheme(UiThemeMode::Dark))
+            .init_resource::<UiTheme>()
+            .add_startup_system(start_system_ui_theme_query)
+            .add_system(finish_system_ui_theme_query.before(apply_ui_theme))
+            .add_system(apply_ui_theme.run_if(
+                resource_changed::<Settings>().or_else(resource_changed::<SystemUiTheme>()),
+            ))
+            .add_systems(
+                (restyle_backgrounds, restyle_texts)
+                    .after(apply_ui_theme)
+                    .distributive_run_if(resource_changed::<UiTheme>()),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UiThemeMode {
+    Light,
+    Dark,
+}
+
+// As read once at startup, dark until the desktop answers
+#[derive(Resource)]
+struct SystemUiTheme(UiThemeMode);
+
+// Asking the desktop runs a program, which mustn't hold up the first frame
+#[derive(Component)]
+struct SystemUiThemeQuery(Task<Option<UiThemeMode>>);
+
+#[derive(Resource, Clone, PartialEq)]
+pub struct UiTheme {
+    // Behind panels that stay up next to the board
+    pub panel: Color,
+    // Behind messages and dialogs over the board
+    pub dialog: Color,
+    pub text: Color,
+    // Help lines, and other text that should stay out of the way
+    pub faint_text: Color,
+    pub comment_text: Color,
+    pub warning_text: Color,
+    // The move being looked at, in lists and graphs
+    pub highlight: Color,
+    // Stands out from both the board and the panels, for modes to notice
+    pub banner: Color,
+    pub banner_text: Color,
+    pub graph_line: Color,
+    pub graph_fill: Color,
+    pub white_bar: Color,
+    pub black_bar: Color,
+}
+
+impl UiTheme {
+    pub fn new(mode: UiThemeMode) -> Self {
+        match mode {
+            UiThemeMode::Dark => Self {
+                panel: Color::rgba(0.0, 0.0, 0.0, 0.7),
+                dialog: Color::rgba(0.0, 0.0, 0.0, 0.85),
+                text: Color::WHITE,
+                faint_text: Color::GRAY,
+                comment_text: Color::rgb(0.7, 0.8, 1.0),
+                warning_text: Color::ORANGE,
+                highlight: Color::rgba(1.0, 1.0, 0.0, 0.35),
+                banner: Color::rgba(1.0, 0.8, 0.2, 0.9),
+                banner_text: Color::BLACK,
+                graph_line: Color::WHITE,
+                graph_fill: Color::rgba(1.0, 1.0, 1.0, 0.3),
+                white_bar: Color::rgb(0.9, 0.9, 0.9),
+                black_bar: Color::rgb(0.4, 0.4, 0.4),
+            },
+            UiThemeMode::Light => Self {
+                panel: Color::rgba(1.0, 1.0, 1.0, 0.8),
+                dialog: Color::rgba(0.96, 0.96, 0.96, 0.92),
+                text: Color::rgb(0.1, 0.1, 0.1),
+                faint_text: Color::rgb(0.45, 0.45, 0.45),
+                comment_text: Color::rgb(0.15, 0.3, 0.6),
+                warning_text: Color::rgb(0.8, 0.35, 0.0),
+                highlight: Color::rgba(1.0, 0.75, 0.0, 0.45),
+                banner: Color::rgba(1.0, 0.8, 0.2, 0.9),
+                banner_text: Color::BLACK,
+                graph_line: Color::rgb(0.1, 0.1, 0.1),
+                graph_fill: Color::rgba(0.0, 0.0, 0.0, 0.25),
+                white_bar: Color::rgb(0.75, 0.75, 0.75),
+                black_bar: Color::rgb(0.25, 0.25, 0.25),
+            },
+        }
+    }
+}
+
+// The look the game always had, until the settings are read
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self::new(UiThemeMode::Dark)
+    }
+}
+
+// For nodes spawned once and kept, which are given the new theme's colors
+// when it changes. Nodes redrawn on their own just redraw
+#[derive(Component)]
+pub struct ThemedBackground(pub fn(&UiTheme) -> Color);
+
+#[derive(Component)]
+pub struct ThemedText(pub fn(&UiTheme) -> Color);
+
+fn start_system_ui_theme_query(mut commands: Commands) {
+    let task = IoTaskPool::get().spawn(async { get_system_ui_theme() });
+    commands.spawn(SystemUiThemeQuery(task));
+}
+
+fn finish_system_ui_theme_query(
+    mut commands: Commands,
+    mut queries: Query<(Entity, &mut SystemUiThemeQuery)>,
+    mut system_theme: ResMut<SystemUiTheme>,
+) {
+    for (entity, mut query) in queries.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut query.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+
+        let system_mode = result.unwrap_or(UiThemeMode::Dark);
+        info!("system ui theme: {system_mode:?}");
+        if system_theme.0 != system_mode {
+            system_theme.0 = system_mode;
+        }
+    }
+}
+
+fn apply_ui_theme(
+    settings: Res<Settings>,
+    system_theme: Res<SystemUiTheme>,
+    mut theme: ResMut<UiTheme>,
+) {
+    let new_theme = UiTheme::new(settings.ui_theme.unwrap_or(system_theme.0));
+    if *theme != new_theme {
+        *theme = new_theme;
+    }
+}
+
+fn restyle_backgrounds(
+    theme: Res<UiTheme>,
+    mut nodes: Query<(&ThemedBackground, &mut BackgroundColor)>,
+) {
+    for (themed, mut background_color) in nodes.iter_mut() {
+        background_color.0 = (themed.0)(&theme);
+    }
+}
+
+fn restyle_texts(theme: Res<UiTheme>, mut texts: Query<(&ThemedText, &mut Text)>) {
+    for (themed, mut text) in texts.iter_mut() {
+        for section in &mut text.sections {
+            section.style.color = (themed.0)(&theme);
+        }
+    }
+}
+
+// None where the desktop doesn't say, or can't be asked
+fn get_system_ui_theme() -> Option<UiThemeMode> {
+    let is_dark = if cfg!(target_os = "macos") {
+        // Only set at all in dark mode
+        let output = Command::new("defaults")
+            .args(["read", "-g", "AppleInterfaceStyle"])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout).trim() == "Dark"
+    } else if cfg!(target_os = "windows") {
+        let output = Command::new("reg")
+            .args([
+                "query",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+                "/v",
+                "AppsUseLightTheme",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .ends_with("0x0")
+    } else {
+        // GTK_THEME overrides the desktop's choice, as it does for GTK apps
+        if let Ok(gtk_theme) = std::env::var("GTK_THEME") {
+            return Some(if gtk_theme.to_lowercase().contains("dark") {
+                UiThemeMode::Dark
+            } else {
+                UiThemeMode::Light
+            });
+        }
+        let output = Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // 'default' states no preference
+        let scheme = String::from_utf8_lossy(&output.stdout);
+        if scheme.contains("default") {
+            return None;
+        }
+        scheme.contains("dark")
+    };
+
+    Some(if is_dark {
+        UiThemeMode::Dark
+    } else {
+        UiThemeMode::Light
+    })
+}
@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+use chess_core::{Move, PgnGame, PgnMove, PgnShape};
+
+use crate::{
+    analysis::ViewedPly,
+    explore::Exploration,
+    input::{Action, Actions},
+    rules::{MoveHistory, ReplaceHistoryEvent},
+    study::Study,
+    GameSet,
+};
+
+// Switching between the lines played from a position, as after a takeback
+// or while exploring, and keeping or dropping them
+pub struct VariationsPlugin;
+
+impl Plugin for VariationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(navigate_variations.in_set(GameSet::Input))
+            .add_system(
+                step_through_tree
+                    .run_if(resource_exists::<Study>().or_else(resource_exists::<Exploration>()))
+                    .run_if(not(resource_exists::<ViewedPly>()))
+                    .in_set(GameSet::Input),
+            );
+    }
+}
+
+pub type NodeId = usize;
+
+#[derive(Clone)]
+pub struct TreeNode {
+    pub mv: Move,
+    // Game time of the move, as in MoveHistory
+    pub time: f64,
+    pub parent: Option<NodeId>,
+    // The first is the main line, the rest variations
+    pub children: Vec<NodeId>,
+    // Only games read from PGN have these
+    pub comment: Option<String>,
+    pub nags: Vec<u8>,
+    pub shapes: Vec<PgnShape>,
+}
+
+// Every line played in the game, branching wherever a position was left
+// with another move. Deleted nodes stay in the arena, out of reach
+#[derive(Clone, Default)]
+pub struct MoveTree {
+    nodes: Vec<TreeNode>,
+    // The first moves of the game
+    roots: Vec<NodeId>,
+    // Before the first move, from PGN
+    pub comment: Option<String>,
+}
+
+impl MoveTree {
+    pub fn from_line(moves: &[Move], times: &[f64]) -> Self {
+        let mut tree = Self::default();
+        let mut parent = None;
+
+        for (i, mv) in moves.iter().enumerate() {
+            let time = times.get(i).copied().unwrap_or_default();
+            parent = Some(tree.add_move(parent, *mv, time));
+        }
+
+        tree
+    }
+
+    // The game's moves and variations, with their annotations. PGN has no
+    // times of its own to give them
+    pub fn from_pgn(game: &PgnGame) -> Self {
+        let mut tree = Self {
+            comment: game.comment.clone(),
+            ..default()
+        };
+        tree.add_pgn_line(None, &game.moves);
+        tree
+    }
+
+    fn add_pgn_line(&mut self, parent: Option<NodeId>, line: &[PgnMove]) {
+        let mut parent = parent;
+
+        for pgn_move in line {
+            let id = self.add_move(parent, pgn_move.mv, 0.0);
+            let node = &mut self.nodes[id];
+            node.comment = pgn_move.comment.clone();
+            node.nags = pgn_move.nags.clone();
+            node.shapes = pgn_move.shapes.clone();
+
+            for variation in &pgn_move.variations {
+                self.add_pgn_line(parent, variation);
+            }
+            parent = Some(id);
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &TreeNode {
+        &self.nodes[id]
+    }
+
+    // The moves played from a node, or from the start with None
+    pub fn children(&self, parent: Option<NodeId>) -> &[NodeId] {
+        match parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        }
+    }
+
+    // The same move from the same position goes to the node already there
+    pub fn add_move(&mut self, parent: Option<NodeId>, mv: Move, time: f64) -> NodeId {
+        if let Some(&existing) = self
+            .children(parent)
+            .iter()
+            .find(|&&child| self.nodes[child].mv == mv)
+        {
+            return existing;
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(TreeNode {
+            mv,
+            time,
+            parent,
+            children: Vec::new(),
+            comment: None,
+            nags: Vec::new(),
+            shapes: Vec::new(),
+        });
+        self.children_mut(parent).push(id);
+        id
+    }
+
+    // The node at the end of a line, None for the start or a line not in
+    // the tree
+    pub fn find(&self, moves: &[Move]) -> Option<NodeId> {
+        let mut node = None;
+
+        for mv in moves {
+            node = Some(
+                *self
+                    .children(node)
+                    .iter()
+                    .find(|&&child| self.nodes[child].mv == *mv)?,
+            );
+        }
+
+        node
+    }
+
+    // The moves and their times from the start up to a node
+    pub fn get_line(&self, node: Option<NodeId>) -> (Vec<Move>, Vec<f64>) {
+        let mut moves = Vec::new();
+        let mut times = Vec::new();
+        let mut node = node;
+
+        while let Some(id) = node {
+            moves.push(self.nodes[id].mv);
+            times.push(self.nodes[id].time);
+            node = self.nodes[id].parent;
+        }
+
+        moves.reverse();
+        times.reverse();
+        (moves, times)
+    }
+
+    // The node and the other moves from the same position, itself included
+    pub fn siblings(&self, node: NodeId) -> &[NodeId] {
+        self.children(self.nodes[node].parent)
+    }
+
+    // The first move off the main line on the way to a node
+    pub fn get_variation_start(&self, node: NodeId) -> Option<NodeId> {
+        let mut node = Some(node);
+
+        while let Some(id) = node {
+            if self.siblings(id).first() != Some(&id) {
+                return Some(id);
+            }
+            node = self.nodes[id].parent;
+        }
+
+        None
+    }
+
+    // Moves the variation a node is in one step towards the main line
+    pub fn promote(&mut self, node: NodeId) -> bool {
+        let Some(start) = self.get_variation_start(node) else {
+            return false;
+        };
+
+        let parent = self.nodes[start].parent;
+        let siblings = self.children_mut(parent);
+        let index = siblings.iter().position(|&id| id == start).unwrap();
+        siblings.swap(index - 1, index);
+        true
+    }
+
+    // Drops the variation a node is in, and returns where it branched off
+    pub fn delete_variation(&mut self, node: NodeId) -> Option<Option<NodeId>> {
+        let start = self.get_variation_start(node)?;
+
+        let parent = self.nodes[start].parent;
+        self.children_mut(parent).retain(|&id| id != start);
+        Some(parent)
+    }
+
+    fn children_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent {
+            Some(parent) => &mut self.nodes[parent].children,
+            None => &mut self.roots,
+        }
+    }
+}
+
+// Back along the line on the board and on down its main line, in a study
+// or while exploring, where there is no finished game to look back over
+fn step_through_tree(
+    actions: Res<Actions>,
+    history: Res<MoveHistory>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+) {
+    let tree = &history.tree;
+    let node = tree.find(&history.moves);
+
+    let target = if actions.just_pressed(Action::PreviousMove) {
+        node.and_then(|node| tree.node(node).parent)
+    } else if actions.just_pressed(Action::NextMove) {
+        tree.children(node).first().copied().or(node)
+    } else if actions.just_pressed(Action::FirstMove) {
+        None
+    } else if actions.just_pressed(Action::LastMove) {
+        let mut last = node;
+        while let Some(&next) = tree.children(last).first() {
+            last = Some(next);
+        }
+        last
+    } else {
+        return;
+    };
+
+    if target == node {
+        return;
+    }
+
+    let (moves, times) = tree.get_line(target);
+    replace_events.send(ReplaceHistoryEvent(MoveHistory {
+        moves,
+        times,
+        tree: tree.clone(),
+    }));
+}
+
+fn navigate_variations(
+    actions: Res<Actions>,
+    mut history: ResMut<MoveHistory>,
+    mut replace_events: EventWriter<ReplaceHistoryEvent>,
+) {
+    let Some(node) = history.tree.find(&history.moves) else {
+        return;
+    };
+
+    let target = if actions.just_pressed(Action::NextVariation)
+        || actions.just_pressed(Action::PreviousVariation)
+    {
+        let siblings = history.tree.siblings(node);
+        let index = siblings.iter().position(|&id| id == node).unwrap();
+        let next = if actions.just_pressed(Action::NextVariation) {
+            (index + 1) % siblings.len()
+        } else {
+            (index + siblings.len() - 1) % siblings.len()
+        };
+        Some(siblings[next])
+    } else if actions.just_pressed(Action::PromoteVariation) {
+        history.tree.promote(node);
+        return;
+    } else if actions.just_pressed(Action::DeleteVariation) {
+        let Some(parent) = history.tree.delete_variation(node) else {
+            return;
+        };
+        parent
+    } else {
+        return;
+    };
+
+    if target == Some(node) {
+        return;
+    }
+
+    let (moves, times) = history.tree.get_line(target);
+    replace_events.send(ReplaceHistoryEvent(MoveHistory {
+        moves,
+        times,
+        tree: history.tree.clone(),
+    }));
+}
@@ -0,0 +1,131 @@
+// The flat board seen from above, or turned into an isometric view with
+// the pieces standing up on it. Only how things are drawn changes: the
+// board keeps its layout, and clicks still find their square through the
+// camera and board transforms
+
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+use bevy::{prelude::*, sprite::Anchor};
+use chess_core::{BoardPosition, Piece, BOARD_SIZE};
+
+use crate::{
+    board::{BoardRoot, PIECE_SIZE, PIECE_Z_INDEX},
+    camera::GameCamera,
+    input::{Action, Actions, Selection, SnapBack},
+    settings::Settings,
+    GameSet,
+};
+
+// How much taller than a square the pieces stand in the isometric view
+const STANDING_HEIGHT: f32 = 1.3;
+// Where on its sprite a standing piece meets its square, up from the middle
+const STANDING_ANCHOR: f32 = -0.4;
+// Between pieces a square nearer the viewer, so the nearer is drawn on top
+const DEPTH_STEP: f32 = 0.01;
+
+pub struct BoardViewPlugin;
+
+impl Plugin for BoardViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_isometric_view)
+            .add_systems(
+                (apply_board_view, stand_up_sprites)
+                    .chain()
+                    .in_set(GameSet::Render),
+            )
+            .add_system(
+                order_pieces_by_depth
+                    .run_if(|settings: Res<Settings>| settings.isometric_view)
+                    .in_set(GameSet::Render),
+            );
+    }
+}
+
+fn toggle_isometric_view(actions: Res<Actions>, mut settings: ResMut<Settings>) {
+    if actions.just_pressed(Action::ToggleIsometricView) {
+        settings.isometric_view = !settings.isometric_view;
+    }
+}
+
+// The board turned a quarter of a right angle and the camera squashing it
+// to half its height, which is how an isometric view is drawn
+fn apply_board_view(
+    settings: Res<Settings>,
+    mut board_root: Query<&mut Transform, With<BoardRoot>>,
+    mut camera: Query<&mut Transform, (With<GameCamera>, Without<BoardRoot>)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let board_size = (PIECE_SIZE * BOARD_SIZE) as f32;
+    let center = Vec3::new(board_size / 2.0, board_size / 2.0, 0.0);
+
+    let (rotation, camera_scale) = if settings.isometric_view {
+        // Wide enough for the board's diagonal
+        (
+            Quat::from_rotation_z(FRAC_PI_4),
+            Vec3::new(SQRT_2, SQRT_2 * 2.0, 1.0),
+        )
+    } else {
+        (Quat::IDENTITY, Vec3::ONE)
+    };
+
+    for mut transform in board_root.iter_mut() {
+        // Around the middle of the board, which the camera looks at
+        transform.rotation = rotation;
+        transform.translation = center - rotation * center;
+    }
+    for mut transform in camera.iter_mut() {
+        transform.scale = camera_scale;
+    }
+}
+
+// Pieces and their ghosts turned back to face the viewer, and stretched
+// up so they stand on their squares once the camera squashes them
+fn stand_up_sprites(
+    settings: Res<Settings>,
+    mut sprites: Query<(&mut TextureAtlasSprite, &mut Transform), With<Parent>>,
+) {
+    let square_size = PIECE_SIZE as f32;
+    let (rotation, size, anchor) = if settings.isometric_view {
+        (
+            Quat::from_rotation_z(-FRAC_PI_4),
+            Vec2::new(square_size, square_size * 2.0 * STANDING_HEIGHT),
+            Anchor::Custom(Vec2::new(0.0, STANDING_ANCHOR)),
+        )
+    } else {
+        (Quat::IDENTITY, Vec2::splat(square_size), Anchor::Center)
+    };
+
+    for (mut sprite, mut transform) in sprites.iter_mut() {
+        // New ones only need it when standing, unless the view just changed
+        let restyle = settings.is_changed() || (sprite.is_added() && settings.isometric_view);
+        if !restyle {
+            continue;
+        }
+
+        transform.rotation = rotation;
+        sprite.custom_size = Some(size);
+        sprite.anchor = anchor.clone();
+    }
+}
+
+fn order_pieces_by_depth(
+    selection: Res<Selection>,
+    mut pieces: Query<(Entity, &BoardPosition, &mut Transform), (With<Piece>, Without<SnapBack>)>,
+) {
+    let farthest = (BOARD_SIZE - 1) * 2;
+
+    for (entity, position, mut transform) in pieces.iter_mut() {
+        if selection.dragged() == Some(entity) {
+            continue;
+        }
+
+        // Further up the screen the further along both files and ranks
+        let z = PIECE_Z_INDEX + DEPTH_STEP * (farthest - position.x - position.y) as f32;
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}